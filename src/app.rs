@@ -1,32 +1,429 @@
 use std::{borrow::Cow, collections::VecDeque, io::Write, sync::mpsc};
 
-use egui::{Id, KeyboardShortcut, Modal, Modifiers, Pos2, Vec2};
+use anyhow::Context as _;
+use egui::{Id, Modal, Pos2, Vec2};
+use egui_extras::syntax_highlighting::{CodeTheme, code_view_ui, highlight};
 use futures::{StreamExt, lock::Mutex};
 use image::{EncodableLayout, GenericImageView};
-use serde::Deserialize;
-use sha1::Digest;
+use itertools::Itertools;
 use strum::IntoEnumIterator;
-use tracing::{debug, error, info, trace};
+use tracing::{debug, error, info, trace, warn};
 use uuid::Uuid;
 
 use crate::{
     Rc,
-    cut::{CutAction, CutGenerator, CutTuning},
+    color::{ColorAdjustment, InkCoverage},
+    current_timestamp_millis,
+    cut::{
+        ContourStats, CutAction, CutError, CutGenerator, CutTuning, DEFAULT_CUT_PRESSURE,
+        check_cuts, svg_cut_lines,
+    },
+    project::{Batch, OpenedProject, PROJECT_EXTENSION, PROJECT_VERSION, Project, ProjectImage},
     protocol::*,
-    spawn,
+    shortcuts, spawn, spawn_blocking,
     transports::*,
     views,
 };
 
+/// Default number of packets to keep in the packet log.
+const DEFAULT_PACKET_LOG_LIMIT: usize = 1000;
+
+/// The user account sent with jobs unless another is set.
+pub const DEFAULT_USER_ACCOUNT: &str = "000000.00000000000000000000000000000000.0000";
+
+/// Times to send a job again when its data may have been corrupted in
+/// transfer.
+const MAX_TRANSFER_RETRIES: usize = 2;
+
+/// Largest encoded image the device accepts.
+const MAX_IMAGE_SIZE: usize = 1024 * 1024;
+
+/// Lowest resolution an image can be printed at before it may look blurry.
+const MIN_PRINT_DPI: f32 = 150.0;
+
+/// Extensions of the image files that can be added.
+const IMAGE_EXTENSIONS: &[&str] = &["jpg", "jpeg", "png"];
+
+/// Number of completed jobs to keep for reprinting.
+const MAX_JOB_HISTORY: usize = 10;
+
+/// How long frequent updates, like progress, may wait before being drawn.
+const THROTTLED_REPAINT: std::time::Duration = std::time::Duration::from_millis(100);
+
 #[derive(derive_more::Debug)]
 pub enum Action {
     Error(anyhow::Error),
     ChangeTransport(usize),
     TransportEvent(TransportEvent),
-    LoadedAvocadoPackets(Result<Vec<AvocadoPacket>, ProtocolError>),
+    LoggedPacket(LoggedPacket),
+    LoadedAvocadoPackets(LoadedPackets),
+    AvocadoPacketsProgress(f32),
     LoadedImage(#[debug(skip)] anyhow::Result<LoadedImage>),
+    LoadedFolder(#[debug(skip)] LoadedFolder),
+    EncodeProgress(EncodeProgress),
+    Encoded(#[debug(skip)] JobRecord),
+    EncodeFailed(anyhow::Error),
     SendProgress(f32),
+    JobCompleted(#[debug(skip)] JobRecord),
+    JobCancelled,
+    /// A job's data may have been corrupted, and it's being sent again for
+    /// this attempt.
+    TransferRetry(usize),
+    JobMediaMismatch(#[debug(skip)] JobRecord, MediaMismatch),
+    LoadedMedia(LoadedMedia),
+    DiscoveredDevices(Vec<DiscoveredDevice>),
+    LoadedRawFile(RawFileKind, #[debug(skip)] RawFile),
     Cut(CutAction),
+    ImportedCuts(#[debug(skip)] anyhow::Result<Vec<geo::MultiPolygon<f32>>>),
+    CompressedPreview(#[debug(skip)] CompressedPreview),
+    EditableJob(#[debug(skip)] JobRecord),
+    /// Files named the way the vendor app names them, ready to be saved.
+    VendorExport(#[debug(skip)] Vec<(String, Vec<u8>)>),
+    /// The response to a failed request that was sent again.
+    ResentRequest(AvocadoPacket),
+    OpenedProject(#[debug(skip)] OpenedProject),
+    /// Projects to print one after another, in order.
+    OpenedBatch(#[debug(skip)] Vec<OpenedProject>),
+    /// A project couldn't be saved or opened.
+    ProjectFailed(anyhow::Error),
+}
+
+/// Something sent to the UI that may arrive constantly.
+pub trait FrequentAction {
+    /// If this arrives constantly, like progress and status polling, but
+    /// nothing important happens if it's drawn a little later.
+    fn is_frequent(&self) -> bool;
+}
+
+impl FrequentAction for Action {
+    fn is_frequent(&self) -> bool {
+        matches!(
+            self,
+            Action::LoggedPacket(_)
+                | Action::AvocadoPacketsProgress(_)
+                | Action::EncodeProgress(_)
+                | Action::SendProgress(_)
+                | Action::Cut(CutAction::Progress { .. })
+                | Action::TransportEvent(
+                    TransportEvent::BytesWritten(_)
+                        | TransportEvent::Checksum(_)
+                        | TransportEvent::DeviceStatus(_)
+                        | TransportEvent::JobStatus(_)
+                        | TransportEvent::JobAttention(_)
+                )
+        )
+    }
+}
+
+/// Images loaded from a folder.
+pub struct LoadedFolder {
+    pub images: Vec<LoadedImage>,
+    /// Names of files that couldn't be loaded, and why.
+    pub errors: Vec<(String, anyhow::Error)>,
+}
+
+/// The most recent attempt at encoding an image within the size limit.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EncodeProgress {
+    pub quality: u8,
+    pub len: usize,
+}
+
+/// How far back writes are considered when calculating the transfer rate, so
+/// the rate and estimated time left follow changes in speed.
+const TRANSFER_RATE_WINDOW_MILLIS: u64 = 10_000;
+
+/// Progress through sending a job's data, for showing the transfer rate and
+/// how much is left.
+#[derive(Debug, Clone)]
+pub struct TransferStats {
+    /// Size of the job's data.
+    pub total_bytes: usize,
+    /// Fraction of the job's data sent.
+    pub progress: f32,
+    /// Bytes written to the transport, including packet framing.
+    pub bytes_written: usize,
+    /// Recent writes as the time and bytes written by then.
+    samples: VecDeque<(u64, usize)>,
+}
+
+impl TransferStats {
+    pub fn new(total_bytes: usize) -> Self {
+        Self {
+            total_bytes,
+            progress: 0.0,
+            bytes_written: 0,
+            samples: VecDeque::new(),
+        }
+    }
+
+    /// Record bytes written to the transport, dropping writes that are too
+    /// old to be part of the rate.
+    pub fn push(&mut self, now_millis: u64, len: usize) {
+        self.bytes_written += len;
+        self.samples.push_back((now_millis, self.bytes_written));
+
+        while self.samples.len() > 2
+            && self.samples.front().is_some_and(|(millis, _)| {
+                now_millis.saturating_sub(*millis) > TRANSFER_RATE_WINDOW_MILLIS
+            })
+        {
+            self.samples.pop_front();
+        }
+    }
+
+    /// Bytes of the job's data sent.
+    pub fn sent_bytes(&self) -> usize {
+        (self.progress * self.total_bytes as f32) as usize
+    }
+
+    /// Bytes per second written over recent writes.
+    pub fn rate(&self) -> Option<f32> {
+        let (start_millis, start) = self.samples.front()?;
+        let (end_millis, end) = self.samples.back()?;
+
+        let elapsed = end_millis.saturating_sub(*start_millis) as f32 / 1000.0;
+        let rate = (end - start) as f32 / elapsed;
+
+        (elapsed > 0.0 && rate > 0.0).then_some(rate)
+    }
+
+    /// Estimated time until all data is sent, once there's enough progress
+    /// to tell.
+    pub fn remaining(&self) -> Option<std::time::Duration> {
+        let sent_bytes = self.sent_bytes();
+        if sent_bytes == 0 {
+            return None;
+        }
+
+        // Framing adds to each packet, so scale the data left to match what
+        // will be written.
+        let overhead = self.bytes_written as f32 / sent_bytes as f32;
+        let remaining_bytes = self.total_bytes.saturating_sub(sent_bytes) as f32 * overhead;
+
+        Some(std::time::Duration::from_secs_f32(
+            remaining_bytes / self.rate()?,
+        ))
+    }
+}
+
+/// How many received packets had checksums matching our calculation.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct ChecksumTally {
+    pub matched: usize,
+    pub mismatched: usize,
+}
+
+/// A packet in the packet log.
+#[derive(Debug, Clone)]
+pub struct LoggedPacket {
+    pub packet: AvocadoPacket,
+    /// If the packet was sent to the device, rather than received from it.
+    pub sent: bool,
+    pub timestamp_millis: u64,
+}
+
+impl LoggedPacket {
+    pub fn new(packet: AvocadoPacket, sent: bool) -> Self {
+        Self {
+            packet,
+            sent,
+            timestamp_millis: current_timestamp_millis(),
+        }
+    }
+}
+
+/// A job that was sent, kept so the exact same data can be sent again.
+#[derive(Clone)]
+pub struct JobRecord {
+    pub name: String,
+    pub method: &'static str,
+    pub params: serde_json::Value,
+    pub data: Vec<u8>,
+}
+
+/// A job built ahead of sending, so its params can be edited by hand.
+pub struct EditableJob {
+    pub job: JobRecord,
+    /// The params as JSON, which may not be valid while being edited.
+    pub json: String,
+}
+
+impl EditableJob {
+    pub fn new(job: JobRecord) -> Self {
+        let json = serde_json::to_string_pretty(&job.params).unwrap_or_default();
+        Self { job, json }
+    }
+
+    /// Get the job with the edited params.
+    pub fn edited(&self) -> serde_json::Result<JobRecord> {
+        Ok(JobRecord {
+            params: serde_json::from_str(&self.json)?,
+            ..self.job.clone()
+        })
+    }
+}
+
+/// Everything needed to build a job, separate from the app state so it can
+/// be built and sent anywhere.
+pub struct PrintRequest<'a> {
+    /// The encoded image, empty for modes without printing.
+    pub encoded_image: Vec<u8>,
+    /// The encoded cut lines, only sent for modes with cutting.
+    pub plt: Vec<u8>,
+    pub mode: &'a Mode,
+    pub canvas_size: &'a CanvasSize,
+    pub image_format: &'a ImageFormat,
+    pub quirks: &'a DeviceQuirks,
+    /// Sent with jobs so the device can attribute them.
+    pub user_account: &'a str,
+    /// Copies to print, or to cut for modes without printing.
+    pub copies: usize,
+    /// Copies to cut for modes with both printing and cutting.
+    pub cut_copies: usize,
+    /// Send only the print for modes with both printing and cutting, for
+    /// reprinting media that has already been cut.
+    pub skip_cut: bool,
+    pub overrides: JobFormatOverrides,
+}
+
+/// Value for the `document-format` field of a cut job.
+const PLT_DOCUMENT_FORMAT: u16 = 18;
+
+/// Formats sent with a job in place of the usual ones, to see how the device
+/// responds to others.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct JobFormatOverrides {
+    /// Instead of the image format's document format.
+    pub document_format: Option<u16>,
+    /// Instead of [`PLT_DOCUMENT_FORMAT`].
+    pub cut_document_format: Option<u16>,
+    /// Instead of the device's hash method.
+    pub hash_method: Option<HashMethod>,
+}
+
+/// Files picked to send as a job as is, without using the canvas.
+#[derive(Default)]
+pub struct RawJob {
+    pub image: Option<RawFile>,
+    pub plt: Option<RawFile>,
+}
+
+#[derive(Debug)]
+pub struct RawFile {
+    pub name: String,
+    pub data: Vec<u8>,
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum RawFileKind {
+    Image,
+    Plt,
+}
+
+impl PrintRequest<'_> {
+    /// Build the job to send for the mode, named by the given timestamp.
+    pub fn job(&self, time: u64) -> JobRecord {
+        let mode_type = &self.mode.mode_type;
+        let canvas_size = self.canvas_size;
+
+        let plt = &self.plt;
+        trace!("plt: {}", String::from_utf8_lossy(plt));
+
+        // The hash has to be computed with the method sent, or the device
+        // will think the data is corrupted.
+        let hash_method = self
+            .overrides
+            .hash_method
+            .unwrap_or(self.quirks.hash_method);
+        let hash = hash_method.digest(&self.encoded_image);
+        debug!(
+            "calculated image hash with {}: {}",
+            hash_method.name(),
+            hex::encode(&hash)
+        );
+
+        let document_format = self
+            .overrides
+            .document_format
+            .unwrap_or(self.image_format.document_format);
+        let cut_document_format = self
+            .overrides
+            .cut_document_format
+            .unwrap_or(PLT_DOCUMENT_FORMAT);
+
+        let skip_cut = self.skip_cut && mode_type.has_printing();
+
+        // Without the cut, the print is sent as a job of its own.
+        let print_type = if skip_cut {
+            &ModeType::Print
+        } else {
+            mode_type
+        };
+
+        let print_params = serde_json::json!({
+            "media-size": canvas_size.media_size,
+            "media-type": canvas_size.media_type,
+            "job-type": print_type.job_type(),
+            "channel": print_type.channel(),
+            "file-size": self.encoded_image.len(),
+            "document-format": document_format,
+            "document-name": format!("{}.{}", time, self.image_format.encoding.extension()),
+            "hash-method": hash_method.code(),
+            "hash-value": hex::encode(hash),
+            "user-account": self.user_account,
+            "link-type": print_type.link_type(),
+            "job-send-time": time / 1000,
+            "copies": self.copies,
+        });
+
+        let name = format!("{} {}", mode_type.name(), canvas_size.name);
+
+        let (method, params, data) = if !mode_type.has_printing() {
+            let params = cut_job_params(
+                mode_type,
+                canvas_size,
+                self.copies,
+                plt.len(),
+                cut_document_format,
+                time,
+            );
+            ("cut-job", params, plt.clone())
+        } else if mode_type.has_cutting() && !skip_cut {
+            let params = serde_json::json!([
+                {
+                    "method": "print-job",
+                    "params": print_params,
+                },
+                {
+                    "method": "cut-job",
+                    "params": cut_job_params(
+                        mode_type,
+                        canvas_size,
+                        self.cut_copies,
+                        plt.len(),
+                        cut_document_format,
+                        time,
+                    ),
+                }
+            ]);
+
+            let mut buf = Vec::with_capacity(self.encoded_image.len() + plt.len());
+            buf.extend_from_slice(plt);
+            buf.extend_from_slice(&self.encoded_image);
+
+            ("combo-job", params, buf)
+        } else {
+            ("print-job", print_params, self.encoded_image.clone())
+        };
+
+        JobRecord {
+            name,
+            method,
+            params,
+            data,
+        }
+    }
 }
 
 pub struct SapodillaApp {
@@ -35,39 +432,150 @@ pub struct SapodillaApp {
 
     pub transports: Vec<Rc<Mutex<Transport>>>,
     pub transport_names: Vec<Cow<'static, str>>,
+    pub transport_available: Vec<bool>,
+    pub transport_discovery: Vec<bool>,
+    /// Devices found by the selected transport, shown so one can be picked
+    /// to connect to.
+    pub discovered_devices: Option<Vec<DiscoveredDevice>>,
     pub selected_transport_index: usize,
 
     pub transport_manager: Option<Rc<TransportManager>>,
     pub transport_status: TransportStatus,
+    /// Size of data in each packet when sending jobs.
+    pub data_size: usize,
+    /// Seconds to wait for the device to respond after connecting.
+    pub handshake_timeout: u64,
+    /// The latest step of connecting, kept after a failed connection to show
+    /// where it stopped.
+    pub connection_step: Option<ConnectionStep>,
+    /// Checksums of packets received since connecting.
+    pub checksum_tally: ChecksumTally,
+    /// The user account jobs are sent with.
+    pub user_account: String,
+    /// Send jobs again when their data seems to have been corrupted in
+    /// transfer. Off by default, as the device doesn't say why it aborted a
+    /// job and resending reprints it.
+    pub retry_corrupted_transfers: bool,
+    /// The retry attempt for the current job, if its data may have been
+    /// corrupted.
+    transfer_retry: Option<usize>,
 
     pub selected_device: usize,
     pub selected_mode: usize,
     pub selected_canvas_size: usize,
+    /// Lay out the canvas sideways, turning it back when printing.
+    pub landscape: bool,
+    /// Show the canvas turned the way it comes out of the printer.
+    pub show_as_printed: bool,
+    pub selected_image_format: usize,
     pub previous_canvas_size: Vec2,
     pub copies: usize,
+    /// Copies to cut when it's different from the printed copies.
+    pub cut_copies: usize,
+    /// If cut copies follow the printed copies.
+    pub link_copies: bool,
+    /// Print without cutting in modes that do both.
+    pub skip_cut: bool,
+    pub fit_new_images: bool,
+    /// Where new images go when they aren't fit to the safe area.
+    pub image_placement: ImagePlacement,
+    /// Space between images when automatically arranging them.
+    pub arrange_gap: f32,
+    /// Fill for the canvas wherever there's no image.
+    pub background_color: egui::Color32,
+    pub confirming_print: bool,
+    /// Ink usage of the canvas, calculated when confirming a print.
+    pub ink_coverage: Option<InkCoverage>,
+    /// Media the device reported as loaded, fetched when confirming a print.
+    pub loaded_media: Option<LoadedMedia>,
+    pub tiling: Option<TileLayout>,
 
     pub device_status: Option<(PrinterState, PrinterSubState, String)>,
     pub job_status: Option<JobStatusInfo>,
+    pub job_attention: Option<JobAttention>,
+    /// A job the device aborted because of the loaded media, kept so it can
+    /// be sent again once the right media is loaded.
+    pub media_mismatch: Option<(JobRecord, MediaMismatch)>,
+    /// An image that was too large to load, waiting on a choice to downscale.
+    pub oversized_image: Option<ImageTooLarge>,
+    /// Progress of the current data transfer, for showing the transfer rate
+    /// and time left.
+    pub transfer_stats: Option<TransferStats>,
+    /// Set while the canvas is being encoded for a job.
+    pub encode_progress: Option<EncodeProgress>,
     pub send_progress: Option<f32>,
-
-    pub packets: VecDeque<AvocadoPacket>,
+    pub job_history: VecDeque<JobRecord>,
+    pub showing_job_history: bool,
+    /// Saved projects being printed one after another.
+    pub batch: Option<Batch>,
+
+    pub packets: VecDeque<LoggedPacket>,
+    /// Show the packet log as request and response pairs.
+    pub packet_log_timeline: bool,
+    pub packet_log_limit: usize,
     pub viewing_packet: Option<AvocadoPacket>,
+    /// The response to a failed request that was sent again, until it's
+    /// dismissed.
+    pub resent_response: Option<AvocadoPacket>,
     pub cut_tuning: CutTuning,
     pub cut_shapes: Vec<geo::MultiPolygon<f32>>,
+    /// Position of each image when cut lines were last generated.
+    pub cut_layout: Vec<(egui::TextureId, egui::Rect)>,
     pub has_intersections: bool,
     pub off_canvas: bool,
     pub cut_progress: Option<(usize, usize)>,
+    pub cut_diagnostics: Vec<(usize, CutError)>,
+    pub cut_contour_stats: ContourStats,
 
     pub showing_packet_log: bool,
     pub showing_avocado_packet_debug: bool,
-    pub avocado_debug_packets: Option<Result<Vec<AvocadoPacket>, ProtocolError>>,
+    pub avocado_debug_packets: Option<LoadedPackets>,
+    pub avocado_debug_progress: Option<f32>,
+    pub showing_logs: bool,
+    pub showing_shortcuts: bool,
+    pub showing_raw_job: bool,
+    pub raw_job: RawJob,
+    pub log_level: tracing::Level,
+
+    pub color_adjustment: ColorAdjustment,
+    pub showing_color: bool,
+    color_preview: Option<ColorPreview>,
+    /// Show the encoded image when confirming a print.
+    previewing_compressed: bool,
+    compressed_preview: Option<CompressedPreview>,
+    /// Build the job when confirming a print, so its params can be edited.
+    editing_job_json: bool,
+    editable_job: Option<EditableJob>,
+    job_format_overrides: JobFormatOverrides,
 
     pub canvas_rect: egui::Rect,
     pub loaded_images: Vec<LoadedImage>,
+    /// Image clicked on the canvas, by texture so it stays selected when the
+    /// images are reordered.
+    pub selected_image: Option<egui::TextureId>,
+    /// Dragging across the selected image crops it instead of moving it.
+    pub cropping: bool,
 
     pub error: Option<anyhow::Error>,
 }
 
+/// The canvas after it's been encoded for printing, to show compression
+/// artifacts.
+pub struct CompressedPreview {
+    texture: egui::TextureHandle,
+    /// The final encoding attempt, if the image fit within the size limit.
+    progress: Option<EncodeProgress>,
+}
+
+/// Before and after thumbnails of the canvas for previewing color
+/// adjustments.
+struct ColorPreview {
+    before: image::RgbaImage,
+    adjustment: ColorAdjustment,
+    before_texture: egui::TextureHandle,
+    after_texture: egui::TextureHandle,
+}
+
 pub struct ContextSender<A> {
     tx: mpsc::Sender<A>,
     ctx: egui::Context,
@@ -86,10 +594,24 @@ impl<A> ContextSender<A> {
     pub fn new(tx: mpsc::Sender<A>, ctx: egui::Context) -> Self {
         Self { tx, ctx }
     }
+}
 
+impl<A: FrequentAction> ContextSender<A> {
+    /// Send an action and request a repaint to draw it.
+    ///
+    /// Frequent actions can wait a moment before being drawn, so their
+    /// repaints are coalesced and many in a row only cause a single repaint
+    /// instead of one each.
     pub fn send(&self, action: A) -> Result<(), mpsc::SendError<A>> {
+        let frequent = action.is_frequent();
         self.tx.send(action)?;
-        self.ctx.request_repaint();
+
+        if frequent {
+            self.ctx.request_repaint_after(THROTTLED_REPAINT);
+        } else {
+            self.ctx.request_repaint();
+        }
+
         Ok(())
     }
 }
@@ -100,45 +622,318 @@ pub struct LoadedImage {
     pub sized_texture: egui::load::SizedTexture,
 
     pub offset: Pos2,
+    /// Where the image was placed when it was added, to return to when it's
+    /// reset.
+    pub initial_offset: Pos2,
     pub scale: Vec2,
     pub scale_locked: bool,
+    pub opacity: f32,
+    /// Color adjustment applied when displaying and printing, but not when
+    /// generating cut lines.
+    pub adjustment: ColorAdjustment,
+    /// Part of the image to use, as a fraction of its size on each axis.
+    /// Offset and size are for the cropped part.
+    crop: Option<egui::Rect>,
 
     // We need this handle so egui doesn't drop the texture.
-    #[allow(dead_code)]
     handle: egui::TextureHandle,
 }
 
+/// UV coordinates covering a whole texture.
+const FULL_UV: egui::Rect = egui::Rect::from_min_max(Pos2::ZERO, Pos2::new(1.0, 1.0));
+
+/// Largest width or height of an image loaded without downscaling, well
+/// beyond any canvas but small enough to not run out of memory.
+pub const MAX_IMAGE_DIMENSION: u32 = 8192;
+
+/// Most pixels an image can have and still be loaded downscaled. The whole
+/// image has to be decoded before it's scaled down, so anything larger is
+/// refused instead of offered for downscaling.
+pub const MAX_DOWNSCALE_PIXELS: u64 = 100_000_000;
+
+/// Returned by [`LoadedImage::new`] when an image is larger than
+/// [`LoadedImage::max_dimension`], with the data so it can be loaded
+/// downscaled.
+#[derive(derive_more::Debug, thiserror::Error)]
+#[error("image is {width} × {height}, larger than the maximum of {max_dimension}")]
+pub struct ImageTooLarge {
+    pub width: u32,
+    pub height: u32,
+    pub max_dimension: u32,
+    #[debug(skip)]
+    pub data: Vec<u8>,
+}
+
 impl LoadedImage {
+    /// Load an image, checking its size before decoding it.
+    ///
+    /// Images larger than [`Self::max_dimension`] return [`ImageTooLarge`],
+    /// unless they have more than [`MAX_DOWNSCALE_PIXELS`] and can't be
+    /// loaded at all.
     pub fn new(ctx: &egui::Context, data: &[u8], offset: Option<Pos2>) -> anyhow::Result<Self> {
+        let (width, height) = Self::checked_dimensions(data)?;
+
+        let max_dimension = Self::max_dimension(ctx);
+        if width.max(height) > max_dimension {
+            return Err(ImageTooLarge {
+                width,
+                height,
+                max_dimension,
+                data: data.to_vec(),
+            }
+            .into());
+        }
+
         let im = image::load_from_memory(data)?;
         trace!("loaded image");
 
-        let (width, height) = im.dimensions();
+        Ok(Self::from_image(ctx, im.to_rgba8(), offset))
+    }
+
+    /// Load an image, scaling it down to fit within [`Self::max_dimension`].
+    ///
+    /// Decoding is limited to what an 8-bit image with
+    /// [`MAX_DOWNSCALE_PIXELS`] needs, and the image is reduced with a
+    /// thumbnail filter so it doesn't need another full sized copy.
+    pub fn new_downscaled(ctx: &egui::Context, data: &[u8]) -> anyhow::Result<Self> {
+        Self::checked_dimensions(data)?;
+
+        let mut limits = image::Limits::default();
+        limits.max_alloc = Some(MAX_DOWNSCALE_PIXELS * 4);
+
+        let mut reader =
+            image::ImageReader::new(std::io::Cursor::new(data)).with_guessed_format()?;
+        reader.limits(limits);
+
+        let max_dimension = Self::max_dimension(ctx);
+        let im = reader
+            .decode()
+            .context("image is too large to downscale")?
+            .thumbnail(max_dimension, max_dimension);
+        trace!(width = im.width(), height = im.height(), "downscaled image");
+
+        Ok(Self::from_image(ctx, im.to_rgba8(), None))
+    }
+
+    /// Read an image's size from its header, checking that it has pixels and
+    /// isn't too large to decode.
+    fn checked_dimensions(data: &[u8]) -> anyhow::Result<(u32, u32)> {
+        let (width, height) = image::ImageReader::new(std::io::Cursor::new(data))
+            .with_guessed_format()?
+            .into_dimensions()?;
         trace!(width, height, "got image size");
 
-        let im = im.to_rgba8();
-        let color_image = egui::ColorImage::from_rgba_unmultiplied(
-            [width as usize, height as usize],
-            im.as_bytes(),
-        );
+        if width == 0 || height == 0 {
+            anyhow::bail!("image is {width} × {height}, which has no pixels");
+        }
 
-        let handle = ctx.load_texture(Uuid::new_v4(), color_image, egui::TextureOptions::LINEAR);
-        let sized_texture =
-            egui::load::SizedTexture::new(handle.id(), Vec2::new(width as f32, height as f32));
-        trace!(id = ?handle.id(), "finished loading texture");
+        if u64::from(width) * u64::from(height) > MAX_DOWNSCALE_PIXELS {
+            anyhow::bail!("image is {width} × {height}, which is too large to load or downscale");
+        }
+
+        Ok((width, height))
+    }
+
+    /// Largest width or height of an image that can be loaded, which is also
+    /// limited by the largest texture the renderer supports.
+    pub fn max_dimension(ctx: &egui::Context) -> u32 {
+        let max_texture_side = ctx.input(|i| i.max_texture_side);
+        MAX_IMAGE_DIMENSION.min(u32::try_from(max_texture_side).unwrap_or(u32::MAX))
+    }
+
+    pub fn from_image(ctx: &egui::Context, im: image::RgbaImage, offset: Option<Pos2>) -> Self {
+        let (handle, sized_texture) = Self::load_texture(ctx, &im, ColorAdjustment::default());
 
-        Ok(LoadedImage {
+        let offset = offset.unwrap_or(Pos2::ZERO);
+
+        LoadedImage {
             image: im,
             sized_texture,
-            offset: offset.unwrap_or(Pos2::ZERO),
+            offset,
+            initial_offset: offset,
             scale: Vec2::splat(1.0),
             scale_locked: true,
+            opacity: 1.0,
+            adjustment: Default::default(),
+            crop: None,
             handle,
-        })
+        }
+    }
+
+    /// Create a copy of this image moved by `delta`.
+    ///
+    /// The copy gets its own texture so it doesn't depend on the original
+    /// staying loaded, and so canvas interactions can tell them apart.
+    pub fn duplicate(&self, ctx: &egui::Context, delta: Vec2) -> Self {
+        let (handle, sized_texture) = Self::load_texture(ctx, &self.image, self.adjustment);
+
+        LoadedImage {
+            image: self.image.clone(),
+            sized_texture,
+            offset: self.offset + delta,
+            initial_offset: self.initial_offset + delta,
+            scale: self.scale,
+            scale_locked: self.scale_locked,
+            opacity: self.opacity,
+            adjustment: self.adjustment,
+            crop: self.crop,
+            handle,
+        }
+    }
+
+    /// Undo any changes to the image, putting it back how it was added.
+    pub fn reset(&mut self) {
+        self.crop = None;
+        self.offset = self.initial_offset;
+        self.scale = Vec2::splat(1.0);
+        self.scale_locked = true;
+        self.opacity = 1.0;
+        self.set_adjustment(ColorAdjustment::default());
+    }
+
+    /// Update the color adjustment, updating the texture to match.
+    pub fn set_adjustment(&mut self, adjustment: ColorAdjustment) {
+        if self.adjustment == adjustment {
+            return;
+        }
+
+        self.adjustment = adjustment;
+        self.handle.set(
+            Self::color_image(&self.image, adjustment),
+            egui::TextureOptions::LINEAR,
+        );
+    }
+
+    /// The cropped image with its color adjustment applied.
+    pub fn adjusted_image(&self) -> Cow<'_, image::RgbaImage> {
+        let mut im = self.cropped_image();
+        if !self.adjustment.is_identity() {
+            self.adjustment.apply(im.to_mut());
+        }
+
+        im
+    }
+
+    /// The cropped part of the image, without color adjustment.
+    pub fn cropped_image(&self) -> Cow<'_, image::RgbaImage> {
+        match self.crop_pixels() {
+            Some([x, y, width, height]) => {
+                Cow::Owned(image::imageops::crop_imm(&self.image, x, y, width, height).to_image())
+            }
+            None => Cow::Borrowed(&self.image),
+        }
+    }
+
+    /// The cropped part of the image in pixels, as x, y, width, and height.
+    fn crop_pixels(&self) -> Option<[u32; 4]> {
+        let crop = self.crop?;
+        let (width, height) = self.image.dimensions();
+
+        let x = (crop.min.x * width as f32).round() as u32;
+        let y = (crop.min.y * height as f32).round() as u32;
+        let max_x = (crop.max.x * width as f32).round() as u32;
+        let max_y = (crop.max.y * height as f32).round() as u32;
+
+        Some([x, y, max_x - x, max_y - y])
+    }
+
+    pub fn crop(&self) -> Option<egui::Rect> {
+        self.crop
+    }
+
+    /// Crop to part of the image, as a fraction of its size on each axis,
+    /// leaving the rest of the image where it was on the canvas.
+    ///
+    /// The crop is snapped to whole pixels, and crops without any pixels are
+    /// ignored.
+    pub fn set_crop(&mut self, crop: Option<egui::Rect>) {
+        let uncropped = self.uncropped_rect();
+
+        let texture_size = self.sized_texture.size;
+        let snap = |uv: Pos2| {
+            let pixel = (uv.to_vec2() * texture_size).round();
+            (pixel / texture_size).to_pos2()
+        };
+
+        let crop = match crop {
+            Some(crop) => {
+                let crop = crop.intersect(FULL_UV);
+                let crop = egui::Rect::from_min_max(snap(crop.min), snap(crop.max));
+
+                if !crop.is_positive() {
+                    return;
+                }
+
+                (crop != FULL_UV).then_some(crop)
+            }
+            None => None,
+        };
+
+        self.crop = crop;
+        self.offset = uncropped.min + self.uv().min.to_vec2() * uncropped.size();
+    }
+
+    /// Part of the texture to draw.
+    pub fn uv(&self) -> egui::Rect {
+        self.crop.unwrap_or(FULL_UV)
+    }
+
+    /// The area of the canvas the whole image would cover if it wasn't
+    /// cropped.
+    pub fn uncropped_rect(&self) -> egui::Rect {
+        let size = self.sized_texture.size * self.scale;
+        egui::Rect::from_min_size(self.offset - self.uv().min.to_vec2() * size, size)
+    }
+
+    /// Size in pixels of the cropped part of the image, before scaling.
+    pub fn source_size(&self) -> Vec2 {
+        match self.crop_pixels() {
+            Some([_, _, width, height]) => Vec2::new(width as f32, height as f32),
+            None => self.sized_texture.size,
+        }
+    }
+
+    fn color_image(im: &image::RgbaImage, adjustment: ColorAdjustment) -> egui::ColorImage {
+        let mut im = Cow::Borrowed(im);
+        if !adjustment.is_identity() {
+            adjustment.apply(im.to_mut());
+        }
+
+        egui::ColorImage::from_rgba_unmultiplied(
+            [im.width() as usize, im.height() as usize],
+            im.as_bytes(),
+        )
+    }
+
+    fn load_texture(
+        ctx: &egui::Context,
+        im: &image::RgbaImage,
+        adjustment: ColorAdjustment,
+    ) -> (egui::TextureHandle, egui::load::SizedTexture) {
+        let (width, height) = im.dimensions();
+        let color_image = Self::color_image(im, adjustment);
+
+        let handle = ctx.load_texture(Uuid::new_v4(), color_image, egui::TextureOptions::LINEAR);
+        let sized_texture =
+            egui::load::SizedTexture::new(handle.id(), Vec2::new(width as f32, height as f32));
+        trace!(id = ?handle.id(), "finished loading texture");
+
+        (handle, sized_texture)
     }
 
     pub fn size(&self) -> Vec2 {
-        self.sized_texture.size * self.scale
+        self.source_size() * self.scale
+    }
+
+    /// Resolution the image will be printed at on a device with the given
+    /// DPI, from its source pixels and how much it's been scaled.
+    pub fn effective_dpi(&self, device_dpi: Vec2) -> f32 {
+        (device_dpi / self.scale).min_elem()
+    }
+
+    /// The area of the canvas covered by this image.
+    pub fn rect(&self) -> egui::Rect {
+        egui::Rect::from_min_size(self.offset, self.size())
     }
 
     pub fn rescale(&mut self, new_scale: Vec2) {
@@ -153,6 +948,124 @@ impl LoadedImage {
         let change = (new_size - current_size) / 2.0;
         self.offset -= change;
     }
+
+    /// Scale the image to fit within an area while preserving its aspect
+    /// ratio, and center it in that area.
+    pub fn fit_within(&mut self, area: egui::Rect) {
+        let source_size = self.source_size();
+        let scale = (area.width() / source_size.x).min(area.height() / source_size.y);
+
+        self.scale = Vec2::splat(scale);
+        self.scale_locked = true;
+        self.offset = area.center() - self.size() / 2.0;
+    }
+}
+
+/// Where newly added images are placed on the canvas.
+#[derive(Clone, Copy, Debug, PartialEq, strum::EnumIter)]
+pub enum ImagePlacement {
+    /// The top left corner of the canvas.
+    Origin,
+    /// The center of the safe area.
+    Center,
+    /// Offset from the previous image, so batch imports don't stack.
+    Cascade,
+}
+
+impl ImagePlacement {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Origin => "Origin",
+            Self::Center => "Center",
+            Self::Cascade => "Cascade",
+        }
+    }
+}
+
+/// Distance between cascaded images, in canvas pixels.
+const CASCADE_STEP: Vec2 = Vec2::splat(32.0);
+
+/// Find positions for rects of the given sizes within an area, with a gap
+/// between them, by filling rows from the tallest rect to the shortest.
+///
+/// Returns `None` if they don't all fit.
+pub fn shelf_pack(sizes: &[Vec2], area: egui::Rect, gap: f32) -> Option<Vec<Pos2>> {
+    let mut order: Vec<_> = (0..sizes.len()).collect();
+    order.sort_by(|a, b| sizes[*b].y.total_cmp(&sizes[*a].y));
+
+    let mut positions = vec![Pos2::ZERO; sizes.len()];
+    let mut cursor = area.min;
+    let mut shelf_height = 0.0f32;
+
+    for index in order {
+        let size = sizes[index];
+
+        // Start a new shelf below the current one when out of room.
+        if cursor.x > area.min.x && cursor.x + size.x > area.max.x {
+            cursor = Pos2::new(area.min.x, cursor.y + shelf_height + gap);
+            shelf_height = 0.0;
+        }
+
+        if cursor.x + size.x > area.max.x || cursor.y + size.y > area.max.y {
+            return None;
+        }
+
+        positions[index] = cursor;
+        cursor.x += size.x + gap;
+        shelf_height = shelf_height.max(size.y);
+    }
+
+    Some(positions)
+}
+
+/// Settings for filling an area with copies of an image.
+#[derive(Clone, Copy, Debug)]
+pub struct TileLayout {
+    pub index: usize,
+    pub rows: usize,
+    pub columns: usize,
+    pub gap: f32,
+}
+
+impl TileLayout {
+    /// Create a layout with as many rows and columns of the image as fit
+    /// within the area.
+    pub fn fill(index: usize, image_size: Vec2, area: egui::Rect, gap: f32) -> Self {
+        let mut layout = Self {
+            index,
+            rows: 1,
+            columns: 1,
+            gap,
+        };
+        layout.fit(image_size, area);
+        layout
+    }
+
+    /// Update the rows and columns to fill the area with the current gap.
+    pub fn fit(&mut self, image_size: Vec2, area: egui::Rect) {
+        let count = |available: f32, size: f32| {
+            (((available + self.gap) / (size + self.gap)).floor() as usize).max(1)
+        };
+
+        self.columns = count(area.width(), image_size.x);
+        self.rows = count(area.height(), image_size.y);
+    }
+
+    /// Offsets for each copy of the image, with the grid centered in the
+    /// area.
+    pub fn offsets(&self, image_size: Vec2, area: egui::Rect) -> impl Iterator<Item = Pos2> {
+        let step = image_size + Vec2::splat(self.gap);
+        let grid_size = Vec2::new(
+            step.x * self.columns as f32 - self.gap,
+            step.y * self.rows as f32 - self.gap,
+        );
+        let origin = area.center() - grid_size / 2.0;
+
+        (0..self.rows).flat_map(move |row| {
+            (0..self.columns)
+                .map(move |column| origin + Vec2::new(step.x * column as f32, step.y * row as f32))
+        })
+    }
 }
 
 impl SapodillaApp {
@@ -172,35 +1085,96 @@ impl SapodillaApp {
             transport_names: Transport::iter()
                 .map(|transport| transport.name())
                 .collect(),
-            selected_transport_index: 0,
+            transport_available: Transport::iter()
+                .map(|transport| transport.is_available())
+                .collect(),
+            transport_discovery: Transport::iter()
+                .map(|transport| transport.supports_discovery())
+                .collect(),
+            discovered_devices: None,
+            selected_transport_index: Transport::iter()
+                .position(|transport| transport.is_available())
+                .unwrap_or_default(),
 
             transport_status: TransportStatus::Disconnected,
+            data_size: DEFAULT_DATA_SIZE,
+            handshake_timeout: DEFAULT_HANDSHAKE_TIMEOUT.as_secs(),
+            connection_step: None,
+            checksum_tally: Default::default(),
+            user_account: DEFAULT_USER_ACCOUNT.to_string(),
+            retry_corrupted_transfers: false,
+            transfer_retry: None,
             transport_manager: None,
 
             selected_device: 0,
             selected_mode: 0,
             selected_canvas_size: 0,
+            landscape: false,
+            show_as_printed: false,
+            selected_image_format: 0,
             previous_canvas_size: Vec2::ZERO,
             copies: 1,
+            cut_copies: 1,
+            link_copies: true,
+            skip_cut: false,
+            fit_new_images: true,
+            image_placement: ImagePlacement::Cascade,
+            arrange_gap: DEVICES[0].average_dpi() / 16.0,
+            background_color: egui::Color32::WHITE,
+            confirming_print: false,
+            loaded_media: None,
+            ink_coverage: None,
+            tiling: None,
 
             device_status: None,
             job_status: None,
+            job_attention: None,
+            encode_progress: None,
+            transfer_stats: None,
+            media_mismatch: None,
+            oversized_image: None,
             send_progress: None,
+            job_history: VecDeque::new(),
+            showing_job_history: false,
+            batch: None,
 
             packets: Default::default(),
+            packet_log_timeline: false,
+            packet_log_limit: DEFAULT_PACKET_LOG_LIMIT,
             viewing_packet: None,
+            resent_response: None,
             cut_tuning: Default::default(),
             cut_shapes: Vec::new(),
+            cut_layout: Vec::new(),
             has_intersections: false,
             off_canvas: false,
             cut_progress: None,
+            cut_diagnostics: Vec::new(),
+            cut_contour_stats: Default::default(),
 
             showing_packet_log: false,
             showing_avocado_packet_debug: false,
             avocado_debug_packets: Default::default(),
+            avocado_debug_progress: None,
+            showing_logs: false,
+            showing_shortcuts: false,
+            showing_raw_job: false,
+            raw_job: Default::default(),
+            log_level: tracing::Level::INFO,
+
+            color_adjustment: Default::default(),
+            showing_color: false,
+            color_preview: None,
+            previewing_compressed: false,
+            compressed_preview: None,
+            editing_job_json: false,
+            editable_job: None,
+            job_format_overrides: Default::default(),
 
             canvas_rect: egui::Rect::ZERO,
             loaded_images: Default::default(),
+            selected_image: None,
+            cropping: false,
 
             error: None,
         }
@@ -213,25 +1187,352 @@ impl SapodillaApp {
             .unwrap()
     }
 
-    fn upload_image(&self, ctx: &egui::Context) {
-        let ctx = ctx.clone();
-        let tx = self.tx.clone();
+    /// If the selected transport can be used in this environment.
+    fn is_transport_available(&self) -> bool {
+        self.transport_available
+            .get(self.selected_transport_index)
+            .copied()
+            .unwrap_or_default()
+    }
 
-        spawn(async move {
-            let file = rfd::AsyncFileDialog::new()
-                .add_filter("image", &["jpg", "png"])
-                .pick_file()
-                .await;
+    /// Connect to the device using the selected transport, first asking which
+    /// device to use if the transport supports discovery.
+    ///
+    /// Does nothing if already connected or connecting, or if the transport
+    /// isn't available.
+    pub fn connect(&mut self) {
+        if matches!(
+            self.transport_status,
+            TransportStatus::Connecting | TransportStatus::Connected
+        ) {
+            debug!("already connected, ignoring connect");
+            return;
+        }
 
-            if let Some(file) = file {
-                let data = file.read().await;
+        if !self.is_transport_available() {
+            warn!("selected transport is not available, ignoring connect");
+            return;
+        }
 
-                let action = match LoadedImage::new(&ctx, &data, None) {
-                    Ok(image) => Action::LoadedImage(Ok(image)),
-                    Err(err) => Action::LoadedImage(Err(err)),
-                };
+        let supports_discovery = self
+            .transport_discovery
+            .get(self.selected_transport_index)
+            .copied()
+            .unwrap_or_default();
+
+        if !supports_discovery {
+            self.connect_to(None);
+            return;
+        }
+
+        // Let the user pick which device to use before starting.
+        let transport = self.get_transport();
+        let tx = self.tx.clone();
+        spawn(async move {
+            let action = match transport.lock().await.discover_devices().await {
+                Ok(devices) => Action::DiscoveredDevices(devices),
+                Err(err) => Action::Error(err),
+            };
+
+            if let Err(err) = tx.send(action) {
+                error!("could not send discovered devices: {err}");
+            }
+        });
+    }
+
+    /// Start the selected transport, optionally with a discovered device.
+    fn connect_to(&mut self, target: Option<DiscoveredDevice>) {
+        let tx = self.tx.clone();
+        self.connection_step = None;
+
+        let manager = TransportManager::new(self.get_transport(), target, move |event| {
+            // Timestamp packets now, as handling the action may be delayed.
+            let action = match event {
+                TransportEvent::Packet(packet) => {
+                    Action::LoggedPacket(LoggedPacket::new(packet, false))
+                }
+                TransportEvent::PacketSent(packet) => {
+                    Action::LoggedPacket(LoggedPacket::new(packet, true))
+                }
+                event => Action::TransportEvent(event),
+            };
+
+            if let Err(err) = tx.send(action) {
+                error!("could not send transport event: {err}");
+            }
+        });
+
+        if let Err(err) = manager.set_data_size(self.data_size) {
+            warn!("could not set data size: {err}");
+        }
+        manager.set_quirks(DEVICES[self.selected_device].quirks.clone());
+        manager.set_handshake_timeout(std::time::Duration::from_secs(self.handshake_timeout));
+        self.checksum_tally = Default::default();
+
+        self.transport_manager = Some(manager);
+    }
+
+    fn device_picker(&mut self, ui: &mut egui::Ui) {
+        let Some(devices) = &self.discovered_devices else {
+            return;
+        };
+
+        let mut selected = None;
+
+        let modal = Modal::new(Id::new("device_picker_modal")).show(ui.ctx(), |ui| {
+            ui.set_width(320.0);
+            ui.heading("Select Printer");
+
+            if devices.is_empty() {
+                ui.label("No devices were found.");
+            }
+
+            for device in devices {
+                let text = match &device.details {
+                    Some(details) => format!("{} ({details})", device.name),
+                    None => device.name.clone(),
+                };
+
+                if ui.button(text).clicked() {
+                    selected = Some(device.clone());
+                    ui.close();
+                }
+            }
+
+            if ui.button("Cancel").clicked() {
+                ui.close();
+            }
+        });
+
+        if modal.should_close() {
+            self.discovered_devices = None;
+        }
+
+        if let Some(device) = selected {
+            info!(id = device.id, "connecting to selected device");
+            self.connect_to(Some(device));
+        }
+    }
+
+    /// Explain a job aborted because of the loaded media, offering to send it
+    /// again once the media has been changed.
+    fn media_mismatch_prompt(&mut self, ui: &mut egui::Ui) {
+        let Some((_, mismatch)) = &self.media_mismatch else {
+            return;
+        };
+
+        let can_send = self.transport_manager.is_some()
+            && self.send_progress.is_none()
+            && self.job_status.is_none()
+            && self.batch.is_none();
+        let mut send_again = false;
+
+        let modal = Modal::new(Id::new("media_mismatch_modal")).show(ui.ctx(), |ui| {
+            ui.set_width(380.0);
+            ui.heading("Media Mismatch");
+
+            ui.label(format!(
+                "Loaded media doesn't match selected size — load {} or change the selection.",
+                mismatch.expected.describe()
+            ));
+            ui.label(format!("Loaded media: {}", mismatch.loaded.describe()));
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(can_send, egui::Button::new("Send Again"))
+                    .on_hover_text("Send the same job after loading the right media")
+                    .clicked()
+                {
+                    send_again = true;
+                    ui.close();
+                }
+
+                if ui.button("Change Selection").clicked() {
+                    ui.close();
+                }
+            });
+        });
+
+        if modal.should_close()
+            && let Some((job, _)) = self.media_mismatch.take()
+            && send_again
+        {
+            info!("sending job again after media mismatch");
+            self.start_job(job);
+        }
+    }
+
+    fn resent_response_prompt(&mut self, ui: &mut egui::Ui) {
+        let Some(packet) = &self.resent_response else {
+            return;
+        };
+
+        let modal = Modal::new(Id::new("resent_response_modal")).show(ui.ctx(), |ui| {
+            ui.set_width(380.0);
+            ui.heading("Request Sent Again");
+
+            ui.label("The device responded to the request that failed.");
+
+            if let Some(data) = packet.as_json::<serde_json::Value>() {
+                let theme = CodeTheme::from_memory(ui.ctx(), ui.style());
+                code_view_ui(
+                    ui,
+                    &theme,
+                    &serde_json::to_string_pretty(&data).unwrap_or_default(),
+                    "json",
+                );
+            }
+
+            if ui.button("Close").clicked() {
+                ui.close();
+            }
+        });
+
+        if modal.should_close() {
+            self.resent_response = None;
+        }
+    }
+
+    fn oversized_image_prompt(&mut self, ui: &mut egui::Ui) {
+        let Some(too_large) = &self.oversized_image else {
+            return;
+        };
+
+        let mut downscale = false;
+
+        let modal = Modal::new(Id::new("oversized_image_modal")).show(ui.ctx(), |ui| {
+            ui.set_width(380.0);
+            ui.heading("Image Too Large");
+
+            ui.label(format!(
+                "This image is {} × {} pixels, which is too large to load. It can be scaled \
+                down to fit within {max} × {max} pixels instead.",
+                too_large.width,
+                too_large.height,
+                max = too_large.max_dimension,
+            ));
+
+            ui.horizontal(|ui| {
+                if ui.button("Downscale").clicked() {
+                    downscale = true;
+                    ui.close();
+                }
+
+                if ui.button("Cancel").clicked() {
+                    ui.close();
+                }
+            });
+        });
+
+        if modal.should_close()
+            && let Some(too_large) = self.oversized_image.take()
+            && downscale
+        {
+            let ctx = ui.ctx().clone();
+            let tx = self.tx.clone();
+
+            spawn_blocking(move || {
+                let _ = tx.send(Action::LoadedImage(LoadedImage::new_downscaled(
+                    &ctx,
+                    &too_large.data,
+                )));
+            });
+        }
+    }
+
+    /// Disconnect, letting any data transfer in progress finish first.
+    pub fn disconnect(&mut self) {
+        self.disconnect_with(DisconnectMode::FinishTransfer);
+    }
+
+    fn disconnect_with(&mut self, mode: DisconnectMode) {
+        let Some(manager) = self.transport_manager.take() else {
+            debug!("not connected, ignoring disconnect");
+            return;
+        };
+
+        let tx = self.tx.clone();
+        spawn(async move {
+            if let Err(err) = manager.disconnect(mode).await {
+                tx.send(Action::Error(err)).unwrap();
+            }
+        });
+    }
+
+    /// Connect or disconnect, depending on the current status.
+    fn toggle_connection(&mut self) {
+        match self.transport_status {
+            TransportStatus::Connected => self.disconnect(),
+            TransportStatus::Disconnected => self.connect(),
+            TransportStatus::Connecting
+            | TransportStatus::FinishingTransfer
+            | TransportStatus::Disconnecting => (),
+        }
+    }
+
+    fn upload_image(&self, ctx: &egui::Context) {
+        let ctx = ctx.clone();
+        let tx = self.tx.clone();
+
+        spawn(async move {
+            let file = rfd::AsyncFileDialog::new()
+                .add_filter("image", IMAGE_EXTENSIONS)
+                .pick_file()
+                .await;
+
+            if let Some(file) = file {
+                let data = file.read().await;
+
+                let action = match LoadedImage::new(&ctx, &data, None) {
+                    Ok(image) => Action::LoadedImage(Ok(image)),
+                    Err(err) => Action::LoadedImage(Err(err)),
+                };
+
+                tx.send(action).unwrap();
+            }
+        });
+    }
+
+    /// Load every image in a folder.
+    #[cfg(not(target_arch = "wasm32"))]
+    fn add_folder(&self, ctx: &egui::Context) {
+        let ctx = ctx.clone();
+        let tx = self.tx.clone();
+
+        spawn(async move {
+            let Some(folder) = rfd::AsyncFileDialog::new().pick_folder().await else {
+                return;
+            };
+            let folder = folder.path().to_path_buf();
+
+            spawn_blocking(move || {
+                let action = match load_folder(&ctx, &folder) {
+                    Ok(loaded) => Action::LoadedFolder(loaded),
+                    Err(err) => Action::Error(err),
+                };
 
                 tx.send(action).unwrap();
+            });
+        });
+    }
+
+    /// Load cut lines from an SVG instead of generating them from the images.
+    fn import_cut_svg(&self, ctx: &egui::Context) {
+        let ctx = ctx.clone();
+        let tx = self.tx.clone();
+        let dpi = DEVICES[self.selected_device].dpi;
+
+        spawn(async move {
+            let file = rfd::AsyncFileDialog::new()
+                .add_filter("svg", &["svg"])
+                .pick_file()
+                .await;
+
+            if let Some(file) = file {
+                let data = file.read().await;
+
+                let _ = tx.send(Action::ImportedCuts(svg_cut_lines(&data, dpi)));
+                ctx.request_repaint();
             }
         });
     }
@@ -242,17 +1543,18 @@ impl SapodillaApp {
         let mut buf = image::ImageBuffer::from_pixel(
             canvas.x as u32,
             canvas.y as u32,
-            image::Rgba::<u8>([255, 255, 255, 255]),
+            image::Rgba::<u8>(self.background_color.to_srgba_unmultiplied()),
         );
 
         for loaded_image in &self.loaded_images {
             let image_size = loaded_image.size();
 
+            let adjusted_image = loaded_image.adjusted_image();
             let resized_image = if loaded_image.scale == Vec2::ONE {
-                Cow::Borrowed(&loaded_image.image)
+                adjusted_image
             } else {
                 Cow::Owned(image::imageops::resize(
-                    &loaded_image.image,
+                    adjusted_image.as_ref(),
                     image_size.x as u32,
                     image_size.y as u32,
                     image::imageops::FilterType::Lanczos3,
@@ -286,7 +1588,7 @@ impl SapodillaApp {
                 "calculated image position"
             );
 
-            let view = resized_image
+            let mut view = resized_image
                 .view(
                     start_x as u32,
                     start_y as u32,
@@ -295,17 +1597,132 @@ impl SapodillaApp {
                 )
                 .to_image();
 
+            if loaded_image.opacity < 1.0 {
+                for pixel in view.pixels_mut() {
+                    pixel.0[3] = (f32::from(pixel.0[3]) * loaded_image.opacity).round() as u8;
+                }
+            }
+
             image::imageops::overlay(&mut buf, &view, end_x as i64, end_y as i64);
         }
 
         buf.into()
     }
 
+    /// Render the canvas for printing, with color adjustments, the canvas
+    /// turned back from landscape, and the mode's output rotation applied.
+    fn render_print_image(&self) -> image::DynamicImage {
+        let mut im = self.render_image().into_rgba8();
+        self.color_adjustment.apply(&mut im);
+
+        let im = if self.landscape {
+            image::imageops::rotate90(&im)
+        } else {
+            im
+        };
+
+        DEVICES[self.selected_device].modes[self.selected_mode]
+            .output_rotation
+            .apply(im.into())
+    }
+
+    /// If cut lines can currently be generated, based on the selected mode and
+    /// if a generation is already running.
+    fn can_generate_cut_lines(&self) -> bool {
+        self.cut_progress.is_none()
+            && DEVICES[self.selected_device].modes[self.selected_mode]
+                .mode_type
+                .has_cutting()
+    }
+
+    /// Remove the cut lines and everything found while generating them.
+    fn clear_cuts(&mut self) {
+        self.cut_shapes.clear();
+        self.cut_layout.clear();
+        self.has_intersections = false;
+        self.off_canvas = false;
+        self.cut_diagnostics.clear();
+        self.cut_contour_stats = Default::default();
+    }
+
+    fn generate_cut_lines(&mut self) {
+        self.clear_cuts();
+        self.cut_progress = None;
+
+        self.cut_layout = self.image_layout();
+
+        let tx = self.tx.clone();
+        let mut rx = CutGenerator::start(
+            self.loaded_images.clone(),
+            self.cut_tuning.clone(),
+            self.get_canvas(),
+            DEVICES[self.selected_device].dpi,
+        );
+
+        spawn(async move {
+            while let Some(action) = rx.next().await {
+                debug!(?action, "got cut action");
+
+                if let Err(err) = tx.send(Action::Cut(action)) {
+                    error!("could not send cut action: {err}");
+                }
+            }
+        });
+    }
+
+    /// The canvas being laid out, which is turned sideways in landscape.
     pub fn get_canvas(&self) -> &'static CanvasSize {
+        if self.landscape {
+            &LANDSCAPE_CANVAS_SIZES[self.selected_device][self.selected_mode]
+                [self.selected_canvas_size]
+        } else {
+            self.device_canvas()
+        }
+    }
+
+    /// The canvas as the device sees it, regardless of orientation.
+    fn device_canvas(&self) -> &'static CanvasSize {
         &DEVICES[self.selected_device].modes[self.selected_mode].canvas_sizes
             [self.selected_canvas_size]
     }
 
+    /// Cut lines turned to match the device's canvas.
+    fn device_cut_shapes(&self) -> Cow<'_, [geo::MultiPolygon<f32>]> {
+        if !self.landscape {
+            return Cow::Borrowed(&self.cut_shapes);
+        }
+
+        // Matches turning the image clockwise in `render_print_image`.
+        let transform = Rotation::Rotate90.transform(self.get_canvas().size);
+
+        Cow::Owned(
+            self.cut_shapes
+                .iter()
+                .map(|shape| geo::AffineOps::affine_transform(shape, &transform))
+                .collect(),
+        )
+    }
+
+    /// How points on the canvas map onto the sheet as it comes out of the
+    /// printer, along with the size of the sheet.
+    ///
+    /// This follows the turns made to the rendered image. Cut lines may be
+    /// mirrored when encoded, but only to match the cutter's coordinates.
+    pub fn printed_transform(&self) -> (geo::AffineTransform<f32>, Vec2) {
+        let canvas_size = self.device_canvas().size;
+        let output_rotation =
+            DEVICES[self.selected_device].modes[self.selected_mode].output_rotation;
+
+        let mut transform = output_rotation.transform(canvas_size);
+        if self.landscape {
+            transform = Rotation::Rotate90
+                .transform(self.get_canvas().size)
+                .compose(&transform);
+        }
+
+        (transform, output_rotation.rotated_size(canvas_size))
+    }
+
     fn apply_actions(&mut self) {
         while let Ok(action) = self.rx.try_recv() {
             info!("got action: {action:?}");
@@ -313,10 +1730,11 @@ impl SapodillaApp {
             match action {
                 Action::Error(err) => {
                     self.error = Some(err);
+                    self.stop_batch("an error occurred");
 
                     if let Some(manager) = self.transport_manager.take() {
                         spawn(async move {
-                            if let Err(err) = manager.disconnect().await {
+                            if let Err(err) = manager.disconnect(DisconnectMode::Immediate).await {
                                 error!("could not disconnect from transport after error: {err}");
                             }
                         });
@@ -325,41 +1743,224 @@ impl SapodillaApp {
                 Action::ChangeTransport(index) => {
                     self.selected_transport_index = index;
                 }
+                Action::LoggedPacket(packet) => {
+                    self.packets
+                        .truncate(self.packet_log_limit.saturating_sub(1));
+                    self.packets.push_front(packet);
+                }
                 Action::TransportEvent(event) => match event {
-                    TransportEvent::Packet(packet) => {
-                        if self.packets.len() >= 999 {
-                            self.packets.pop_back();
-                        }
-
-                        self.packets.push_front(packet);
-                    }
+                    // Logged as they arrive, see `connect_to`.
+                    TransportEvent::Packet(_) | TransportEvent::PacketSent(_) => {}
                     TransportEvent::TransportStatus(status) => {
                         self.transport_status = status;
 
                         if status == TransportStatus::Disconnecting {
                             self.device_status = None;
+                            self.job_history.clear();
+                            self.stop_batch("disconnected");
                         }
                     }
+                    TransportEvent::ConnectionStep(step) => {
+                        self.connection_step = Some(step);
+                    }
                     TransportEvent::DeviceStatus(status) => {
                         self.device_status = Some(status);
                     }
                     TransportEvent::JobStatus(status) => {
                         self.job_status = Some(status);
                     }
+                    TransportEvent::JobAttention(attention) => {
+                        self.job_attention = attention;
+                    }
+                    // Only data transfers are interesting, not status polling.
+                    TransportEvent::BytesWritten(len) => {
+                        if self
+                            .transport_manager
+                            .as_ref()
+                            .is_some_and(|manager| manager.is_sending())
+                            && let Some(stats) = &mut self.transfer_stats
+                        {
+                            stats.push(current_timestamp_millis(), len);
+                        }
+                    }
+                    TransportEvent::Checksum(matched) => {
+                        if matched {
+                            self.checksum_tally.matched += 1;
+                        } else {
+                            self.checksum_tally.mismatched += 1;
+                        }
+                    }
                     TransportEvent::Error(err) => {
                         self.error = Some(err);
                     }
                 },
 
-                Action::LoadedAvocadoPackets(packets) => self.avocado_debug_packets = Some(packets),
+                Action::LoadedAvocadoPackets(packets) => {
+                    self.avocado_debug_packets = Some(packets);
+                    self.avocado_debug_progress = None;
+                }
+                Action::AvocadoPacketsProgress(pct) => {
+                    self.avocado_debug_progress = Some(pct);
+                }
+                Action::CompressedPreview(preview) => {
+                    // The confirmation may have been closed while encoding.
+                    if self.confirming_print && self.previewing_compressed {
+                        self.compressed_preview = Some(preview);
+                    }
+                }
+                Action::ResentRequest(packet) => {
+                    self.resent_response = Some(packet);
+                }
+                Action::OpenedProject(opened) => {
+                    info!(name = opened.name, "opened project");
+                    self.apply_project(opened);
+                }
+                Action::OpenedBatch(projects) => {
+                    info!(len = projects.len(), "starting batch");
+                    self.batch = Some(Batch::new(projects));
+                    self.next_batch_project();
+                }
+                Action::ProjectFailed(err) => {
+                    self.error = Some(err);
+                }
+                Action::EditableJob(job) => {
+                    self.encode_progress = None;
+
+                    if self.confirming_print && self.editing_job_json {
+                        self.editable_job = Some(EditableJob::new(job));
+                    }
+                }
+                Action::VendorExport(files) => {
+                    self.encode_progress = None;
+                    Self::save_vendor_export(files);
+                }
+                Action::ImportedCuts(res) => match res {
+                    Ok(cut_shapes) => {
+                        let (has_intersections, off_canvas) = check_cuts(
+                            &cut_shapes,
+                            self.get_canvas(),
+                            self.cut_tuning
+                                .overlap_tolerance_px(DEVICES[self.selected_device].dpi),
+                        );
+
+                        self.clear_cuts();
+                        self.cut_shapes = cut_shapes;
+                        self.has_intersections = has_intersections;
+                        self.off_canvas = off_canvas;
+
+                        // Imported cuts don't follow the images, so they're
+                        // only stale once the images change.
+                        self.cut_layout = self.image_layout();
+                    }
+                    Err(err) => self.error = Some(err),
+                },
                 Action::LoadedImage(res) => match res {
-                    Ok(image) => {
+                    Ok(mut image) => {
+                        if self.fit_new_images {
+                            image.fit_within(self.get_canvas().safe_rect());
+                        } else {
+                            image.offset = self.new_image_offset(image.size());
+                        }
+                        image.initial_offset = image.offset;
+
                         self.loaded_images.push(image);
                     }
-                    Err(err) => self.error = Some(err),
+                    Err(err) => match err.downcast::<ImageTooLarge>() {
+                        Ok(too_large) => self.oversized_image = Some(too_large),
+                        Err(err) => self.error = Some(err),
+                    },
                 },
+                Action::LoadedFolder(LoadedFolder { images, errors }) => {
+                    // Fitting every image to the canvas would pile them on
+                    // top of each other, so always cascade them instead.
+                    for mut image in images {
+                        image.offset = self.cascade_offset();
+                        image.initial_offset = image.offset;
+                        self.loaded_images.push(image);
+                    }
+
+                    if !errors.is_empty() {
+                        let failed = errors
+                            .iter()
+                            .map(|(name, err)| format!("{name}: {err}"))
+                            .join("\n");
+                        self.error = Some(anyhow::anyhow!(
+                            "could not load {} images:\n{failed}",
+                            errors.len()
+                        ));
+                    }
+                }
+                Action::EncodeProgress(progress) => {
+                    self.encode_progress = Some(progress);
+                }
+                Action::Encoded(job) => {
+                    self.encode_progress = None;
+
+                    if self.transport_manager.is_some() {
+                        self.start_job(job);
+                    } else {
+                        warn!("disconnected while encoding, not starting job");
+                    }
+                }
+                Action::EncodeFailed(err) => {
+                    self.encode_progress = None;
+                    self.error = Some(err);
+                    self.stop_batch("the canvas couldn't be encoded");
+                }
                 Action::SendProgress(pct) => {
                     self.send_progress = Some(pct);
+
+                    if let Some(stats) = &mut self.transfer_stats {
+                        stats.progress = pct;
+                    }
+                }
+                Action::TransferRetry(attempt) => {
+                    self.transfer_retry = Some(attempt);
+                    self.send_progress = None;
+
+                    if let Some(stats) = &mut self.transfer_stats {
+                        *stats = TransferStats::new(stats.total_bytes);
+                    }
+                }
+                Action::JobCompleted(record) => {
+                    self.job_status = None;
+                    self.job_attention = None;
+                    self.send_progress = None;
+                    self.transfer_retry = None;
+
+                    self.job_history.truncate(MAX_JOB_HISTORY.saturating_sub(1));
+                    self.job_history.push_front(record);
+
+                    self.next_batch_project();
+                }
+                Action::JobCancelled => {
+                    self.job_status = None;
+                    self.job_attention = None;
+                    self.send_progress = None;
+                    self.transfer_retry = None;
+                    self.stop_batch("the job was cancelled");
+                }
+                Action::JobMediaMismatch(record, mismatch) => {
+                    self.job_status = None;
+                    self.job_attention = None;
+                    self.send_progress = None;
+                    self.transfer_retry = None;
+
+                    self.media_mismatch = Some((record, mismatch));
+                    self.stop_batch("the loaded media didn't match");
+                }
+                Action::LoadedRawFile(kind, file) => match kind {
+                    RawFileKind::Image => self.raw_job.image = Some(file),
+                    RawFileKind::Plt => self.raw_job.plt = Some(file),
+                },
+                Action::DiscoveredDevices(devices) => {
+                    self.discovered_devices = Some(devices);
+                }
+                Action::LoadedMedia(media) => {
+                    // The confirmation may have been closed while waiting.
+                    if self.confirming_print {
+                        self.loaded_media = Some(media);
+                    }
                 }
                 Action::Cut(action) => match action {
                     CutAction::Progress { completed, total } => {
@@ -370,197 +1971,1496 @@ impl SapodillaApp {
                         self.cut_shapes = result.polygons;
                         self.cut_progress = None;
                         self.off_canvas = result.off_canvas;
+                        self.cut_diagnostics = result.diagnostics;
+                        self.cut_contour_stats = result.contour_stats;
+
+                        if let Some(batch) = &mut self.batch
+                            && batch.generating_cuts
+                        {
+                            batch.generating_cuts = false;
+                            self.print_batch_project();
+                        }
                     }
                 },
             }
         }
     }
 
-    fn print_canvas(&mut self) {
-        let im = self.render_image();
-        let encoded_image = encode_image(&im);
-        let encoded_image_len = encoded_image.len();
-        let mode = &DEVICES[self.selected_device].modes[self.selected_mode];
-        let canvas_size = &mode.canvas_sizes[self.selected_canvas_size];
+    /// Ask the device what media is loaded, so it can be checked against the
+    /// selected canvas size.
+    fn fetch_loaded_media(&mut self) {
+        self.loaded_media = None;
+
+        let Some(manager) = self.transport_manager.clone() else {
+            return;
+        };
+        let tx = self.tx.clone();
+
+        spawn(async move {
+            match manager.get_media().await {
+                Ok(media) => {
+                    let _ = tx.send(Action::LoadedMedia(media));
+                }
+                // Not every firmware may report media, so this is only used
+                // for a warning.
+                Err(err) => warn!("could not get loaded media: {err}"),
+            }
+        });
+    }
+
+    /// Set the media the device expects to the selected canvas size's, then
+    /// check what it reports again.
+    fn set_device_media(&mut self) {
+        self.loaded_media = None;
+
+        let Some(manager) = self.transport_manager.clone() else {
+            return;
+        };
+        let canvas_size = self.device_canvas();
+        let (media_size, media_type) = (canvas_size.media_size, canvas_size.media_type);
+        let tx = self.tx.clone();
+
+        spawn(async move {
+            if let Err(err) = manager.set_media(media_size, media_type).await {
+                error!("could not set media: {err}");
+                let _ = tx.send(Action::Error(err));
+                return;
+            }
+
+            match manager.get_media().await {
+                Ok(media) => {
+                    let _ = tx.send(Action::LoadedMedia(media));
+                }
+                Err(err) => warn!("could not get loaded media: {err}"),
+            }
+        });
+    }
+
+    fn print_canvas(&mut self) {
+        let blockers = self.print_blockers();
+        if !blockers.is_empty() {
+            self.error = Some(anyhow::anyhow!(blockers.join(" ")));
+            return;
+        }
+
+        self.build_job(Action::Encoded);
+    }
+
+    /// Render and encode the canvas into a job, which is sent back as the
+    /// given action once it's ready.
+    fn build_job(&mut self, action: fn(JobRecord) -> Action) {
+        self.build_request(move |request, time| action(request.job(time)));
+    }
+
+    /// Render and encode the canvas in the background, turning the request
+    /// and the timestamp its job is named by into the action sent back.
+    fn build_request(
+        &mut self,
+        action: impl FnOnce(PrintRequest<'_>, u64) -> Action + Send + 'static,
+    ) {
+        let device = &DEVICES[self.selected_device];
+        let mode = &device.modes[self.selected_mode];
+        let image_format = &device.image_formats[self.selected_image_format];
+
+        let canvas_size = &mode.canvas_sizes[self.selected_canvas_size];
+        if let Some(media) = &self.loaded_media
+            && !canvas_size.matches_media(media)
+        {
+            warn!(
+                ?media,
+                media_size = canvas_size.media_size,
+                media_type = canvas_size.media_type,
+                "loaded media does not match canvas size"
+            );
+        }
+
         let plt = encode_plt(
-            &self.cut_shapes,
-            DEVICES[self.selected_device]
-                .cutter_calibration
-                .clone()
-                .unwrap_or_default(),
+            &self.device_cut_shapes(),
+            device.cutter_calibration.clone().unwrap_or_default(),
             canvas_size,
+            &self.cut_tuning,
         );
+        let im = mode
+            .mode_type
+            .has_printing()
+            .then(|| self.render_print_image());
+        let copies = self.copies;
+        let cut_copies = self.cut_copies();
+        let skip_cut = self.skip_cut;
+        let user_account = self.user_account.clone();
+        let overrides = self.job_format_overrides;
+
+        // Encoding may take many attempts to fit within the size limit, so
+        // keep it off the UI thread.
+        self.encode_progress = Some(EncodeProgress::default());
+        let tx = self.tx.clone();
+        spawn_blocking(move || {
+            let encoded_image = im.map(|im| {
+                encode_image(&im, image_format.encoding, |progress| {
+                    let _ = tx.send(Action::EncodeProgress(progress));
+                })
+            });
+            let encoded_image = match encoded_image.transpose() {
+                Ok(encoded_image) => encoded_image.unwrap_or_default(),
+                Err(err) => {
+                    let _ = tx.send(Action::EncodeFailed(err));
+                    return;
+                }
+            };
+
+            let request = PrintRequest {
+                encoded_image,
+                plt,
+                mode,
+                canvas_size,
+                image_format,
+                quirks: &device.quirks,
+                user_account: &user_account,
+                copies,
+                cut_copies,
+                skip_cut,
+                overrides,
+            };
+
+            if let Err(err) = tx.send(action(request, current_timestamp_millis())) {
+                error!("could not send encoded job: {err}");
+            }
+        });
+    }
 
-        trace!("plt: {}", String::from_utf8(plt.clone()).unwrap());
+    /// Cut a small square in the middle of the canvas, to check the cutter's
+    /// calibration without using a full job.
+    fn test_cut(&mut self) {
+        let device = &DEVICES[self.selected_device];
+        let canvas_size = self.device_canvas();
 
-        let manager = self.transport_manager.clone();
+        let square = CutGenerator::test_square(canvas_size, device.dpi);
+        let plt = encode_plt(
+            &[square],
+            device.cutter_calibration.clone().unwrap_or_default(),
+            canvas_size,
+            &self.cut_tuning,
+        );
+
+        let params = cut_job_params(
+            &ModeType::CutOnly,
+            canvas_size,
+            1,
+            plt.len(),
+            self.job_format_overrides
+                .cut_document_format
+                .unwrap_or(PLT_DOCUMENT_FORMAT),
+            current_timestamp_millis(),
+        );
+        self.start_job(JobRecord {
+            name: "Test Cut".to_string(),
+            method: "cut-job",
+            params,
+            data: plt,
+        });
+    }
+
+    /// Start a job, send its data, and poll it until it completes.
+    fn start_job(&mut self, job: JobRecord) {
+        let Some(manager) = self.transport_manager.clone() else {
+            warn!("tried to start {} without a transport", job.method);
+            self.error = Some(anyhow::anyhow!("Not connected to a device."));
+            return;
+        };
         let tx = self.tx.clone();
-        let copies = self.copies;
+        let max_retries = if self.retry_corrupted_transfers {
+            MAX_TRANSFER_RETRIES
+        } else {
+            0
+        };
         self.send_progress = None;
+        self.transfer_stats = Some(TransferStats::new(job.data.len()));
+        self.transfer_retry = None;
+
+        spawn(async move {
+            let on_progress = |progress| {
+                let _ = tx.send(Action::SendProgress(progress));
+            };
+
+            // The device discards jobs with corrupted data, so the whole job
+            // has to be sent again.
+            let mut attempt = 0;
+            let res = loop {
+                match run_job(&manager, &job, on_progress).await {
+                    Err(err)
+                        if err.is::<TransferCorrupted>()
+                            && attempt < max_retries
+                            && !manager.is_disconnecting() =>
+                    {
+                        attempt += 1;
+                        warn!(attempt, "{}, sending again", err);
+                        let _ = tx.send(Action::TransferRetry(attempt));
+                    }
+                    res => break res,
+                }
+            };
 
-        let hash = sha1::Sha1::digest(&encoded_image);
-        debug!("calculated image hash: {}", hex::encode(hash));
+            // Whatever happened to the job, it stopped because of the
+            // disconnect, which isn't an error.
+            if manager.is_disconnecting() {
+                info!("{} stopped by disconnecting", job.method);
+                let _ = tx.send(Action::JobCancelled);
+                return;
+            }
 
-        let time = current_timestamp_millis();
+            match res {
+                Ok(()) => {
+                    let _ = tx.send(Action::JobCompleted(job));
+                }
+                Err(err) if err.is::<TransferCancelled>() => {
+                    info!("{} was cancelled", job.method);
+                    let _ = tx.send(Action::JobCancelled);
+                }
+                Err(err) => match err.downcast::<MediaMismatch>() {
+                    Ok(mismatch) => {
+                        let _ = tx.send(Action::JobMediaMismatch(job, mismatch));
+                    }
+                    Err(err) => {
+                        error!("could not run {}: {err}", job.method);
+                        let _ = tx.send(Action::Error(err));
+                    }
+                },
+            }
+        });
+    }
 
-        let packet_data = if mode.mode_type.has_cutting() {
-            let mut buf = Vec::with_capacity(encoded_image.len() + plt.len());
-            buf.extend_from_slice(&plt);
-            buf.extend_from_slice(&encoded_image);
-            buf
-        } else {
-            encoded_image
+    /// Send a previous job again, with exactly the same data.
+    fn reprint(&mut self, index: usize) {
+        let Some(record) = self.job_history.get(index).cloned() else {
+            return;
+        };
+
+        self.start_job(record);
+    }
+
+    /// If nothing is being encoded or sent, so a new job can be started.
+    fn can_start_job(&self) -> bool {
+        self.transport_status == TransportStatus::Connected
+            && self.encode_progress.is_none()
+            && self.send_progress.is_none()
+            && self.job_status.is_none()
+            && self.batch.is_none()
+    }
+
+    /// The canvas and its settings, for saving.
+    fn project(&self) -> anyhow::Result<Project> {
+        let device = &DEVICES[self.selected_device];
+        let mode = &device.modes[self.selected_mode];
+        let canvas_size = self.device_canvas();
+
+        Ok(Project {
+            version: PROJECT_VERSION,
+            device: device.name.clone(),
+            mode: mode.mode_type.clone(),
+            media_size: canvas_size.media_size,
+            media_type: canvas_size.media_type,
+            landscape: self.landscape,
+            document_format: device.image_formats[self.selected_image_format].document_format,
+            copies: self.copies,
+            cut_copies: self.cut_copies,
+            link_copies: self.link_copies,
+            skip_cut: self.skip_cut,
+            background_color: self.background_color.to_srgba_unmultiplied(),
+            cut_tuning: self.cut_tuning.clone(),
+            images: self
+                .loaded_images
+                .iter()
+                .map(ProjectImage::new)
+                .collect::<anyhow::Result<_>>()?,
+        })
+    }
+
+    /// Replace the canvas and its settings with a project's.
+    ///
+    /// Cut lines aren't saved, so they have to be generated again.
+    fn apply_project(&mut self, opened: OpenedProject) {
+        let OpenedProject {
+            selection,
+            project,
+            images,
+            ..
+        } = opened;
+
+        if let Some(manager) = &self.transport_manager {
+            manager.set_quirks(DEVICES[selection.device].quirks.clone());
+        }
+
+        self.selected_device = selection.device;
+        self.selected_mode = selection.mode;
+        self.selected_canvas_size = selection.canvas_size;
+        self.landscape = project.landscape;
+        self.selected_image_format = selection.image_format;
+        self.copies = project.copies;
+        self.cut_copies = project.cut_copies;
+        self.link_copies = project.link_copies;
+        self.skip_cut = project.skip_cut;
+        let [r, g, b, a] = project.background_color;
+        self.background_color = egui::Color32::from_rgba_unmultiplied(r, g, b, a);
+        self.cut_tuning = project.cut_tuning;
+
+        self.loaded_images = images;
+        self.selected_image = None;
+        self.cropping = false;
+
+        self.clear_cuts();
+    }
+
+    fn save_project(&self) {
+        let project = match self.project().and_then(|project| project.encode()) {
+            Ok(project) => project,
+            Err(err) => {
+                let _ = self.tx.send(Action::ProjectFailed(err));
+                return;
+            }
         };
+        let tx = self.tx.clone();
 
         spawn(async move {
-            let manager = manager.unwrap();
-            let id = manager.next_message_id();
-
-            let data = if mode.mode_type.has_cutting() {
-                serde_json::json!({
-                    "id": id,
-                    "method": "combo-job",
-                    "params": [
-                        {
-                            "method": "print-job",
-                            "params": {
-                                "media-size": canvas_size.media_size,
-                                "media-type": canvas_size.media_type,
-                                "job-type": mode.mode_type.job_type(),
-                                "channel": mode.mode_type.channel(),
-                                "file-size": encoded_image_len,
-                                "document-format": 9,
-                                "document-name": format!("{}.jpeg", time),
-                                "hash-method": 1,
-                                "hash-value": hex::encode(hash),
-                                "user-account": "000000.00000000000000000000000000000000.0000",
-                                "job-send-time": time / 1000,
-                                "link-type": mode.mode_type.link_type(),
-                                "copies": copies,
-                            }
-                        },
-                        {
-                            "method": "cut-job",
-                            "params": {
-                                "copies": copies,
-                                "media-size": canvas_size.media_size,
-                                "document-name": format!("{}.plt", time),
-                                "file-size": plt.len(),
-                                "channel": mode.mode_type.channel(),
-                                "media-type": canvas_size.media_type,
-                                "job-type": mode.mode_type.job_type(),
-                                "document-format": 18,
-                                "job-send-time": time / 1000,
-                            }
+            let Some(handle) = rfd::AsyncFileDialog::new()
+                .add_filter("project", &[PROJECT_EXTENSION])
+                .set_file_name(format!("project.{PROJECT_EXTENSION}"))
+                .save_file()
+                .await
+            else {
+                return;
+            };
+
+            if let Err(err) = handle.write(&project).await {
+                error!("could not write project: {err}");
+                let _ = tx.send(Action::ProjectFailed(err.into()));
+            }
+        });
+    }
+
+    fn open_project(&self, ctx: &egui::Context) {
+        let ctx = ctx.clone();
+        let tx = self.tx.clone();
+
+        spawn(async move {
+            let Some(file) = rfd::AsyncFileDialog::new()
+                .add_filter("project", &[PROJECT_EXTENSION])
+                .pick_file()
+                .await
+            else {
+                return;
+            };
+
+            let data = file.read().await;
+            let action = match OpenedProject::open(&ctx, file.file_name(), &data) {
+                Ok(opened) => Action::OpenedProject(opened),
+                Err(err) => Action::ProjectFailed(err),
+            };
+
+            let _ = tx.send(action);
+        });
+    }
+
+    /// Pick saved projects to print one after another, in order of their
+    /// names.
+    fn open_batch(&self, ctx: &egui::Context) {
+        let ctx = ctx.clone();
+        let tx = self.tx.clone();
+
+        spawn(async move {
+            let Some(mut files) = rfd::AsyncFileDialog::new()
+                .add_filter("project", &[PROJECT_EXTENSION])
+                .pick_files()
+                .await
+            else {
+                return;
+            };
+            files.sort_by_key(|file| file.file_name());
+
+            // Check every project opens before printing any of them.
+            let mut projects = Vec::with_capacity(files.len());
+            for file in files {
+                let name = file.file_name();
+                let data = file.read().await;
+
+                match OpenedProject::open(&ctx, name.clone(), &data) {
+                    Ok(opened) => projects.push(opened),
+                    Err(err) => {
+                        let _ = tx.send(Action::ProjectFailed(anyhow::anyhow!(
+                            "could not open {name}: {err}"
+                        )));
+                        return;
+                    }
+                }
+            }
+
+            let _ = tx.send(Action::OpenedBatch(projects));
+        });
+    }
+
+    /// Put the next project in the batch on the canvas and start printing it,
+    /// generating its cut lines first if it needs them.
+    fn next_batch_project(&mut self) {
+        let Some(batch) = &mut self.batch else {
+            return;
+        };
+
+        let Some(opened) = batch.queue.pop_front() else {
+            info!(total = batch.total, "finished batch");
+            self.batch = None;
+            return;
+        };
+
+        batch.position += 1;
+        batch.name = opened.name.clone();
+        info!(
+            position = batch.position,
+            total = batch.total,
+            name = batch.name,
+            "starting next project in batch"
+        );
+
+        self.apply_project(opened);
+
+        let needs_cuts = DEVICES[self.selected_device].modes[self.selected_mode]
+            .mode_type
+            .has_cutting()
+            && !self.skipping_cut();
+        if needs_cuts {
+            if let Some(batch) = &mut self.batch {
+                batch.generating_cuts = true;
+            }
+
+            self.generate_cut_lines();
+        } else {
+            self.print_batch_project();
+        }
+    }
+
+    /// Print the batch's current project, which is already on the canvas.
+    fn print_batch_project(&mut self) {
+        let blockers = self.print_blockers();
+        if !blockers.is_empty() {
+            let name = self
+                .batch
+                .as_ref()
+                .map(|batch| batch.name.clone())
+                .unwrap_or_default();
+            self.error = Some(anyhow::anyhow!("{name}: {}", blockers.join(" ")));
+            self.stop_batch("the project can't be printed");
+            return;
+        }
+
+        self.build_job(Action::Encoded);
+    }
+
+    /// Stop starting projects from the batch, leaving any job that's running.
+    fn stop_batch(&mut self, reason: &str) {
+        if let Some(batch) = self.batch.take() {
+            warn!(
+                position = batch.position,
+                total = batch.total,
+                "stopping batch because {reason}"
+            );
+        }
+    }
+
+    fn image_layout(&self) -> Vec<(egui::TextureId, egui::Rect)> {
+        self.loaded_images
+            .iter()
+            .map(|image| (image.sized_texture.id, image.rect()))
+            .collect()
+    }
+
+    /// Moves between the end of each cut and the start of the next, in the
+    /// order they're sent to the cutter, on the canvas being laid out.
+    pub fn travel_moves(&self) -> Vec<(Pos2, Pos2)> {
+        let mirror = DEVICES[self.selected_device]
+            .cutter_calibration
+            .as_ref()
+            .and_then(|calibration| calibration.mirror);
+        let moves = plt_travel_moves(&self.device_cut_shapes(), self.device_canvas(), mirror);
+
+        if !self.landscape {
+            return moves;
+        }
+
+        // Turn back from the device's canvas, undoing `device_cut_shapes`.
+        let Some(transform) = Rotation::Rotate90
+            .transform(self.get_canvas().size)
+            .inverse()
+        else {
+            return Vec::new();
+        };
+        let turn_back = |pos: Pos2| {
+            let coord = transform.apply(geo::coord! { x: pos.x, y: pos.y });
+            Pos2::new(coord.x, coord.y)
+        };
+
+        moves
+            .into_iter()
+            .map(|(start, end)| (turn_back(start), turn_back(end)))
+            .collect()
+    }
+
+    /// If images have been added, removed, moved, or resized since the cut
+    /// lines were generated.
+    fn cuts_stale(&self) -> bool {
+        !self.cut_shapes.is_empty() && self.cut_layout != self.image_layout()
+    }
+
+    /// If the cut will be left out of a job in a mode that prints and cuts.
+    fn skipping_cut(&self) -> bool {
+        self.skip_cut
+            && DEVICES[self.selected_device].modes[self.selected_mode]
+                .mode_type
+                .has_printing()
+    }
+
+    /// Where to put a new image of the given size, based on the placement
+    /// setting.
+    fn new_image_offset(&self, size: Vec2) -> Pos2 {
+        let canvas = self.get_canvas();
+
+        match self.image_placement {
+            ImagePlacement::Origin => Pos2::ZERO,
+            ImagePlacement::Center => canvas.safe_rect().center() - size / 2.0,
+            ImagePlacement::Cascade => self.cascade_offset(),
+        }
+    }
+
+    /// A little below and to the right of the last image.
+    fn cascade_offset(&self) -> Pos2 {
+        let canvas = self.get_canvas();
+        let offset = self
+            .loaded_images
+            .last()
+            .map_or(Pos2::ZERO, |previous| previous.offset + CASCADE_STEP);
+
+        // Start over once images would begin falling off the canvas.
+        if offset.x >= canvas.size.x - CASCADE_STEP.x || offset.y >= canvas.size.y - CASCADE_STEP.y
+        {
+            Pos2::ZERO
+        } else {
+            offset
+        }
+    }
+
+    /// Copies to cut for jobs with both printing and cutting.
+    fn cut_copies(&self) -> usize {
+        if self.link_copies {
+            self.copies
+        } else {
+            self.cut_copies
+        }
+    }
+
+    /// Reasons the canvas can't be sent with the selected mode.
+    fn print_blockers(&self) -> Vec<&'static str> {
+        let device = &DEVICES[self.selected_device];
+        let mode_type = &device.modes[self.selected_mode].mode_type;
+        let mut blockers = Vec::new();
+
+        let copies = [self.copies, self.cut_copies()];
+        if copies
+            .iter()
+            .any(|copies| !(1..=device.max_copies).contains(copies))
+        {
+            blockers.push("Copies are outside of what the device allows.");
+        }
+
+        if mode_type.has_printing() && self.loaded_images.is_empty() {
+            blockers.push("There are no images to print.");
+        }
+
+        if mode_type.has_cutting() && !self.skipping_cut() {
+            if self.cut_shapes.is_empty() {
+                blockers.push("Cut lines have not been generated.");
+            } else if self.cuts_stale() {
+                blockers.push("Images have changed since cut lines were generated.");
+            }
+        }
+
+        blockers
+    }
+
+    /// Count the images that extend past the safe area of the canvas.
+    fn images_outside_safe_area(&self) -> usize {
+        let safe_rect = self.get_canvas().safe_rect();
+
+        self.loaded_images
+            .iter()
+            .filter(|image| !safe_rect.contains_rect(image.rect()))
+            .count()
+    }
+
+    /// The lowest resolution any image will be printed at, if it's below
+    /// [`MIN_PRINT_DPI`], with the number of images below it.
+    fn low_resolution_images(&self) -> Option<(usize, f32)> {
+        let dpi = DEVICES[self.selected_device].dpi;
+
+        let low: Vec<_> = self
+            .loaded_images
+            .iter()
+            .map(|image| image.effective_dpi(dpi))
+            .filter(|effective_dpi| *effective_dpi < MIN_PRINT_DPI)
+            .collect();
+
+        let lowest = low.iter().copied().min_by(f32::total_cmp)?;
+        Some((low.len(), lowest))
+    }
+
+    fn print_confirmation(&mut self, ui: &mut egui::Ui) {
+        let mode = &DEVICES[self.selected_device].modes[self.selected_mode];
+        let outside_safe_area = if mode.mode_type.has_printing() {
+            self.images_outside_safe_area()
+        } else {
+            0
+        };
+        let low_resolution = mode
+            .mode_type
+            .has_printing()
+            .then(|| self.low_resolution_images())
+            .flatten();
+
+        let blockers = self.print_blockers();
+
+        // Rendering is slow, so only estimate coverage once per confirmation.
+        if mode.mode_type.has_printing() && self.ink_coverage.is_none() {
+            self.ink_coverage = Some(InkCoverage::estimate(
+                &self.render_print_image().into_rgba8(),
+            ));
+        }
+
+        let mut print = false;
+        let mut skip_cut = self.skip_cut;
+        let mut previewing_compressed = self.previewing_compressed;
+        let mut set_device_media = false;
+        let mut editing_job_json = self.editing_job_json;
+        let mut overrides = self.job_format_overrides;
+        let edited_job = self.editable_job.as_ref().map(EditableJob::edited);
+        let is_jpeg = DEVICES[self.selected_device].image_formats[self.selected_image_format]
+            .encoding
+            == ImageEncoding::Jpeg;
+
+        let modal = Modal::new(Id::new("print_modal")).show(ui.ctx(), |ui| {
+            ui.set_width(380.0);
+            ui.heading("Print Canvas");
+
+            if mode.mode_type.has_printing() && mode.mode_type.has_cutting() {
+                ui.checkbox(&mut skip_cut, "Print without cutting")
+                    .on_hover_text("Only print, for media that has already been cut");
+            }
+
+            for blocker in &blockers {
+                ui.colored_label(egui::Color32::RED, *blocker);
+            }
+
+            if outside_safe_area > 0 {
+                ui.colored_label(
+                    egui::Color32::RED,
+                    format!(
+                        "{outside_safe_area} image(s) extend past the safe area and may be clipped"
+                    ),
+                );
+            }
+
+            if let Some((count, lowest)) = low_resolution {
+                ui.colored_label(
+                    ui.visuals().warn_fg_color,
+                    format!(
+                        "{count} image(s) are enlarged below {MIN_PRINT_DPI:.0} DPI and may look blurry (lowest is {lowest:.0} DPI)"
+                    ),
+                );
+            }
+
+            if let Some(media) = &self.loaded_media {
+                let canvas_size = self.get_canvas();
+
+                if canvas_size.matches_media(media) {
+                    ui.label(format!("Loaded media: {}", media.describe()));
+                } else {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        format!(
+                            "Loaded media is {}, but this canvas expects {}",
+                            media.describe(),
+                            canvas_size.media_description()
+                        ),
+                    );
+
+                    set_device_media = ui
+                        .button("Set Device Media")
+                        .on_hover_text(
+                            "Tell the device the media for this canvas is loaded, if it reported the wrong media",
+                        )
+                        .clicked();
+                }
+            }
+
+            if let Some(coverage) = &self.ink_coverage {
+                ui.label(format!(
+                    "Estimated ink usage: {:.0}% (C {:.0}%, M {:.0}%, Y {:.0}%)",
+                    coverage.average() * 100.0,
+                    coverage.cyan * 100.0,
+                    coverage.magenta * 100.0,
+                    coverage.yellow * 100.0,
+                ));
+
+                if coverage.is_high() {
+                    ui.colored_label(
+                        ui.visuals().warn_fg_color,
+                        "High coverage, this will use a lot of ink",
+                    );
+                }
+            }
+
+            if mode.mode_type.has_printing() && is_jpeg {
+                ui.checkbox(&mut previewing_compressed, "Preview compressed")
+                    .on_hover_text("Show the image as it will be printed, after compression");
+
+                if previewing_compressed {
+                    match &self.compressed_preview {
+                        Some(preview) => {
+                            match preview.progress {
+                                Some(progress) => ui.label(format!(
+                                    "JPEG quality {}, {:.0} KB",
+                                    progress.quality,
+                                    progress.len as f32 / 1024.0
+                                )),
+                                None => ui.colored_label(
+                                    ui.visuals().warn_fg_color,
+                                    "Image does not fit at any quality, sending lowest",
+                                ),
+                            };
+
+                            // Show it at full size so artifacts are visible.
+                            egui::ScrollArea::both().max_height(320.0).show(ui, |ui| {
+                                ui.image(egui::load::SizedTexture::from_handle(&preview.texture));
+                            });
                         }
-                    ],
-                })
-            } else {
-                serde_json::json!({
-                    "id": id,
-                    "method": "print-job",
-                    "params": {
-                        "media-size": canvas_size.media_size,
-                        "media-type": canvas_size.media_type,
-                        "job-type": mode.mode_type.job_type(),
-                        "channel": mode.mode_type.channel(),
-                        "file-size": encoded_image_len,
-                        "document-format": 9,
-                        "document-name": format!("{}.jpeg", time),
-                        "hash-method": 1,
-                        "hash-value": hex::encode(hash),
-                        "user-account": "000000.00000000000000000000000000000000.0000",
-                        "link-type": mode.mode_type.link_type(),
-                        "job-send-time": time / 1000,
-                        "copies": copies,
-                    },
-                })
+                        None => {
+                            ui.spinner();
+                        }
+                    }
+                }
+            }
+
+            let device = &DEVICES[self.selected_device];
+            ui.collapsing("Advanced: job formats", |ui| {
+                let optional_format = |ui: &mut egui::Ui,
+                                           value: &mut Option<u16>,
+                                           default: u16,
+                                           label: &str| {
+                    ui.horizontal(|ui| {
+                        let mut enabled = value.is_some();
+                        let mut current = value.unwrap_or(default);
+
+                        ui.checkbox(&mut enabled, label);
+                        ui.add_enabled(enabled, egui::DragValue::new(&mut current));
+
+                        *value = enabled.then_some(current);
+                    });
+                };
+
+                optional_format(
+                    ui,
+                    &mut overrides.document_format,
+                    device.image_formats[self.selected_image_format].document_format,
+                    "Image document format",
+                );
+                optional_format(
+                    ui,
+                    &mut overrides.cut_document_format,
+                    PLT_DOCUMENT_FORMAT,
+                    "Cut document format",
+                );
+
+                let device_hash_method = device.quirks.hash_method;
+                egui::ComboBox::from_label("Hash method")
+                    .selected_text(
+                        overrides
+                            .hash_method
+                            .unwrap_or(device_hash_method)
+                            .name(),
+                    )
+                    .show_ui(ui, |ui| {
+                        ui.selectable_value(
+                            &mut overrides.hash_method,
+                            None,
+                            format!("Device default ({})", device_hash_method.name()),
+                        );
+
+                        for method in HashMethod::iter() {
+                            ui.selectable_value(
+                                &mut overrides.hash_method,
+                                Some(method),
+                                format!("{} ({})", method.name(), method.code()),
+                            );
+                        }
+                    });
+            })
+            .header_response
+            .on_hover_text("Send other formats than usual, to see how the device responds");
+
+            ui.checkbox(&mut editing_job_json, "Advanced: edit job JSON")
+                .on_hover_text("Change the params sent with the job before printing");
+
+            let mut can_print = blockers.is_empty();
+
+            if editing_job_json {
+                match &mut self.editable_job {
+                    Some(editable) => {
+                        ui.label(format!("Params for {}", editable.job.method));
+
+                        let theme = CodeTheme::from_memory(ui.ctx(), ui.style());
+                        let mut layouter =
+                            |ui: &egui::Ui, buf: &dyn egui::TextBuffer, wrap_width: f32| {
+                                let mut job = highlight(
+                                    ui.ctx(),
+                                    ui.style(),
+                                    &theme,
+                                    buf.as_str(),
+                                    "json",
+                                );
+                                job.wrap.max_width = wrap_width;
+                                ui.painter().layout_job(job)
+                            };
+
+                        egui::ScrollArea::vertical().max_height(320.0).show(ui, |ui| {
+                            ui.add(
+                                egui::TextEdit::multiline(&mut editable.json)
+                                    .code_editor()
+                                    .desired_width(f32::INFINITY)
+                                    .layouter(&mut layouter),
+                            );
+                        });
+
+                        if let Some(Err(err)) = &edited_job {
+                            ui.colored_label(egui::Color32::RED, format!("Invalid JSON: {err}"));
+                            can_print = false;
+                        }
+                    }
+                    None => {
+                        ui.horizontal(|ui| {
+                            ui.spinner();
+                            ui.label("Building job");
+                        });
+                        can_print = false;
+                    }
+                }
+            }
+
+            ui.horizontal(|ui| {
+                if ui
+                    .add_enabled(can_print, egui::Button::new("Print"))
+                    .clicked()
+                {
+                    print = true;
+                    ui.close();
+                }
+
+                if ui.button("Cancel").clicked() {
+                    ui.close();
+                }
+            });
+        });
+
+        self.skip_cut = skip_cut;
+
+        if set_device_media {
+            self.set_device_media();
+        }
+
+        if previewing_compressed != self.previewing_compressed {
+            self.previewing_compressed = previewing_compressed;
+
+            if previewing_compressed && self.compressed_preview.is_none() {
+                self.start_compressed_preview(ui.ctx());
+            }
+        }
+
+        if overrides != self.job_format_overrides {
+            self.job_format_overrides = overrides;
+
+            // The edited JSON was built with the old formats.
+            if self.editable_job.take().is_some() && self.encode_progress.is_none() {
+                self.build_job(Action::EditableJob);
+            }
+        }
+
+        if editing_job_json != self.editing_job_json {
+            self.editing_job_json = editing_job_json;
+
+            if editing_job_json && self.editable_job.is_none() && self.encode_progress.is_none() {
+                self.build_job(Action::EditableJob);
+            }
+        }
+
+        if modal.should_close() {
+            self.confirming_print = false;
+            self.ink_coverage = None;
+            self.loaded_media = None;
+            self.previewing_compressed = false;
+            self.compressed_preview = None;
+            self.editing_job_json = false;
+            self.editable_job = None;
+        }
+
+        if print {
+            match edited_job {
+                Some(Ok(job)) if self.editing_job_json => self.start_job(job),
+                _ => self.print_canvas(),
+            }
+        }
+    }
+
+    /// Encode the canvas like it would be for printing, then decode it again
+    /// for [`CompressedPreview`].
+    fn start_compressed_preview(&self, ctx: &egui::Context) {
+        let im = self.render_print_image();
+        let encoding =
+            DEVICES[self.selected_device].image_formats[self.selected_image_format].encoding;
+
+        let ctx = ctx.clone();
+        let tx = self.tx.clone();
+        spawn_blocking(move || {
+            let last_attempt = std::cell::Cell::new(None);
+            let buf = match encode_image(&im, encoding, |progress| last_attempt.set(Some(progress)))
+            {
+                Ok(buf) => buf,
+                Err(err) => {
+                    let _ = tx.send(Action::EncodeFailed(err));
+                    return;
+                }
             };
 
-            let packet = AvocadoPacket {
-                version: 100,
-                content_type: ContentType::Message,
-                interaction_type: InteractionType::Request,
-                encoding_type: EncodingType::Json,
-                encryption_mode: EncryptionMode::None,
-                terminal_id: id,
-                msg_number: id,
-                msg_package_total: 1,
-                msg_package_num: 1,
-                is_subpackage: false,
-                data: serde_json::to_vec(&data).unwrap(),
+            // The last attempt is the one that was kept, unless nothing fit.
+            let progress = last_attempt
+                .get()
+                .filter(|progress| progress.len == buf.len());
+
+            let decoded = match image::load_from_memory(&buf) {
+                Ok(decoded) => decoded,
+                Err(err) => {
+                    let _ = tx.send(Action::Error(err.into()));
+                    return;
+                }
             };
-            debug!(?packet, "built print job packet");
 
-            let packet = manager.wait_for_response(packet).await.unwrap();
-            debug!(?packet, "got response packet");
+            // Textures can't be larger than the renderer supports.
+            let max_dimension = LoadedImage::max_dimension(&ctx);
+            let decoded = if decoded.width().max(decoded.height()) > max_dimension {
+                decoded.resize(
+                    max_dimension,
+                    max_dimension,
+                    image::imageops::FilterType::Triangle,
+                )
+            } else {
+                decoded
+            }
+            .into_rgba8();
+
+            let texture = ctx.load_texture(
+                "compressed_preview",
+                egui::ColorImage::from_rgba_unmultiplied(
+                    [decoded.width() as usize, decoded.height() as usize],
+                    decoded.as_bytes(),
+                ),
+                egui::TextureOptions::NEAREST,
+            );
+
+            let _ = tx.send(Action::CompressedPreview(CompressedPreview {
+                texture,
+                progress,
+            }));
+        });
+    }
+
+    /// Render thumbnails of the canvas for previewing color adjustments.
+    fn new_color_preview(&self, ctx: &egui::Context, width: f32) -> ColorPreview {
+        let canvas = self.get_canvas().size;
+        let before = image::imageops::thumbnail(
+            &self.render_image().into_rgba8(),
+            width as u32,
+            (width / canvas.x * canvas.y) as u32,
+        );
+        let color_image = egui::ColorImage::from_rgba_unmultiplied(
+            [before.width() as usize, before.height() as usize],
+            before.as_bytes(),
+        );
+
+        ColorPreview {
+            before_texture: ctx.load_texture(
+                "color_before",
+                color_image.clone(),
+                egui::TextureOptions::LINEAR,
+            ),
+            after_texture: ctx.load_texture(
+                "color_after",
+                color_image,
+                egui::TextureOptions::LINEAR,
+            ),
+            // Make sure the adjustment is applied on first show.
+            adjustment: Default::default(),
+            before,
+        }
+    }
 
-            #[derive(Debug, Deserialize)]
-            #[serde(rename_all = "kebab-case")]
-            struct JobResult {
-                job_id: u32,
+    fn pick_raw_file(&self, kind: RawFileKind) {
+        let (name, extensions) = match kind {
+            RawFileKind::Image => {
+                let encoding = DEVICES[self.selected_device].image_formats
+                    [self.selected_image_format]
+                    .encoding;
+                (encoding.name(), vec![encoding.extension()])
             }
+            RawFileKind::Plt => ("PLT", vec!["plt"]),
+        };
 
-            let job_id = packet
-                .as_json::<AvocadoResult<JobResult>>()
-                .unwrap()
-                .result
-                .job_id;
-            debug!(job_id, "got job id");
-
-            manager
-                .send_data(job_id, &packet_data, |total, sent| {
-                    debug!(total, sent, "sent data packet");
-                    let _ = tx.send(Action::SendProgress(sent as f32 / total as f32));
-                })
+        let tx = self.tx.clone();
+        spawn(async move {
+            let Some(file) = rfd::AsyncFileDialog::new()
+                .add_filter(name, &extensions)
+                .pick_file()
                 .await
-                .unwrap();
+            else {
+                return;
+            };
+
+            let file = RawFile {
+                name: file.file_name(),
+                data: file.read().await,
+            };
 
-            manager.poll_job(job_id).await.unwrap();
-            info!("finished sending data");
+            if let Err(err) = tx.send(Action::LoadedRawFile(kind, file)) {
+                error!("could not send raw file: {err}");
+            }
         });
     }
 
+    /// Send already encoded files for the selected mode, without rendering
+    /// the canvas or generating cut lines.
+    fn raw_job_window(&mut self, ctx: &egui::Context, can_send: bool) {
+        let mut open = self.showing_raw_job;
+
+        egui::Window::new("Send Raw Job")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let device = &DEVICES[self.selected_device];
+                let mode = &device.modes[self.selected_mode];
+                let canvas_size = self.get_canvas();
+
+                ui.label(format!(
+                    "Sends files exactly as they are for {} on {}.",
+                    mode.mode_type.name(),
+                    canvas_size.label(device.dpi)
+                ));
+
+                let rows = [
+                    (RawFileKind::Image, mode.mode_type.has_printing()),
+                    (RawFileKind::Plt, mode.mode_type.has_cutting()),
+                ];
+
+                for (kind, needed) in rows {
+                    if !needed {
+                        continue;
+                    }
+
+                    let file = match kind {
+                        RawFileKind::Image => &self.raw_job.image,
+                        RawFileKind::Plt => &self.raw_job.plt,
+                    };
+
+                    ui.horizontal(|ui| {
+                        let label = match kind {
+                            RawFileKind::Image => "Select Image",
+                            RawFileKind::Plt => "Select PLT",
+                        };
+
+                        if ui.button(label).clicked() {
+                            self.pick_raw_file(kind);
+                        }
+
+                        match file {
+                            Some(file) => {
+                                ui.label(format!("{} ({} bytes)", file.name, file.data.len()))
+                            }
+                            None => ui.weak("No file selected"),
+                        };
+                    });
+                }
+
+                let ready = rows.iter().all(|(kind, needed)| {
+                    !needed
+                        || match kind {
+                            RawFileKind::Image => self.raw_job.image.is_some(),
+                            RawFileKind::Plt => self.raw_job.plt.is_some(),
+                        }
+                });
+
+                if ui
+                    .add_enabled(can_send && ready, egui::Button::new("Send"))
+                    .clicked()
+                {
+                    let data = |file: &Option<RawFile>| {
+                        file.as_ref()
+                            .map(|file| file.data.clone())
+                            .unwrap_or_default()
+                    };
+
+                    let request = PrintRequest {
+                        encoded_image: data(&self.raw_job.image),
+                        plt: data(&self.raw_job.plt),
+                        mode,
+                        canvas_size,
+                        image_format: &device.image_formats[self.selected_image_format],
+                        quirks: &device.quirks,
+                        user_account: &self.user_account,
+                        copies: self.copies,
+                        cut_copies: self.cut_copies(),
+                        skip_cut: false,
+                        overrides: self.job_format_overrides,
+                    };
+
+                    let mut job = request.job(current_timestamp_millis());
+                    job.name = format!("Raw {}", job.name);
+                    self.start_job(job);
+                }
+            });
+
+        self.showing_raw_job = open;
+    }
+
+    fn color_window(&mut self, ctx: &egui::Context) {
+        const PREVIEW_WIDTH: f32 = 200.0;
+
+        let mut open = self.showing_color;
+
+        egui::Window::new("Color Adjustment")
+            .open(&mut open)
+            .resizable(false)
+            .show(ctx, |ui| {
+                let adjustment = &mut self.color_adjustment;
+                ui.add(egui::Slider::new(&mut adjustment.gamma, 0.2..=3.0).text("Gamma"))
+                    .on_hover_text("Values above 1 brighten midtones");
+                ui.add(egui::Slider::new(&mut adjustment.contrast, 0.5..=2.0).text("Contrast"));
+                ui.add(egui::Slider::new(&mut adjustment.saturation, 0.0..=2.0).text("Saturation"));
+
+                ui.horizontal(|ui| {
+                    if ui.button("Reset").clicked() {
+                        *adjustment = Default::default();
+                    }
+
+                    if ui.button("Update Preview").clicked() {
+                        self.color_preview = None;
+                    }
+                });
+
+                ui.separator();
+
+                if self.color_preview.is_none() {
+                    self.color_preview = Some(self.new_color_preview(ctx, PREVIEW_WIDTH));
+                }
+                let Some(preview) = &mut self.color_preview else {
+                    return;
+                };
+
+                if preview.adjustment != self.color_adjustment {
+                    let mut after = preview.before.clone();
+                    self.color_adjustment.apply(&mut after);
+                    preview.after_texture.set(
+                        egui::ColorImage::from_rgba_unmultiplied(
+                            [after.width() as usize, after.height() as usize],
+                            after.as_bytes(),
+                        ),
+                        egui::TextureOptions::LINEAR,
+                    );
+                    preview.adjustment = self.color_adjustment;
+                }
+
+                ui.horizontal(|ui| {
+                    for (label, texture) in [
+                        ("Before", &preview.before_texture),
+                        ("After", &preview.after_texture),
+                    ] {
+                        ui.vertical(|ui| {
+                            ui.label(label);
+                            ui.image(egui::load::SizedTexture::from_handle(texture));
+                        });
+                    }
+                });
+            });
+
+        self.showing_color = open;
+
+        // Don't keep the preview around, it'd be out of date when reopened.
+        if !open {
+            self.color_preview = None;
+        }
+    }
+
+    fn tile_dialog(&mut self, ui: &mut egui::Ui) {
+        let Some(mut layout) = self.tiling else {
+            return;
+        };
+
+        let Some(image) = self.loaded_images.get(layout.index) else {
+            self.tiling = None;
+            return;
+        };
+
+        let dpi = DEVICES[self.selected_device].average_dpi();
+        let area = self.get_canvas().safe_rect();
+        let image_size = image.size();
+
+        let mut tile = false;
+
+        let modal = Modal::new(Id::new("tile_modal")).show(ui.ctx(), |ui| {
+            ui.set_width(380.0);
+            ui.heading("Tile Image");
+
+            egui::Grid::new("tile_grid").num_columns(2).show(ui, |ui| {
+                ui.label("Rows");
+                ui.add(egui::DragValue::new(&mut layout.rows).range(1..=100));
+                ui.end_row();
+
+                ui.label("Columns");
+                ui.add(egui::DragValue::new(&mut layout.columns).range(1..=100));
+                ui.end_row();
+
+                ui.label("Gap");
+                ui.add(views::px_slider(&mut layout.gap, dpi, 0.0..=area.width()));
+                ui.end_row();
+            });
+
+            if ui.button("Fit to Safe Area").clicked() {
+                layout.fit(image_size, area);
+            }
+
+            ui.label(format!("Creates {} copies", layout.rows * layout.columns));
+
+            ui.horizontal(|ui| {
+                if ui.button("Tile").clicked() {
+                    tile = true;
+                    ui.close();
+                }
+
+                if ui.button("Cancel").clicked() {
+                    ui.close();
+                }
+            });
+        });
+
+        if tile {
+            self.tile_image(ui.ctx(), layout);
+        }
+
+        self.tiling = if modal.should_close() {
+            None
+        } else {
+            Some(layout)
+        };
+    }
+
+    /// Move every image so they're packed within the safe area without
+    /// overlapping.
+    fn auto_arrange(&mut self) {
+        let sizes: Vec<_> = self.loaded_images.iter().map(LoadedImage::size).collect();
+
+        match shelf_pack(&sizes, self.get_canvas().safe_rect(), self.arrange_gap) {
+            Some(positions) => {
+                for (image, position) in self.loaded_images.iter_mut().zip(positions) {
+                    image.offset = position;
+                }
+            }
+            None => {
+                self.error = Some(anyhow::anyhow!(
+                    "Images do not fit within the safe area, try scaling them down or reducing the gap."
+                ));
+            }
+        }
+    }
+
+    /// Replace an image with a grid of copies, the original becoming the
+    /// first copy.
+    fn tile_image(&mut self, ctx: &egui::Context, layout: TileLayout) {
+        let area = self.get_canvas().safe_rect();
+        let original = &self.loaded_images[layout.index];
+        let image_size = original.size();
+
+        let mut offsets = layout.offsets(image_size, area);
+        let Some(first) = offsets.next() else {
+            return;
+        };
+
+        let copies: Vec<_> = offsets
+            .map(|offset| original.duplicate(ctx, offset - original.offset))
+            .collect();
+        debug!(copies = copies.len(), "tiled image");
+
+        self.loaded_images[layout.index].offset = first;
+
+        let index = layout.index + 1;
+        self.loaded_images.splice(index..index, copies);
+    }
+
     fn menu(&mut self, ui: &mut egui::Ui, ctx: &egui::Context) {
         egui::widgets::global_theme_preference_switch(ui);
 
         ui.separator();
 
         let is_web = cfg!(target_arch = "wasm32");
-        if !is_web {
-            ui.menu_button("File", |ui| {
+        ui.menu_button("File", |ui| {
+            if ui
+                .add_enabled(self.batch.is_none(), egui::Button::new("Open Project"))
+                .clicked()
+            {
+                self.open_project(ctx);
+            }
+
+            if ui.button("Save Project").clicked() {
+                self.save_project();
+            }
+
+            if ui
+                .add_enabled(
+                    self.can_start_job(),
+                    egui::Button::new("Batch Print Projects"),
+                )
+                .on_hover_text(
+                    "Print saved projects one after another, in order of their names, replacing the canvas",
+                )
+                .on_disabled_hover_text("Connect to a device and wait for any job to finish")
+                .clicked()
+            {
+                self.open_batch(ctx);
+            }
+
+            if !is_web {
+                ui.separator();
+
                 if ui.button("Quit").clicked() {
                     ctx.send_viewport_cmd(egui::ViewportCommand::Close);
                 }
-            });
+            }
+        });
+
+        if ui.input_mut(|i| i.consume_shortcut(&shortcuts::ADD_IMAGE)) {
+            self.upload_image(ctx);
+        }
+
+        if ui.input_mut(|i| i.consume_shortcut(&shortcuts::GENERATE_CUT_LINES))
+            && self.can_generate_cut_lines()
+        {
+            self.generate_cut_lines();
+        }
+
+        if ui.input_mut(|i| i.consume_shortcut(&shortcuts::TOGGLE_CONNECTION)) {
+            self.toggle_connection();
         }
 
-        let image_shortcut =
-            KeyboardShortcut::new(Modifiers::COMMAND | Modifiers::SHIFT, egui::Key::U);
-        if ui.input_mut(|i| i.consume_shortcut(&image_shortcut)) {
-            self.upload_image(ctx);
+        // Typing a question mark into a text field shouldn't open help.
+        if !ctx.wants_keyboard_input()
+            && ui.input_mut(|i| i.consume_shortcut(&shortcuts::SHOW_SHORTCUTS))
+        {
+            self.showing_shortcuts = !self.showing_shortcuts;
         }
 
         ui.menu_button("Canvas", |ui| {
-            let btn =
-                egui::Button::new("Add Image").shortcut_text(ctx.format_shortcut(&image_shortcut));
+            let btn = egui::Button::new("Add Image")
+                .shortcut_text(ctx.format_shortcut(&shortcuts::ADD_IMAGE));
 
             if ui.add(btn).clicked() {
                 self.upload_image(ctx);
             }
+
+            #[cfg(not(target_arch = "wasm32"))]
+            if ui
+                .button("Add Folder")
+                .on_hover_text("Add every image in a folder")
+                .clicked()
+            {
+                self.add_folder(ctx);
+            }
+
+            let btn = egui::Button::new("Generate Cut Lines")
+                .shortcut_text(ctx.format_shortcut(&shortcuts::GENERATE_CUT_LINES));
+
+            if ui.add_enabled(self.can_generate_cut_lines(), btn).clicked() {
+                self.generate_cut_lines();
+            }
+
+            if ui
+                .add_enabled(
+                    self.can_generate_cut_lines(),
+                    egui::Button::new("Import Cut SVG"),
+                )
+                .on_hover_text("Use the paths from an SVG as cut lines instead of tracing images")
+                .clicked()
+            {
+                self.import_cut_svg(ctx);
+            }
+
+            ui.separator();
+
+            ui.checkbox(&mut self.showing_color, "Color Adjustment");
         });
 
         ui.menu_button("Connection", |ui| {
+            let (label, enabled) = match self.transport_status {
+                TransportStatus::Connected => ("Disconnect", true),
+                TransportStatus::Disconnected => ("Connect", self.is_transport_available()),
+                TransportStatus::Connecting => ("Connecting", false),
+                TransportStatus::FinishingTransfer | TransportStatus::Disconnecting => {
+                    ("Disconnecting", false)
+                }
+            };
+            let btn = egui::Button::new(label)
+                .shortcut_text(ctx.format_shortcut(&shortcuts::TOGGLE_CONNECTION));
+
+            if ui.add_enabled(enabled, btn).clicked() {
+                self.toggle_connection();
+            }
+
+            if self
+                .transport_manager
+                .as_ref()
+                .is_some_and(|manager| manager.is_sending())
+                && ui
+                    .button("Cancel Transfer and Disconnect")
+                    .on_hover_text("Disconnecting otherwise waits for the transfer to finish")
+                    .clicked()
+            {
+                self.disconnect_with(DisconnectMode::CancelTransfer);
+            }
+
+            ui.separator();
+
             ui.menu_button("Transport", |ui| {
-                for (index, transport) in self.transport_names.iter().enumerate() {
+                for (index, (transport, available)) in self
+                    .transport_names
+                    .iter()
+                    .zip(&self.transport_available)
+                    .enumerate()
+                {
                     if ui
-                        .radio(self.selected_transport_index == index, transport.as_ref())
+                        .add_enabled(
+                            *available,
+                            egui::RadioButton::new(
+                                self.selected_transport_index == index,
+                                transport.as_ref(),
+                            ),
+                        )
+                        .on_disabled_hover_text("Not available in this environment")
                         .clicked()
                     {
                         if let Some(manager) = self.transport_manager.take() {
                             let tx = self.tx.clone();
                             spawn(async move {
-                                let action = if let Err(err) = manager.disconnect().await {
+                                let action = if let Err(err) =
+                                    manager.disconnect(DisconnectMode::FinishTransfer).await
+                                {
                                     Action::Error(err)
                                 } else {
                                     Action::ChangeTransport(index)
@@ -576,6 +3476,8 @@ impl SapodillaApp {
                     }
                 }
             });
+
+            ui.checkbox(&mut self.showing_job_history, "Job History");
         });
 
         ui.menu_button("Debug Tools", |ui| {
@@ -584,6 +3486,83 @@ impl SapodillaApp {
                 &mut self.showing_avocado_packet_debug,
                 "Saved Packet Debugger",
             );
+            ui.checkbox(&mut self.showing_logs, "Show Logs");
+            ui.checkbox(&mut self.showing_raw_job, "Send Raw Job");
+
+            if let Some(manager) = &self.transport_manager {
+                ui.label(format!("Terminal ID: {}", manager.terminal_id()));
+            }
+
+            ui.horizontal(|ui| {
+                let tally = self.checksum_tally;
+                let text = format!(
+                    "Checksums: {} matched, {} mismatched",
+                    tally.matched, tally.mismatched
+                );
+
+                if tally.mismatched > 0 {
+                    ui.colored_label(egui::Color32::RED, text).on_hover_text(
+                        "Our checksum calculation or packet framing may not match the device",
+                    );
+                } else {
+                    ui.label(text);
+                }
+
+                if ui.small_button("Reset").clicked() {
+                    self.checksum_tally = Default::default();
+                }
+            });
+
+            ui.horizontal(|ui| {
+                let response = ui
+                    .add(egui::DragValue::new(&mut self.data_size).range(5..=MAX_DATA_SIZE))
+                    .on_hover_text("Bytes of data in each packet, including the 4 byte job ID");
+                ui.label("Data Packet Size");
+
+                if response.changed()
+                    && let Some(manager) = &self.transport_manager
+                    && let Err(err) = manager.set_data_size(self.data_size)
+                {
+                    warn!("could not set data size: {err}");
+                }
+            });
+
+            ui.horizontal(|ui| {
+                ui.add(egui::TextEdit::singleline(&mut self.user_account).desired_width(200.0))
+                    .on_hover_text("Sent with jobs so the device can tell who sent them");
+                ui.label("User Account");
+
+                if ui
+                    .small_button("New")
+                    .on_hover_text("Use an account unique to this session")
+                    .clicked()
+                {
+                    self.user_account = session_user_account();
+                }
+
+                if ui.small_button("Reset").clicked() {
+                    self.user_account = DEFAULT_USER_ACCOUNT.to_string();
+                }
+            });
+
+            ui.checkbox(
+                &mut self.retry_corrupted_transfers,
+                "Retry Corrupted Transfers",
+            )
+            .on_hover_text(
+                "Send jobs again when the device aborts them after receiving all of their data, \
+                which is usually corrupted data but may reprint the job",
+            );
+
+            ui.horizontal(|ui| {
+                ui.add(
+                    egui::DragValue::new(&mut self.handshake_timeout)
+                        .range(1..=120)
+                        .suffix(" s"),
+                )
+                .on_hover_text("How long to wait for the device to respond after connecting");
+                ui.label("Connection Timeout");
+            });
 
             if let Some(manager) = &self.transport_manager
                 && ui.button("Send Get Prop Packet").clicked()
@@ -595,11 +3574,12 @@ impl SapodillaApp {
                     let packet = manager
                         .wait_for_response(AvocadoPacket {
                             version: 100,
+                            reserved: 0,
                             content_type: crate::protocol::ContentType::Message,
                             interaction_type: crate::protocol::InteractionType::Request,
                             encoding_type: EncodingType::Json,
                             encryption_mode: EncryptionMode::None,
-                            terminal_id: id,
+                            terminal_id: manager.terminal_id(),
                             msg_number: id,
                             msg_package_total: 1,
                             msg_package_num: 1,
@@ -641,11 +3621,12 @@ impl SapodillaApp {
                     let packet = manager
                         .wait_for_response(AvocadoPacket {
                             version: 100,
+                            reserved: 0,
                             content_type: crate::protocol::ContentType::Message,
                             interaction_type: crate::protocol::InteractionType::Request,
                             encoding_type: EncodingType::Json,
                             encryption_mode: EncryptionMode::None,
-                            terminal_id: id,
+                            terminal_id: manager.terminal_id(),
                             msg_number: id,
                             msg_package_total: 1,
                             msg_package_num: 1,
@@ -666,9 +3647,37 @@ impl SapodillaApp {
 
             ui.separator();
 
+            if ui
+                .add_enabled(
+                    !self.cut_shapes.is_empty(),
+                    egui::Button::new("Export Cut Lines (G-code)"),
+                )
+                .clicked()
+            {
+                let buf = encode_gcode(
+                    &self.cut_shapes,
+                    self.get_canvas(),
+                    DEVICES[self.selected_device].dpi,
+                );
+
+                spawn(async move {
+                    let Some(handle) = rfd::AsyncFileDialog::new()
+                        .set_file_name("cuts.gcode")
+                        .save_file()
+                        .await
+                    else {
+                        return;
+                    };
+
+                    if let Err(err) = handle.write(&buf).await {
+                        error!("could not write cut lines: {err}");
+                    }
+                });
+            }
+
             if ui.button("Export Canvas").clicked() {
-                let im = self.render_image();
-                let buf = encode_image(&im);
+                let im = self.render_print_image();
+                let buf = encode_jpeg_within(&im, MAX_IMAGE_SIZE, |_| ());
 
                 spawn(async move {
                     let Some(handle) = rfd::AsyncFileDialog::new()
@@ -684,32 +3693,148 @@ impl SapodillaApp {
                     }
                 });
             }
+
+            if ui
+                .button("Export PNG")
+                .on_hover_text("Save the canvas as a lossless PNG at full resolution")
+                .clicked()
+            {
+                // Unlike printing, this isn't size limited or color adjusted.
+                let im = self.render_image();
+                let mut buf = Vec::new();
+                match im.write_to(&mut std::io::Cursor::new(&mut buf), image::ImageFormat::Png) {
+                    Ok(()) => spawn(async move {
+                        let Some(handle) = rfd::AsyncFileDialog::new()
+                            .set_file_name("canvas.png")
+                            .save_file()
+                            .await
+                        else {
+                            return;
+                        };
+
+                        if let Err(err) = handle.write(&buf).await {
+                            error!("could not write canvas png: {err}");
+                        }
+                    }),
+                    Err(err) => error!("could not encode canvas png: {err}"),
+                }
+            }
+
+            if ui
+                .button("Export for Vendor App")
+                .on_hover_text(
+                    "Save the image, cut lines, and job details as the vendor app names them",
+                )
+                .clicked()
+            {
+                self.export_for_vendor_app();
+            }
+        });
+
+        ui.menu_button("Help", |ui| {
+            let button = egui::Button::selectable(self.showing_shortcuts, "Keyboard Shortcuts")
+                .shortcut_text(ctx.format_shortcut(&shortcuts::SHOW_SHORTCUTS));
+            if ui.add(button).clicked() {
+                self.showing_shortcuts = !self.showing_shortcuts;
+            }
+        });
+    }
+
+    /// Save the files for a job with the names the vendor app uses, along with
+    /// the job's params in a JSON sidecar.
+    fn export_for_vendor_app(&mut self) {
+        self.build_request(|mut request, time| {
+            request.skip_cut = false;
+            let job = request.job(time);
+            let mode_type = &request.mode.mode_type;
+
+            let mut files = Vec::with_capacity(3);
+            if mode_type.has_printing() {
+                files.push((
+                    format!("{time}.{}", request.image_format.encoding.extension()),
+                    request.encoded_image,
+                ));
+            }
+            if mode_type.has_cutting() {
+                files.push((format!("{time}.plt"), request.plt));
+            }
+
+            match serde_json::to_vec_pretty(&serde_json::json!({
+                "method": job.method,
+                "params": job.params,
+            })) {
+                Ok(sidecar) => files.push((format!("{time}.json"), sidecar)),
+                Err(err) => error!("could not encode job details: {err}"),
+            }
+
+            Action::VendorExport(files)
+        });
+    }
+
+    /// Ask where to save each exported file, stopping at the first that's
+    /// cancelled or fails.
+    fn save_vendor_export(files: Vec<(String, Vec<u8>)>) {
+        spawn(async move {
+            for (name, data) in files {
+                let Some(handle) = rfd::AsyncFileDialog::new()
+                    .set_file_name(&name)
+                    .save_file()
+                    .await
+                else {
+                    return;
+                };
+
+                if let Err(err) = handle.write(&data).await {
+                    error!("could not write {name}: {err}");
+                    return;
+                }
+            }
         });
     }
 
     fn device_status(&mut self, ui: &mut egui::Ui) {
         match self.transport_status {
             TransportStatus::Connected => {
-                if ui.button("Disconnect").clicked()
-                    && let Some(manager) = self.transport_manager.take()
-                {
-                    let tx = self.tx.clone();
-                    spawn(async move {
-                        if let Err(err) = manager.disconnect().await {
-                            tx.send(Action::Error(err)).unwrap();
+                if ui.button("Disconnect").clicked() {
+                    self.disconnect();
+                }
+
+                if self.connection_step == Some(ConnectionStep::Handshaking) {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Waiting for the device to respond");
+                    });
+                }
+
+                if let Some(manager) = &self.transport_manager {
+                    let (color, text) = match manager.health() {
+                        ConnectionHealth::Healthy => (egui::Color32::GREEN, "Responding"),
+                        ConnectionHealth::Stale => {
+                            (ui.visuals().warn_fg_color, "Not responding recently")
                         }
+                        ConnectionHealth::Lost => (ui.visuals().error_fg_color, "Not responding"),
+                    };
+
+                    ui.horizontal(|ui| {
+                        ui.label(egui::RichText::new("●").color(color));
+                        ui.label(text);
                     });
+
+                    // Health changes without any events when the device stops
+                    // responding, so keep checking.
+                    ui.ctx()
+                        .request_repaint_after(std::time::Duration::from_secs(1));
                 }
 
                 if let Some(status) = &self.device_status {
                     ui.horizontal(|ui| {
                         ui.label("State: ");
-                        ui.label(serde_plain::to_string(&status.0).unwrap());
+                        ui.label(status.0.to_string());
                     });
 
                     ui.horizontal(|ui| {
                         ui.label("Sub State: ");
-                        ui.label(serde_plain::to_string(&status.1).unwrap());
+                        ui.label(status.1.to_string());
                     });
 
                     ui.horizontal(|ui| {
@@ -718,15 +3843,75 @@ impl SapodillaApp {
                     });
                 }
 
+                if self
+                    .transport_manager
+                    .as_ref()
+                    .is_some_and(|manager| manager.is_sending())
+                {
+                    ui.label(egui::RichText::new("Status paused during transfer").weak());
+                }
+
                 ui.separator();
 
                 ui.heading("Current Job");
 
-                if self.send_progress.is_none() && self.job_status.is_none() {
+                let mut stopping_batch = false;
+                if let Some(batch) = &self.batch {
+                    ui.horizontal(|ui| {
+                        ui.label(format!(
+                            "Job {} of {}: {}",
+                            batch.position, batch.total, batch.name
+                        ));
+
+                        stopping_batch = ui
+                            .button("Stop Batch")
+                            .on_hover_text(
+                                "Don't start any more projects, the current job continues",
+                            )
+                            .clicked();
+                    });
+                }
+                if stopping_batch {
+                    self.stop_batch("it was stopped");
+                }
+
+                if let Some(progress) = self.encode_progress {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+
+                        if progress.len == 0 {
+                            ui.label("Encoding image");
+                        } else {
+                            ui.label(format!(
+                                "Encoding image, quality {} was {:.0} KB",
+                                progress.quality,
+                                progress.len as f32 / 1024.0
+                            ));
+                        }
+                    });
+                } else if self.send_progress.is_none()
+                    && self.job_status.is_none()
+                    && self.batch.is_some()
+                {
+                    ui.horizontal(|ui| {
+                        ui.spinner();
+                        ui.label("Preparing job");
+                    });
+                } else if self.send_progress.is_none() && self.job_status.is_none() {
                     if ui.button("Print Canvas").clicked() {
-                        self.print_canvas();
+                        self.confirming_print = true;
+                        self.fetch_loaded_media();
                     }
                 } else {
+                    if let Some(attempt) = self.transfer_retry {
+                        ui.colored_label(
+                            ui.visuals().warn_fg_color,
+                            format!(
+                                "Job aborted after all data was received, the transfer may have been corrupted, retrying ({attempt} of {MAX_TRANSFER_RETRIES})"
+                            ),
+                        );
+                    }
+
                     if let Some(send_progress) = self.send_progress {
                         ui.horizontal(|ui| {
                             ui.label("Data transfer: ");
@@ -736,27 +3921,97 @@ impl SapodillaApp {
                                     .animate(true),
                             );
                         });
+
+                        if let Some(stats) = &self.transfer_stats {
+                            let mut text = format!(
+                                "{:.0} of {:.0} KB",
+                                stats.sent_bytes() as f32 / 1024.0,
+                                stats.total_bytes as f32 / 1024.0
+                            );
+
+                            if let Some(rate) = stats.rate() {
+                                text.push_str(&format!(" at {:.1} KB/s", rate / 1024.0));
+                            }
+
+                            if send_progress < 1.0
+                                && let Some(remaining) = stats.remaining()
+                            {
+                                let secs = remaining.as_secs();
+                                text.push_str(&format!(
+                                    ", about {}:{:02} left",
+                                    secs / 60,
+                                    secs % 60
+                                ));
+                            }
+
+                            ui.label(text);
+                        }
+
+                        if send_progress < 1.0
+                            && let Some(manager) = &self.transport_manager
+                            && ui.button("Cancel Transfer").clicked()
+                        {
+                            manager.cancel_sending();
+                        }
                     }
 
                     if let Some(status) = &self.job_status {
                         ui.horizontal(|ui| {
                             ui.label("State: ");
-                            ui.label(serde_plain::to_string(&status.job_state).unwrap_or_default());
+                            ui.label(status.job_state.to_string());
                         });
 
                         ui.horizontal(|ui| {
                             ui.label("Sub State: ");
-                            ui.label(
-                                serde_plain::to_string(&status.job_sub_state).unwrap_or_default(),
-                            );
+                            ui.label(status.job_sub_state.to_string());
                         });
+
+                        if status.job_state == JobState::Processing {
+                            ui.horizontal(|ui| {
+                                ui.label("Progress: ");
+                                ui.add(
+                                    egui::ProgressBar::new(status.job_sub_state.progress())
+                                        .show_percentage()
+                                        .animate(true),
+                                );
+                            });
+                        }
+                    }
+
+                    match self.job_attention {
+                        Some(JobAttention::ActionRequired(sub_state)) => {
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                format!(
+                                    "The printer is waiting, insert media or scan as needed ({sub_state})"
+                                ),
+                            );
+                        }
+                        Some(JobAttention::NoProgress(secs)) => {
+                            ui.colored_label(
+                                ui.visuals().warn_fg_color,
+                                format!("The job hasn't progressed in {secs} seconds"),
+                            );
+                        }
+                        None => (),
                     }
                 }
             }
             TransportStatus::Connecting => {
                 ui.horizontal(|ui| {
                     ui.spinner();
-                    ui.label("Connecting");
+
+                    match &self.connection_step {
+                        Some(step) => ui.label(format!("Connecting, {step}")),
+                        None => ui.label("Connecting"),
+                    };
+                });
+            }
+
+            TransportStatus::FinishingTransfer => {
+                ui.horizontal(|ui| {
+                    ui.spinner();
+                    ui.label("Disconnecting, finishing transfer…");
                 });
             }
 
@@ -768,16 +4023,38 @@ impl SapodillaApp {
             }
 
             TransportStatus::Disconnected => {
-                if ui.button("Connect").clicked() {
-                    let tx = self.tx.clone();
-
-                    let manager = TransportManager::new(self.get_transport(), move |event| {
-                        if let Err(err) = tx.send(Action::TransportEvent(event)) {
-                            error!("could not send transport event: {err}");
-                        }
+                if let Some(mut transport) = self.get_transport().try_lock()
+                    && let Some(address) = transport.address_mut()
+                {
+                    ui.horizontal(|ui| {
+                        ui.label("Address: ");
+                        ui.text_edit_singleline(address)
+                            .on_hover_text("Host and port, such as 192.168.1.10:9100");
                     });
+                }
+
+                let failed_step = self
+                    .connection_step
+                    .as_ref()
+                    .filter(|step| **step != ConnectionStep::Ready);
+                if let Some(step) = failed_step {
+                    ui.colored_label(
+                        ui.visuals().error_fg_color,
+                        format!("Connection failed while {step}"),
+                    );
+                }
 
-                    self.transport_manager = Some(manager);
+                let label = if failed_step.is_some() {
+                    "Retry"
+                } else {
+                    "Connect"
+                };
+                if ui
+                    .add_enabled(self.is_transport_available(), egui::Button::new(label))
+                    .on_disabled_hover_text("This transport isn't available here")
+                    .clicked()
+                {
+                    self.connect();
                 }
             }
         }
@@ -820,8 +4097,13 @@ impl eframe::App for SapodillaApp {
                         &DEVICES[i].name
                     });
                 if self.selected_device != previous {
+                    if let Some(manager) = &self.transport_manager {
+                        manager.set_quirks(DEVICES[self.selected_device].quirks.clone());
+                    }
+
                     self.selected_mode = 0;
                     self.selected_canvas_size = 0;
+                    self.selected_image_format = 0;
                 }
 
                 let previous = self.selected_mode;
@@ -841,43 +4123,133 @@ impl eframe::App for SapodillaApp {
                     self.selected_canvas_size = 0;
                 }
 
+                let dpi = DEVICES[self.selected_device].dpi;
+                let canvas_sizes =
+                    &DEVICES[self.selected_device].modes[self.selected_mode].canvas_sizes;
                 egui::ComboBox::from_label("Canvas Size")
-                    .selected_text(
-                        &DEVICES[self.selected_device].modes[self.selected_mode].canvas_sizes
-                            [self.selected_canvas_size]
-                            .name,
-                    )
-                    .show_index(
-                        ui,
-                        &mut self.selected_canvas_size,
-                        DEVICES[self.selected_device].modes[self.selected_mode]
-                            .canvas_sizes
-                            .len(),
-                        |i| {
-                            &DEVICES[self.selected_device].modes[self.selected_mode].canvas_sizes[i]
-                                .name
-                        },
+                    .selected_text(canvas_sizes[self.selected_canvas_size].label(dpi))
+                    .show_ui(ui, |ui| {
+                        // Show smaller sizes first, regardless of how they
+                        // were defined.
+                        let sorted = canvas_sizes.iter().enumerate().sorted_by(|(_, a), (_, b)| {
+                            (a.size.x * a.size.y).total_cmp(&(b.size.x * b.size.y))
+                        });
+
+                        for (index, canvas_size) in sorted {
+                            ui.selectable_value(
+                                &mut self.selected_canvas_size,
+                                index,
+                                canvas_size.label(dpi),
+                            )
+                            .on_hover_text(canvas_size.media_description());
+                        }
+                    });
+
+                if ui
+                    .checkbox(&mut self.landscape, "Landscape")
+                    .on_hover_text("Turn the canvas sideways, which may fit more copies")
+                    .changed()
+                {
+                    // Cut lines were generated for the other orientation.
+                    self.clear_cuts();
+                }
+
+                ui.checkbox(&mut self.show_as_printed, "Show as Printed")
+                    .on_hover_text(
+                        "Show the canvas turned the way the sheet comes out of the printer, images can't be moved while shown",
                     );
 
+                let image_formats = &DEVICES[self.selected_device].image_formats;
+                if image_formats.len() > 1
+                    && DEVICES[self.selected_device].modes[self.selected_mode]
+                        .mode_type
+                        .has_printing()
+                {
+                    egui::ComboBox::from_label("Image Format")
+                        .selected_text(image_formats[self.selected_image_format].encoding.name())
+                        .show_index(
+                            ui,
+                            &mut self.selected_image_format,
+                            image_formats.len(),
+                            |i| image_formats[i].encoding.name(),
+                        )
+                        .on_hover_text("PNG is lossless but larger, JPEG is better for photos");
+                }
+
+                let device = &DEVICES[self.selected_device];
+                let mode_type = &device.modes[self.selected_mode].mode_type;
+                let separate_copies =
+                    mode_type.has_printing() && mode_type.has_cutting() && !self.link_copies;
+
                 ui.horizontal(|ui| {
-                    ui.add(egui::DragValue::new(&mut self.copies).range(1..=10));
-                    ui.label("Copies");
+                    ui.add(egui::DragValue::new(&mut self.copies).range(1..=device.max_copies));
+                    ui.label(if separate_copies {
+                        "Print Copies"
+                    } else {
+                        "Copies"
+                    });
+                });
+
+                if mode_type.has_printing() && mode_type.has_cutting() {
+                    ui.checkbox(&mut self.link_copies, "Cut Every Printed Copy")
+                        .on_hover_text("Use the same number of copies for printing and cutting");
+
+                    if separate_copies {
+                        ui.horizontal(|ui| {
+                            ui.add(
+                                egui::DragValue::new(&mut self.cut_copies)
+                                    .range(1..=device.max_copies),
+                            );
+                            ui.label("Cut Copies");
+                        });
+                    }
+                }
+
+                ui.checkbox(&mut self.fit_new_images, "Fit New Images to Safe Area")
+                    .on_hover_text("Scale and center newly added images within the safe area");
+
+                ui.add_enabled_ui(!self.fit_new_images, |ui| {
+                    egui::ComboBox::from_label("New Image Placement")
+                        .selected_text(self.image_placement.name())
+                        .show_ui(ui, |ui| {
+                            for placement in ImagePlacement::iter() {
+                                ui.selectable_value(
+                                    &mut self.image_placement,
+                                    placement,
+                                    placement.name(),
+                                );
+                            }
+                        });
                 });
 
+                ui.horizontal(|ui| {
+                    egui::color_picker::color_edit_button_srgba(
+                        ui,
+                        &mut self.background_color,
+                        egui::color_picker::Alpha::OnlyBlend,
+                    );
+                    ui.label("Background Color");
+                })
+                .response
+                .on_hover_text("Color printed wherever there's no image");
+
                 if DEVICES[self.selected_device].modes[self.selected_mode]
                     .mode_type
                     .has_cutting()
                 {
                     ui.separator();
 
+                    let cuts_stale = self.cuts_stale();
                     views::cut_controls(
                         ui,
-                        DEVICES[self.selected_device].dpi,
+                        DEVICES[self.selected_device].average_dpi(),
                         &mut self.cut_tuning,
                         self.cut_progress,
                         self.has_intersections,
                         self.off_canvas,
+                        cuts_stale,
                     );
+                    views::cut_diagnostics(ui, &self.cut_diagnostics, &self.cut_contour_stats);
 
                     if ui
                         .add_enabled(
@@ -886,27 +4258,15 @@ impl eframe::App for SapodillaApp {
                         )
                         .clicked()
                     {
-                        self.cut_shapes.clear();
-                        self.has_intersections = false;
-                        self.off_canvas = false;
-                        self.cut_progress = None;
-
-                        let tx = self.tx.clone();
-                        let mut rx = CutGenerator::start(
-                            self.loaded_images.clone(),
-                            self.cut_tuning.clone(),
-                            self.get_canvas(),
-                        );
-
-                        spawn(async move {
-                            while let Some(action) = rx.next().await {
-                                debug!(?action, "got cut action");
+                        self.generate_cut_lines();
+                    }
 
-                                if let Err(err) = tx.send(Action::Cut(action)) {
-                                    error!("could not send cut action: {err}");
-                                }
-                            }
-                        });
+                    if ui
+                        .add_enabled(self.can_start_job(), egui::Button::new("Test Cut"))
+                        .on_hover_text("Cut a small square in the center of the canvas")
+                        .clicked()
+                    {
+                        self.test_cut();
                     }
                 }
 
@@ -914,10 +4274,29 @@ impl eframe::App for SapodillaApp {
                     ui.separator();
                     views::loaded_images(
                         ui,
-                        DEVICES[self.selected_device].dpi,
+                        &DEVICES[self.selected_device],
                         self.get_canvas().size,
+                        self.get_canvas().safe_rect(),
                         &mut self.loaded_images,
+                        &mut self.tiling,
                     );
+
+                    ui.horizontal(|ui| {
+                        if ui
+                            .button("Auto Arrange")
+                            .on_hover_text("Pack images within the safe area without overlapping")
+                            .clicked()
+                        {
+                            self.auto_arrange();
+                        }
+
+                        ui.add(views::px_slider(
+                            &mut self.arrange_gap,
+                            DEVICES[self.selected_device].average_dpi(),
+                            0.0..=DEVICES[self.selected_device].average_dpi(),
+                        ));
+                        ui.label("Gap");
+                    });
                 }
             });
 
@@ -969,22 +4348,95 @@ impl eframe::App for SapodillaApp {
 
                     ui.label(err.to_string());
 
-                    if ui.button("Close").clicked() {
-                        ui.close();
-                    }
-                });
+                    // Only offer to retry when this error came from the
+                    // request that would be sent again.
+                    let failed_request = err
+                        .downcast_ref::<RequestFailed>()
+                        .zip(self.transport_manager.as_ref())
+                        .filter(|(failed, manager)| manager.has_failed_request(failed.msg_number))
+                        .map(|(_, manager)| manager);
+
+                    ui.horizontal(|ui| {
+                        if let Some(manager) = failed_request
+                            && ui
+                                .button("Retry")
+                                .on_hover_text("Send the request that failed again")
+                                .clicked()
+                        {
+                            let manager = manager.clone();
+                            let tx = self.tx.clone();
+
+                            spawn(async move {
+                                let res = manager.resend_last().await.and_then(|packet| {
+                                    packet.try_result::<serde_json::Value>()?;
+                                    Ok(packet)
+                                });
+
+                                match res {
+                                    Ok(packet) => {
+                                        let _ = tx.send(Action::ResentRequest(packet));
+                                    }
+                                    Err(err) => {
+                                        let _ = tx.send(Action::Error(err));
+                                    }
+                                }
+                            });
+
+                            ui.close();
+                        }
+
+                        if ui.button("Close").clicked() {
+                            ui.close();
+                        }
+                    });
+                });
+
+                if modal.should_close() {
+                    self.error = None;
+                }
+            }
+
+            if self.confirming_print {
+                self.print_confirmation(ui);
+            }
+
+            self.device_picker(ui);
+
+            self.media_mismatch_prompt(ui);
+
+            self.resent_response_prompt(ui);
+
+            self.oversized_image_prompt(ui);
 
-                if modal.should_close() {
-                    self.error = None;
-                }
-            }
+            self.tile_dialog(ui);
         });
 
         egui::Window::new("Packet Log")
             .open(&mut self.showing_packet_log)
             .default_size([1000.0, 300.0])
             .show(ctx, |ui| {
-                views::protocol_packets_table(ui, &self.packets, &mut self.viewing_packet)
+                ui.horizontal(|ui| {
+                    ui.add(egui::DragValue::new(&mut self.packet_log_limit).range(1..=100_000));
+                    ui.label("Maximum Packets");
+
+                    if ui.button("Clear Log").clicked() {
+                        self.packets.clear();
+                    }
+
+                    ui.separator();
+
+                    ui.selectable_value(&mut self.packet_log_timeline, false, "Table");
+                    ui.selectable_value(&mut self.packet_log_timeline, true, "Timeline");
+                });
+                self.packets.truncate(self.packet_log_limit);
+
+                ui.separator();
+
+                if self.packet_log_timeline {
+                    views::packet_timeline(ui, &self.packets, &mut self.viewing_packet)
+                } else {
+                    views::protocol_packets_table(ui, &self.packets, &mut self.viewing_packet)
+                }
             });
 
         views::packet_debug(
@@ -992,41 +4444,333 @@ impl eframe::App for SapodillaApp {
             &self.tx,
             &mut self.showing_avocado_packet_debug,
             &self.avocado_debug_packets,
+            self.avocado_debug_progress,
         );
+
+        views::log_viewer(ctx, &mut self.showing_logs, &mut self.log_level);
+        views::shortcuts_help(ctx, &mut self.showing_shortcuts);
+
+        self.color_window(ctx);
+
+        let can_send = self.can_start_job();
+        if let Some(index) = views::job_history(
+            ctx,
+            &mut self.showing_job_history,
+            &self.job_history,
+            can_send,
+        ) {
+            self.reprint(index);
+        }
+
+        self.raw_job_window(ctx, can_send);
+    }
+}
+
+/// The user account a job was sent with, from the first part of combo jobs.
+fn job_user_account(params: &serde_json::Value) -> Option<&str> {
+    params.get(0).map_or(params, |part| &part["params"])["user-account"].as_str()
+}
+
+/// Create a user account unique to this session, in the same shape as
+/// [`DEFAULT_USER_ACCOUNT`].
+fn session_user_account() -> String {
+    format!("000000.{}.0000", Uuid::new_v4().simple())
+}
+
+/// Create a job, send its data, and wait for it to finish.
+///
+/// Resolves once the job has reached a final state, so jobs can be run one
+/// after another.
+async fn run_job<F>(
+    manager: &TransportManager,
+    job: &JobRecord,
+    on_progress: F,
+) -> anyhow::Result<()>
+where
+    F: Fn(f32),
+{
+    let id = manager.next_message_id();
+    let method = job.method;
+
+    let packet = AvocadoPacket {
+        version: 100,
+        reserved: 0,
+        content_type: ContentType::Message,
+        interaction_type: InteractionType::Request,
+        encoding_type: EncodingType::Json,
+        encryption_mode: EncryptionMode::None,
+        terminal_id: manager.terminal_id(),
+        msg_number: id,
+        msg_package_total: 1,
+        msg_package_num: 1,
+        is_subpackage: false,
+        data: serde_json::to_vec(&serde_json::json!({
+            "id": id,
+            "method": method,
+            "params": job.params,
+        }))?,
+    };
+    debug!(?packet, "built {method} packet");
+
+    let packet = manager.wait_for_response(packet).await?;
+    debug!(?packet, "got response packet");
+
+    let job_id = packet.try_result::<JobResult>()?.job_id;
+    debug!(job_id, "got job id");
+
+    manager
+        .send_data(job_id, &job.data, |total, sent| {
+            debug!(total, sent, "sent data packet");
+            on_progress(sent as f32 / total as f32);
+        })
+        .await?;
+
+    // The transport is closed once the data is sent, so there's nothing left
+    // to wait on.
+    if manager.is_disconnecting() {
+        info!(job_id, "disconnecting, not waiting for job to finish");
+        return Ok(());
     }
+
+    manager
+        .poll_job(job_id, job_user_account(&job.params))
+        .await?;
+    info!("finished sending data");
+
+    Ok(())
+}
+
+/// Build the params for a `cut-job` request.
+fn cut_job_params(
+    mode_type: &ModeType,
+    canvas_size: &CanvasSize,
+    copies: usize,
+    plt_len: usize,
+    document_format: u16,
+    time: u64,
+) -> serde_json::Value {
+    serde_json::json!({
+        "copies": copies,
+        "media-size": canvas_size.media_size,
+        "document-name": format!("{}.plt", time),
+        "file-size": plt_len,
+        "channel": mode_type.channel(),
+        "media-type": canvas_size.media_type,
+        "job-type": mode_type.job_type(),
+        "document-format": document_format,
+        "job-send-time": time / 1000,
+    })
 }
 
-fn encode_image(im: &image::DynamicImage) -> Vec<u8> {
-    let mut buf = Vec::with_capacity(1024 * 1024);
-    let mut quality = 100;
-    loop {
-        // Image needs to be under 1MB, so decrease quality
-        // until we get there.
-        let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buf, quality);
-        encoder.encode_image(im).unwrap();
-        debug!(quality, len = buf.len(), "got jpeg size");
+/// Encode an image for printing, calling `on_attempt` after each attempt to
+/// fit it within the size limit.
+fn encode_image<F>(
+    im: &image::DynamicImage,
+    encoding: ImageEncoding,
+    on_attempt: F,
+) -> anyhow::Result<Vec<u8>>
+where
+    F: Fn(EncodeProgress),
+{
+    match encoding {
+        ImageEncoding::Jpeg => Ok(encode_jpeg_within(im, MAX_IMAGE_SIZE, on_attempt)),
+        ImageEncoding::Png => encode_png_within(im, MAX_IMAGE_SIZE),
+    }
+}
 
-        if buf.len() <= 1024 * 1024 || quality == 0 {
-            break;
+/// Encode a PNG, failing if it's larger than `limit` bytes.
+///
+/// PNG is lossless so there's no quality to reduce, the canvas has to be
+/// sent as JPEG instead.
+fn encode_png_within(im: &image::DynamicImage, limit: usize) -> anyhow::Result<Vec<u8>> {
+    let mut buf = Vec::new();
+
+    let writer = LimitedWriter {
+        buf: &mut buf,
+        limit,
+    };
+    match im.write_with_encoder(image::codecs::png::PngEncoder::new(writer)) {
+        Ok(()) => (),
+        Err(image::ImageError::IoError(err)) if err.kind() == std::io::ErrorKind::FileTooLarge => {
+            anyhow::bail!(
+                "canvas is larger than {} KB as a PNG, use JPEG instead",
+                limit / 1024
+            );
         }
+        Err(err) => return Err(err.into()),
+    }
+    debug!(len = buf.len(), "got png size");
+
+    Ok(buf)
+}
 
-        quality -= 1;
+/// Encode a JPEG at the highest quality that fits within `limit` bytes.
+///
+/// Attempts write into a single buffer that never grows past the limit, as
+/// large canvases at high quality can otherwise use many times the limit in
+/// memory, which the browser may not have.
+fn encode_jpeg_within<F>(im: &image::DynamicImage, limit: usize, on_attempt: F) -> Vec<u8>
+where
+    F: Fn(EncodeProgress),
+{
+    let mut buf = Vec::with_capacity(limit);
+
+    let attempt = |buf: &mut Vec<u8>, quality: u8| {
         buf.clear();
+
+        let mut writer = LimitedWriter { buf, limit };
+        let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut writer, quality);
+        let fits = encoder.encode_image(im).is_ok();
+        debug!(quality, len = buf.len(), fits, "got jpeg size");
+
+        on_attempt(EncodeProgress {
+            quality,
+            len: buf.len(),
+        });
+
+        fits
+    };
+
+    // Size generally grows with quality, so search for the highest quality
+    // that fits instead of trying each one.
+    let (mut low, mut high) = (1, 100);
+    let mut best = None;
+    let mut last = 0;
+    while low <= high {
+        let quality = low + (high - low) / 2;
+        last = quality;
+
+        if attempt(&mut buf, quality) {
+            best = Some(quality);
+            low = quality + 1;
+        } else {
+            high = quality - 1;
+        }
+    }
+
+    match best {
+        // The buffer holds the last attempt, which may not have been the best.
+        Some(quality) if quality != last => {
+            attempt(&mut buf, quality);
+        }
+        Some(_) => (),
+        None => {
+            warn!(limit, "image does not fit at any quality, sending lowest");
+            buf.clear();
+            let mut encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut buf, 1);
+            encoder.encode_image(im).unwrap();
+        }
     }
 
     buf
 }
 
+/// A writer that fails instead of growing its buffer past a limit.
+struct LimitedWriter<'a> {
+    buf: &'a mut Vec<u8>,
+    limit: usize,
+}
+
+impl Write for LimitedWriter<'_> {
+    fn write(&mut self, data: &[u8]) -> std::io::Result<usize> {
+        if self.buf.len() + data.len() > self.limit {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::FileTooLarge,
+                "encoded image exceeded size limit",
+            ));
+        }
+
+        self.buf.extend_from_slice(data);
+        Ok(data.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Load each image in a folder in name order, with the names of any files
+/// that couldn't be loaded.
+#[cfg(not(target_arch = "wasm32"))]
+fn load_folder(ctx: &egui::Context, folder: &std::path::Path) -> anyhow::Result<LoadedFolder> {
+    let mut paths: Vec<_> = std::fs::read_dir(folder)?
+        .filter_map(|entry| Some(entry.ok()?.path()))
+        .filter(|path| {
+            path.extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_ascii_lowercase().as_str()))
+        })
+        .collect();
+    paths.sort();
+
+    if paths.is_empty() {
+        anyhow::bail!("no images found in {}", folder.display());
+    }
+
+    let mut images = Vec::with_capacity(paths.len());
+    let mut errors = Vec::new();
+
+    for path in paths {
+        let name = path
+            .file_name()
+            .unwrap_or_default()
+            .to_string_lossy()
+            .into_owned();
+
+        match std::fs::read(&path)
+            .map_err(anyhow::Error::from)
+            .and_then(|data| LoadedImage::new(ctx, &data, None))
+        {
+            Ok(image) => images.push(image),
+            Err(err) => {
+                warn!(name, "could not load image from folder: {err}");
+                errors.push((name, err));
+            }
+        }
+    }
+
+    Ok(LoadedFolder { images, errors })
+}
+
 fn encode_plt(
     cut_shapes: &[geo::MultiPolygon<f32>],
     cutter_calibration: CutterCalibration,
     canvas_size: &CanvasSize,
+    cut_tuning: &CutTuning,
 ) -> Vec<u8> {
-    let mut buf = b"IN VER0.1.0 KP42".to_vec();
+    // The vendor app always sends KP42, which appears to be the blade
+    // pressure. VS is the usual HPGL speed command, and is only sent when set.
+    let pressure = cut_tuning.cut_pressure.unwrap_or(DEFAULT_CUT_PRESSURE);
+    let mut buf = format!("IN VER0.1.0 KP{pressure}").into_bytes();
+    if let Some(speed) = cut_tuning.cut_speed {
+        write!(buf, " VS{speed}").unwrap();
+    }
+
+    for line_string in cut_paths(cut_shapes, canvas_size, cutter_calibration.mirror) {
+        write_line_string(&cutter_calibration, &mut buf, &line_string);
+    }
+
+    write!(buf, " U6476,0 @ ").unwrap();
+
+    buf
+}
 
-    let flipped = CutGenerator::mirror_cuts(cut_shapes.iter(), canvas_size.size);
+/// Flatten cut shapes into the line strings to cut, in the order they should
+/// be cut, optionally mirroring them first.
+fn cut_paths(
+    cut_shapes: &[geo::MultiPolygon<f32>],
+    canvas_size: &CanvasSize,
+    mirror: Option<Axis>,
+) -> impl Iterator<Item = geo::LineString<f32>> {
+    let cut_shapes: Vec<_> = match mirror {
+        Some(axis) => {
+            CutGenerator::mirror_cuts(cut_shapes.iter(), canvas_size.size, axis).collect()
+        }
+        None => cut_shapes.to_vec(),
+    };
 
-    let mut polygons: Vec<_> = flipped
+    let mut polygons: Vec<_> = cut_shapes
+        .into_iter()
         .flat_map(|multi_polygon| multi_polygon.0.into_iter())
         .collect();
     polygons.sort_by(|a, b| {
@@ -1039,15 +4783,73 @@ fn encode_plt(
             .then(a_start.x.total_cmp(&b_start.x))
     });
 
-    for polygon in polygons {
-        write_line_string(&cutter_calibration, &mut buf, polygon.exterior());
+    polygons.into_iter().flat_map(|polygon| {
+        let (exterior, interiors) = polygon.into_inner();
+        std::iter::once(exterior).chain(interiors)
+    })
+}
+
+/// Moves between the end of each cut and the start of the next, in the order
+/// [`encode_plt`] sends them when mirroring across `mirror`, in the same
+/// coordinates as the cut shapes.
+fn plt_travel_moves(
+    cut_shapes: &[geo::MultiPolygon<f32>],
+    canvas_size: &CanvasSize,
+    mirror: Option<Axis>,
+) -> Vec<(Pos2, Pos2)> {
+    // Mirroring is its own inverse, so this also undoes it.
+    let unmirror = |coord: geo::Coord<f32>| match mirror {
+        Some(Axis::X) => Pos2::new(canvas_size.size.x - coord.x, coord.y),
+        Some(Axis::Y) => Pos2::new(coord.x, canvas_size.size.y - coord.y),
+        None => Pos2::new(coord.x, coord.y),
+    };
+
+    cut_paths(cut_shapes, canvas_size, mirror)
+        .filter_map(|line_string| Some((*line_string.0.first()?, *line_string.0.last()?)))
+        .tuple_windows()
+        .map(|((_, end), (start, _))| (unmirror(end), unmirror(start)))
+        .collect()
+}
+
+/// Encode cut shapes as G-code for generic cutters, in millimeters.
+///
+/// Shapes are mirrored vertically so the origin is at the bottom of the
+/// canvas. The blade is raised and lowered on the Z axis.
+fn encode_gcode(
+    cut_shapes: &[geo::MultiPolygon<f32>],
+    canvas_size: &CanvasSize,
+    dpi: Vec2,
+) -> Vec<u8> {
+    const BLADE_UP: f32 = 5.0;
+    const BLADE_DOWN: f32 = 0.0;
+
+    let x_mm = |val: f32| val / dpi.x * 25.4;
+    let y_mm = |val: f32| val / dpi.y * 25.4;
+
+    let mut buf = Vec::new();
+    writeln!(buf, "G21").unwrap();
+    writeln!(buf, "G90").unwrap();
+    writeln!(buf, "G0 Z{BLADE_UP:.3}").unwrap();
+
+    for line_string in cut_paths(cut_shapes, canvas_size, Some(Axis::Y)) {
+        let mut coords = line_string.coords();
+
+        let Some(start) = coords.next() else {
+            continue;
+        };
+
+        writeln!(buf, "G0 X{:.3} Y{:.3}", x_mm(start.x), y_mm(start.y)).unwrap();
+        writeln!(buf, "G1 Z{BLADE_DOWN:.3}").unwrap();
 
-        for interior in polygon.interiors() {
-            write_line_string(&cutter_calibration, &mut buf, interior);
+        for point in coords {
+            writeln!(buf, "G1 X{:.3} Y{:.3}", x_mm(point.x), y_mm(point.y)).unwrap();
         }
+
+        writeln!(buf, "G0 Z{BLADE_UP:.3}").unwrap();
     }
 
-    write!(buf, " U6476,0 @ ").unwrap();
+    writeln!(buf, "G0 X0 Y0").unwrap();
+    writeln!(buf, "M2").unwrap();
 
     buf
 }
@@ -1057,34 +4859,704 @@ fn write_line_string(
     buf: &mut Vec<u8>,
     line_shape: &geo::LineString<f32>,
 ) {
-    write!(
-        buf,
-        " U{:.0},{:.0}",
-        (line_shape.0[0].y + cutter_calibration.offset.y) * cutter_calibration.scale_factor,
-        (line_shape.0[0].x + cutter_calibration.offset.x) * cutter_calibration.scale_factor
-    )
-    .unwrap();
+    write!(buf, " U{}", cutter_calibration.to_cutter(line_shape.0[0])).unwrap();
 
     for point in line_shape.coords() {
-        write!(
-            buf,
-            " D{:.0},{:.0}",
-            (point.y + cutter_calibration.offset.y) * cutter_calibration.scale_factor,
-            (point.x + cutter_calibration.offset.x) * cutter_calibration.scale_factor
-        )
-        .unwrap();
+        write!(buf, " D{}", cutter_calibration.to_cutter(*point)).unwrap();
     }
 }
 
-#[cfg(target_arch = "wasm32")]
-fn current_timestamp_millis() -> u64 {
-    web_sys::window().unwrap().performance().unwrap().now() as u64
-}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::transports::mock::MockTransport;
+
+    #[test]
+    fn test_encode_plt() {
+        let square = geo::MultiPolygon::new(vec![geo::Polygon::new(
+            geo::LineString::from(vec![
+                (100.0, 100.0),
+                (200.0, 100.0),
+                (200.0, 200.0),
+                (100.0, 200.0),
+                (100.0, 100.0),
+            ]),
+            vec![],
+        )]);
+
+        let device = &DEVICES[0];
+        let plt = encode_plt(
+            std::slice::from_ref(&square),
+            device.cutter_calibration.clone().unwrap_or_default(),
+            &device.modes[1].canvas_sizes[0],
+            &CutTuning::default(),
+        );
 
-#[cfg(not(target_arch = "wasm32"))]
-fn current_timestamp_millis() -> u64 {
-    std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)
-        .unwrap()
-        .as_millis() as u64
+        assert_eq!(
+            String::from_utf8(plt).unwrap(),
+            "IN VER0.1.0 KP42 U6819,312 D6819,312 D6819,655 D6476,655 D6476,312 D6819,312 U6476,0 @ "
+        );
+
+        let plt = encode_plt(
+            &[square],
+            device.cutter_calibration.clone().unwrap_or_default(),
+            &device.modes[1].canvas_sizes[0],
+            &CutTuning {
+                cut_pressure: Some(60),
+                cut_speed: Some(10),
+                ..Default::default()
+            },
+        );
+        assert!(
+            String::from_utf8(plt)
+                .unwrap()
+                .starts_with("IN VER0.1.0 KP60 VS10 U6819,312")
+        );
+    }
+
+    #[test]
+    fn test_travel_moves_match_plt() {
+        let device = &DEVICES[0];
+        let calibration = device.cutter_calibration.clone().unwrap();
+        let canvas_size = &device.modes[1].canvas_sizes[0];
+
+        // Mirroring reverses the order these are cut in.
+        let square = |x: f32, y: f32| -> geo::MultiPolygon<f32> {
+            geo::Rect::new(
+                geo::coord! { x: x, y: y },
+                geo::coord! { x: x + 50.0, y: y + 50.0 },
+            )
+            .to_polygon()
+            .into()
+        };
+        let cut_shapes = [
+            square(100.0, 100.0),
+            square(300.0, 500.0),
+            square(500.0, 300.0),
+        ];
+
+        let plt = encode_plt(
+            &cut_shapes,
+            calibration.clone(),
+            canvas_size,
+            &CutTuning::default(),
+        );
+        let plt = String::from_utf8(plt).unwrap();
+
+        // Skip the move to the first cut and the move away at the end.
+        let pen_up: Vec<_> = plt
+            .split(' ')
+            .filter_map(|command| command.strip_prefix('U'))
+            .collect();
+        let pen_up = &pen_up[1..pen_up.len() - 1];
+
+        let moves = plt_travel_moves(&cut_shapes, canvas_size, calibration.mirror);
+        assert_ne!(moves, plt_travel_moves(&cut_shapes, canvas_size, None));
+
+        let to_cutter = |pos: Pos2| {
+            let mirrored = geo::coord! { x: pos.x, y: canvas_size.size.y - pos.y };
+            calibration.to_cutter(mirrored).to_string()
+        };
+        let ends: Vec<_> = moves.iter().map(|(_, end)| to_cutter(*end)).collect();
+        assert_eq!(pen_up, ends);
+
+        // Cuts are closed, so each move starts where the one before it ended.
+        for ((_, end), (start, _)) in moves.iter().tuple_windows() {
+            assert_eq!(end, start);
+        }
+    }
+
+    #[test]
+    fn test_encode_gcode_non_square_dpi() {
+        // A square inch on a device with twice the vertical resolution.
+        let rect =
+            geo::MultiPolygon::from(geo::Rect::new((100.0, 100.0), (400.0, 700.0)).to_polygon());
+
+        let gcode = encode_gcode(
+            &[rect],
+            &DEVICES[0].modes[1].canvas_sizes[0],
+            Vec2::new(300.0, 600.0),
+        );
+
+        let (xs, ys): (Vec<f32>, Vec<f32>) = String::from_utf8(gcode)
+            .unwrap()
+            .lines()
+            .filter_map(|line| line.strip_prefix("G1 X"))
+            .map(|coords| {
+                let (x, y) = coords.split_once(" Y").unwrap();
+                (x.parse::<f32>().unwrap(), y.parse::<f32>().unwrap())
+            })
+            .unzip();
+
+        let span = |values: &[f32]| {
+            values.iter().copied().fold(f32::MIN, f32::max)
+                - values.iter().copied().fold(f32::MAX, f32::min)
+        };
+        assert!((span(&xs) - 25.4).abs() < 0.01);
+        assert!((span(&ys) - 25.4).abs() < 0.01);
+    }
+
+    #[test]
+    fn test_encode_jpeg_within() {
+        // Noise doesn't compress well, so quality has to be reduced to fit.
+        let im = image::RgbaImage::from_fn(64, 64, |x, y| {
+            let value = (x * 7919 + y * 104729) as u8;
+            image::Rgba([value, value.wrapping_mul(3), value.wrapping_mul(7), 255])
+        });
+        let im = image::DynamicImage::ImageRgba8(im);
+
+        let full_quality = encode_jpeg_within(&im, MAX_IMAGE_SIZE, |_| ());
+        let limit = full_quality.len() / 2;
+
+        let attempts = std::sync::Mutex::new(Vec::new());
+        let buf = encode_jpeg_within(&im, limit, |progress| {
+            attempts.lock().unwrap().push(progress.quality);
+        });
+
+        assert!(buf.len() <= limit);
+        assert!(buf.capacity() <= limit);
+        assert!(image::load_from_memory(&buf).is_ok());
+        assert!(attempts.into_inner().unwrap().len() <= 8);
+    }
+
+    #[test]
+    fn test_encode_png_within() {
+        let im = image::DynamicImage::ImageRgba8(image::RgbaImage::from_fn(64, 64, |x, y| {
+            let value = (x * 7919 + y * 104729) as u8;
+            image::Rgba([value, value.wrapping_mul(3), value.wrapping_mul(7), 255])
+        }));
+
+        let buf = encode_png_within(&im, MAX_IMAGE_SIZE).unwrap();
+        assert_eq!(image::load_from_memory(&buf).unwrap(), im);
+
+        // There's no quality to lower, so it fails instead of shrinking.
+        assert!(encode_png_within(&im, buf.len() / 2).is_err());
+    }
+
+    /// Start a manager for a mock transport, waiting until it's connected.
+    async fn connect_mock() -> (
+        Rc<TransportManager>,
+        Rc<Mutex<Transport>>,
+        futures::channel::mpsc::UnboundedReceiver<TransportEvent>,
+    ) {
+        connect(MockTransport::default()).await
+    }
+
+    async fn connect(
+        mock: MockTransport,
+    ) -> (
+        Rc<TransportManager>,
+        Rc<Mutex<Transport>>,
+        futures::channel::mpsc::UnboundedReceiver<TransportEvent>,
+    ) {
+        let (event_tx, mut event_rx) = futures::channel::mpsc::unbounded();
+        let transport = Rc::new(Mutex::new(Transport::MockTransport(mock)));
+        let manager = TransportManager::new(transport.clone(), None, move |event| {
+            let _ = event_tx.unbounded_send(event);
+        });
+
+        while let Some(event) = event_rx.next().await {
+            if matches!(
+                event,
+                TransportEvent::TransportStatus(TransportStatus::Connected)
+            ) {
+                break;
+            }
+        }
+
+        (manager, transport, event_rx)
+    }
+
+    /// A request for the first device's mode of the given type, without any
+    /// data, to fill in with struct update syntax.
+    fn print_request(mode_type: ModeType) -> PrintRequest<'static> {
+        let device = &DEVICES[0];
+        let mode = device
+            .modes
+            .iter()
+            .find(|mode| mode.mode_type == mode_type)
+            .unwrap();
+
+        PrintRequest {
+            encoded_image: Vec::new(),
+            plt: Vec::new(),
+            mode,
+            canvas_size: &mode.canvas_sizes[0],
+            image_format: &device.image_formats[0],
+            quirks: &device.quirks,
+            user_account: DEFAULT_USER_ACCOUNT,
+            copies: 1,
+            cut_copies: 1,
+            skip_cut: false,
+            overrides: Default::default(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_connection_steps() {
+        let (_manager, _transport, mut event_rx) = connect_mock().await;
+
+        let mut steps = Vec::new();
+        while let Some(event) = event_rx.next().await {
+            if let TransportEvent::ConnectionStep(step) = event {
+                steps.push(step.clone());
+
+                if step == ConnectionStep::Ready {
+                    break;
+                }
+            }
+        }
+
+        assert_eq!(steps, [ConnectionStep::Handshaking, ConnectionStep::Ready]);
+    }
+
+    #[tokio::test]
+    async fn test_corrupted_transfer() {
+        let (manager, _transport, _event_rx) = connect(MockTransport::corrupting(1)).await;
+
+        let request = PrintRequest {
+            encoded_image: vec![1; 2000],
+            plt: vec![2; 100],
+            ..print_request(ModeType::PrintAndCut)
+        };
+        manager
+            .set_media(
+                request.canvas_size.media_size,
+                request.canvas_size.media_type,
+            )
+            .await
+            .unwrap();
+
+        let job = request.job(0);
+
+        let err = run_job(&manager, &job, |_| ()).await.unwrap_err();
+        assert!(err.is::<TransferCorrupted>());
+
+        // Only the first job was corrupted, so sending it again works.
+        run_job(&manager, &job, |_| ()).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_print_job() {
+        let (manager, transport, mut event_rx) = connect_mock().await;
+
+        let request = print_request(ModeType::Print);
+        let request = PrintRequest {
+            encoded_image: encode_image(
+                &image::DynamicImage::new_rgb8(100, 100),
+                request.image_format.encoding,
+                |_| (),
+            )
+            .unwrap(),
+            ..request
+        };
+        let job = request.job(0);
+        assert_eq!(job.method, "print-job");
+        assert_eq!(job.params["file-size"], request.encoded_image.len());
+
+        let progress = std::sync::Mutex::new(Vec::new());
+        run_job(&manager, &job, |sent| progress.lock().unwrap().push(sent))
+            .await
+            .unwrap();
+
+        let chunks = job.data.len().div_ceil(manager.data_size() - JOB_ID_SIZE);
+        assert_eq!(progress.into_inner().unwrap().last(), Some(&1.0));
+
+        let transport = transport.lock().await;
+        let Transport::MockTransport(mock) = &*transport else {
+            unreachable!();
+        };
+
+        let methods: Vec<_> = mock
+            .sent_packets()
+            .iter()
+            .filter_map(|packet| packet.as_json::<AvocadoMethod>())
+            .map(|request| request.method)
+            .filter(|method| method != "get-prop")
+            .collect();
+        assert_eq!(methods.first().map(String::as_str), Some("print-job"));
+        assert_eq!(methods.last().map(String::as_str), Some("get-job-info"));
+
+        let data_packets: Vec<_> = mock
+            .sent_packets()
+            .iter()
+            .filter(|packet| packet.content_type == ContentType::Data)
+            .collect();
+        assert_eq!(data_packets.len(), chunks);
+        assert!(
+            data_packets
+                .iter()
+                .all(|packet| packet.data[..JOB_ID_SIZE] == 1u32.to_le_bytes())
+        );
+        drop(transport);
+
+        // Job statuses are delivered through the event stream, so they may
+        // arrive after the job has finished.
+        let completed = tokio::time::timeout(std::time::Duration::from_secs(5), async {
+            while let Some(event) = event_rx.next().await {
+                if let TransportEvent::JobStatus(info) = event
+                    && info.job_state == JobState::Completed
+                {
+                    return true;
+                }
+            }
+
+            false
+        });
+        assert!(completed.await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_media_mismatch_job() {
+        let (manager, _transport, _event_rx) = connect(MockTransport::checking_media()).await;
+
+        // The mock has media for the first mode loaded, which cut only jobs
+        // don't use.
+        let mode = &DEVICES[0].modes[2];
+        let canvas_size = &mode.canvas_sizes[0];
+        let params = cut_job_params(&mode.mode_type, canvas_size, 1, 4, PLT_DOCUMENT_FORMAT, 0);
+        let job = JobRecord {
+            name: "Mismatch".to_string(),
+            method: "cut-job",
+            params,
+            data: vec![0; 4],
+        };
+
+        let err = run_job(&manager, &job, |_| ()).await.unwrap_err();
+        let mismatch = err.downcast::<MediaMismatch>().unwrap();
+        assert!(canvas_size.matches_media(&mismatch.expected));
+        assert!(DEVICES[0].modes[0].canvas_sizes[0].matches_media(&mismatch.loaded));
+
+        // Once the device is told the right media is loaded, it's accepted.
+        manager
+            .set_media(canvas_size.media_size, canvas_size.media_type)
+            .await
+            .unwrap();
+        assert!(canvas_size.matches_media(&manager.get_media().await.unwrap()));
+        run_job(&manager, &job, |_| ()).await.unwrap();
+    }
+
+    #[test]
+    fn test_combo_job_copies() {
+        let request = PrintRequest {
+            encoded_image: vec![0; 8],
+            plt: vec![0; 4],
+            copies: 3,
+            ..print_request(ModeType::PrintAndCut)
+        };
+
+        let job = request.job(0);
+        assert_eq!(job.method, "combo-job");
+        assert_eq!(job.params[0]["params"]["copies"], 3);
+        assert_eq!(job.params[1]["params"]["copies"], 1);
+        assert_eq!(job_user_account(&job.params), Some(DEFAULT_USER_ACCOUNT));
+
+        let job = PrintRequest {
+            skip_cut: true,
+            ..request
+        }
+        .job(0);
+        assert_eq!(job.method, "print-job");
+        assert_eq!(job.data.len(), 8);
+        assert_eq!(job_user_account(&job.params), Some(DEFAULT_USER_ACCOUNT));
+    }
+
+    #[test]
+    fn test_job_format_overrides() {
+        let request = PrintRequest {
+            encoded_image: vec![0; 8],
+            plt: vec![0; 4],
+            overrides: JobFormatOverrides {
+                document_format: Some(3),
+                cut_document_format: Some(4),
+                hash_method: Some(HashMethod::Md5),
+            },
+            ..print_request(ModeType::PrintAndCut)
+        };
+
+        let job = request.job(0);
+        let print = &job.params[0]["params"];
+        assert_eq!(print["document-format"], 3);
+        assert_eq!(print["hash-method"], 2);
+        assert_eq!(print["hash-value"], "7dea362b3fac8e00956a4952a3d4f474");
+        assert_eq!(job.params[1]["params"]["document-format"], 4);
+    }
+
+    #[test]
+    fn test_editable_job() {
+        let mut editable = EditableJob::new(JobRecord {
+            name: "Test".to_string(),
+            method: "print-job",
+            params: serde_json::json!({ "user-account": "0", "copies": 1 }),
+            data: vec![1, 2, 3],
+        });
+
+        let job = editable.edited().unwrap();
+        assert_eq!(job.params["copies"], 1);
+
+        editable.json = editable.json.replace("\"0\"", "\"1234\"");
+        let job = editable.edited().unwrap();
+        assert_eq!(job.params["user-account"], "1234");
+        assert_eq!(job.data, vec![1, 2, 3]);
+
+        editable.json.push('}');
+        assert!(editable.edited().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_device_quirks() {
+        let (manager, transport, _event_rx) = connect_mock().await;
+
+        manager.set_data_size(MAX_DATA_SIZE).unwrap();
+        manager.set_quirks(DeviceQuirks {
+            reserved_byte: 3,
+            max_data_size: 64,
+            ..Default::default()
+        });
+        assert_eq!(manager.data_size(), 64);
+
+        manager.send_data(1, &[0; 100], |_, _| ()).await.unwrap();
+
+        let transport = transport.lock().await;
+        let Transport::MockTransport(mock) = &*transport else {
+            unreachable!();
+        };
+
+        let data_packets: Vec<_> = mock
+            .sent_packets()
+            .iter()
+            .filter(|packet| packet.content_type == ContentType::Data)
+            .collect();
+        assert_eq!(data_packets.len(), 2);
+        assert!(
+            data_packets
+                .iter()
+                .all(|packet| packet.reserved == 3 && packet.data.len() <= 64)
+        );
+    }
+
+    #[tokio::test]
+    async fn test_combo_job_golden() {
+        let (manager, transport, _event_rx) = connect(MockTransport::recording()).await;
+
+        let square = geo::MultiPolygon::new(vec![geo::Polygon::new(
+            geo::LineString::from(vec![
+                (100.0, 100.0),
+                (200.0, 100.0),
+                (200.0, 200.0),
+                (100.0, 200.0),
+                (100.0, 100.0),
+            ]),
+            vec![],
+        )]);
+
+        // Stand in for the image so only the params and framing are checked,
+        // not the encoder's output.
+        let request = print_request(ModeType::PrintAndCut);
+        let request = PrintRequest {
+            encoded_image: (0..=255).cycle().take(636).collect(),
+            plt: encode_plt(
+                &[square],
+                DEVICES[0].cutter_calibration.clone().unwrap_or_default(),
+                request.canvas_size,
+                &CutTuning::default(),
+            ),
+            ..request
+        };
+        let job = request.job(0);
+
+        run_job(&manager, &job, |_| ()).await.unwrap();
+
+        let transport = transport.lock().await;
+        let Transport::MockTransport(mock) = &*transport else {
+            unreachable!();
+        };
+
+        // Keep each packet on its own line so changes are easier to find.
+        let recorded: String = PacketFramer::default()
+            .push(mock.recorded_bytes())
+            .unwrap()
+            .iter()
+            .map(|(packet, _)| hex::encode(packet.encode()) + "\n")
+            .collect();
+
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/src/testdata/combo_job.hex");
+        if std::env::var_os("UPDATE_GOLDEN").is_some() {
+            std::fs::write(path, &recorded).unwrap();
+        }
+
+        assert_eq!(
+            recorded,
+            std::fs::read_to_string(path).unwrap(),
+            "bytes sent for job changed, run with UPDATE_GOLDEN=1 if expected"
+        );
+    }
+
+    #[test]
+    fn test_shelf_pack() {
+        let area = egui::Rect::from_min_size(Pos2::new(10.0, 10.0), Vec2::new(100.0, 100.0));
+        let sizes = [
+            Vec2::new(40.0, 20.0),
+            Vec2::new(40.0, 40.0),
+            Vec2::new(40.0, 30.0),
+        ];
+
+        let positions = shelf_pack(&sizes, area, 5.0).unwrap();
+        assert_eq!(
+            positions,
+            [
+                Pos2::new(10.0, 55.0),
+                Pos2::new(10.0, 10.0),
+                Pos2::new(55.0, 10.0),
+            ]
+        );
+
+        assert!(shelf_pack(&[Vec2::new(120.0, 10.0)], area, 0.0).is_none());
+        assert!(shelf_pack(&[Vec2::splat(60.0), Vec2::splat(60.0)], area, 0.0).is_none());
+    }
+
+    #[test]
+    fn test_oversized_image() {
+        let ctx = egui::Context::default();
+
+        let mut data = Vec::new();
+        let max_dimension = LoadedImage::max_dimension(&ctx);
+        image::DynamicImage::new_luma8(max_dimension + 1, 1)
+            .write_to(
+                &mut std::io::Cursor::new(&mut data),
+                image::ImageFormat::Png,
+            )
+            .unwrap();
+
+        let err = LoadedImage::new(&ctx, &data, None).err().unwrap();
+        let too_large = err.downcast::<ImageTooLarge>().unwrap();
+        assert_eq!(too_large.width, max_dimension + 1);
+
+        let image = LoadedImage::new_downscaled(&ctx, &too_large.data).unwrap();
+        assert_eq!(image.image.dimensions(), (max_dimension, 1));
+    }
+
+    #[test]
+    fn test_image_too_large_to_downscale() {
+        let ctx = egui::Context::default();
+
+        let mut data = Vec::new();
+        image::DynamicImage::new_luma8(8, 8)
+            .write_to(
+                &mut std::io::Cursor::new(&mut data),
+                image::ImageFormat::Jpeg,
+            )
+            .unwrap();
+
+        // Make the frame header claim a size that would take over a gigabyte
+        // to decode, without having to create an image that large.
+        let sof = data
+            .windows(2)
+            .position(|marker| marker == [0xFF, 0xC0])
+            .unwrap();
+        data[sof + 5..sof + 7].copy_from_slice(&20_000u16.to_be_bytes());
+        data[sof + 7..sof + 9].copy_from_slice(&20_000u16.to_be_bytes());
+
+        let err = LoadedImage::new(&ctx, &data, None).err().unwrap();
+        assert!(!err.is::<ImageTooLarge>());
+        assert!(LoadedImage::new_downscaled(&ctx, &data).is_err());
+    }
+
+    #[test]
+    fn test_load_folder() {
+        let ctx = egui::Context::default();
+
+        let folder = std::env::temp_dir().join(format!("sapodilla-{}", Uuid::new_v4()));
+        std::fs::create_dir(&folder).unwrap();
+
+        image::RgbaImage::new(4, 4)
+            .save(folder.join("a.png"))
+            .unwrap();
+        std::fs::write(folder.join("b.jpg"), b"not an image").unwrap();
+        std::fs::write(folder.join("c.txt"), b"ignored").unwrap();
+
+        let loaded = load_folder(&ctx, &folder).unwrap();
+        std::fs::remove_dir_all(&folder).unwrap();
+
+        assert_eq!(loaded.images.len(), 1);
+        assert_eq!(loaded.errors.len(), 1);
+        assert_eq!(loaded.errors[0].0, "b.jpg");
+    }
+
+    #[test]
+    fn test_transfer_stats() {
+        let mut stats = TransferStats::new(10_000);
+        assert!(stats.remaining().is_none());
+
+        // Packets add a tenth to the data written.
+        stats.push(0, 550);
+        stats.push(1_000, 550);
+        stats.progress = 0.1;
+        assert_eq!(stats.sent_bytes(), 1_000);
+        assert_eq!(stats.rate().unwrap().round(), 550.0);
+        assert_eq!(stats.remaining().unwrap().as_secs(), 18);
+
+        // Older writes stop counting once the transfer slows down.
+        stats.push(20_000, 100);
+        stats.push(30_000, 1_000);
+        assert_eq!(stats.rate().unwrap().round(), 100.0);
+    }
+
+    #[test]
+    fn test_crop_image() {
+        let ctx = egui::Context::default();
+
+        let mut image = LoadedImage::from_image(
+            &ctx,
+            image::RgbaImage::new(10, 10),
+            Some(Pos2::new(5.0, 5.0)),
+        );
+        image.rescale(Vec2::splat(2.0));
+        let uncropped = image.rect();
+
+        image.set_crop(Some(egui::Rect::from_min_max(
+            Pos2::new(0.2, 0.0),
+            Pos2::new(0.6, 0.52),
+        )));
+        assert_eq!(image.cropped_image().dimensions(), (4, 5));
+        assert_eq!(image.size(), Vec2::new(8.0, 10.0));
+        assert_eq!(image.offset, uncropped.min + Vec2::new(4.0, 0.0));
+        assert_eq!(image.uncropped_rect(), uncropped);
+
+        // Crops without any pixels don't change anything.
+        image.set_crop(Some(egui::Rect::from_min_max(
+            Pos2::new(0.5, 0.5),
+            Pos2::new(0.51, 0.9),
+        )));
+        assert_eq!(image.size(), Vec2::new(8.0, 10.0));
+
+        image.set_crop(None);
+        assert_eq!(image.rect(), uncropped);
+    }
+
+    #[test]
+    fn test_reset_image() {
+        let ctx = egui::Context::default();
+
+        let mut image = LoadedImage::from_image(
+            &ctx,
+            image::RgbaImage::new(10, 10),
+            Some(Pos2::new(5.0, 5.0)),
+        );
+        image.rescale(Vec2::new(2.0, 3.0));
+        image.offset = Pos2::new(40.0, 20.0);
+        image.scale_locked = false;
+        image.opacity = 0.5;
+        image.set_adjustment(ColorAdjustment {
+            brightness: 0.5,
+            ..Default::default()
+        });
+
+        image.reset();
+        assert_eq!(image.offset, Pos2::new(5.0, 5.0));
+        assert_eq!(image.size(), Vec2::splat(10.0));
+        assert!(image.scale_locked);
+        assert_eq!(image.opacity, 1.0);
+        assert!(image.adjustment.is_identity());
+    }
 }
@@ -0,0 +1,142 @@
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+
+/// A simple tone curve and saturation adjustment.
+///
+/// This isn't full color management, but gets prints much closer to what's
+/// shown on screen.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorAdjustment {
+    /// Amount to add to each channel, from -1 to 1.
+    pub brightness: f32,
+    /// Gamma to apply, values above 1 brighten midtones.
+    pub gamma: f32,
+    /// Contrast multiplier around the midpoint.
+    pub contrast: f32,
+    /// Saturation multiplier, 0 is grayscale.
+    pub saturation: f32,
+}
+
+impl Default for ColorAdjustment {
+    fn default() -> Self {
+        Self {
+            brightness: 0.0,
+            gamma: 1.0,
+            contrast: 1.0,
+            saturation: 1.0,
+        }
+    }
+}
+
+impl ColorAdjustment {
+    pub fn is_identity(&self) -> bool {
+        *self == Self::default()
+    }
+
+    /// Apply the adjustment to every pixel of an image, leaving alpha as is.
+    pub fn apply(&self, im: &mut RgbaImage) {
+        if self.is_identity() {
+            return;
+        }
+
+        // The tone curve only depends on each channel's value, so it can be
+        // calculated once for every possible value.
+        let curve: [f32; 256] = std::array::from_fn(|value| {
+            let value = (value as f32 / 255.0).powf(1.0 / self.gamma);
+            ((value - 0.5) * self.contrast + 0.5 + self.brightness) * 255.0
+        });
+
+        for pixel in im.pixels_mut() {
+            let [r, g, b, _] = &mut pixel.0;
+            let (red, green, blue) = (curve[*r as usize], curve[*g as usize], curve[*b as usize]);
+
+            let luma = 0.299 * red + 0.587 * green + 0.114 * blue;
+            let saturate = |value: f32| {
+                (luma + (value - luma) * self.saturation)
+                    .round()
+                    .clamp(0.0, 255.0) as u8
+            };
+
+            *r = saturate(red);
+            *g = saturate(green);
+            *b = saturate(blue);
+        }
+    }
+}
+
+/// Approximate share of each ink or ribbon panel an image would use.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct InkCoverage {
+    pub cyan: f32,
+    pub magenta: f32,
+    pub yellow: f32,
+}
+
+impl InkCoverage {
+    /// Coverage above which an image is considered to use a lot of ink.
+    const HIGH_COVERAGE: f32 = 0.6;
+
+    /// Estimate coverage from how far each channel is from white, ignoring
+    /// any transparency.
+    pub fn estimate(im: &RgbaImage) -> Self {
+        let mut totals = [0u64; 3];
+        for pixel in im.pixels() {
+            for (total, value) in totals.iter_mut().zip(pixel.0) {
+                *total += u64::from(255 - value);
+            }
+        }
+
+        let max = (im.pixels().len() as f32 * 255.0).max(1.0);
+        let [cyan, magenta, yellow] = totals.map(|total| total as f32 / max);
+
+        Self {
+            cyan,
+            magenta,
+            yellow,
+        }
+    }
+
+    pub fn average(&self) -> f32 {
+        (self.cyan + self.magenta + self.yellow) / 3.0
+    }
+
+    pub fn is_high(&self) -> bool {
+        self.average() >= Self::HIGH_COVERAGE
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_apply() {
+        let pixel = image::Rgba([200, 100, 50, 128]);
+
+        let mut im = RgbaImage::from_pixel(1, 1, pixel);
+        ColorAdjustment::default().apply(&mut im);
+        assert_eq!(im.get_pixel(0, 0), &pixel);
+
+        let mut im = RgbaImage::from_pixel(1, 1, pixel);
+        ColorAdjustment {
+            saturation: 0.0,
+            ..Default::default()
+        }
+        .apply(&mut im);
+        assert_eq!(im.get_pixel(0, 0), &image::Rgba([124, 124, 124, 128]));
+    }
+
+    #[test]
+    fn test_ink_coverage() {
+        let coverage = InkCoverage::estimate(&RgbaImage::from_pixel(2, 2, image::Rgba([255; 4])));
+        assert_eq!(coverage.average(), 0.0);
+        assert!(!coverage.is_high());
+
+        let coverage =
+            InkCoverage::estimate(&RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 255, 255])));
+        assert_eq!(coverage.cyan, 1.0);
+        assert_eq!(coverage.yellow, 0.0);
+        assert!(coverage.is_high());
+    }
+}
@@ -3,15 +3,26 @@ use std::collections::HashMap;
 use egui::Vec2;
 use futures::channel::mpsc::{UnboundedReceiver, UnboundedSender, unbounded};
 use geo::{
-    Buffer, ChaikinSmoothing, Contains, Coord, Euclidean, Intersects, LineString, MultiPolygon,
-    Polygon, Rect, Scale, Simplify, Validation, Winding, coord, line_measures::LengthMeasurable,
+    Area, BooleanOps, Buffer, ChaikinSmoothing, Contains, Coord, Euclidean, Intersects, LineString,
+    MultiPolygon, Polygon, Rect, Scale, Simplify, Validation, Winding, coord,
+    line_measures::LengthMeasurable,
 };
 use image::imageops::{self, FilterType};
-use imageproc::contours::BorderType;
+use imageproc::{
+    contours::BorderType,
+    distance_transform::Norm,
+    morphology::{close_mut, open_mut},
+};
 use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use tracing::{debug, error, trace, warn};
 
-use crate::{app::LoadedImage, protocol::CanvasSize, spawn_blocking};
+use crate::{
+    app::LoadedImage,
+    protocol::{Axis, CanvasSize},
+    spawn_blocking,
+};
 
 #[derive(Debug)]
 pub enum CutAction {
@@ -24,15 +35,101 @@ pub struct CutResult {
     pub has_intersections: bool,
     pub off_canvas: bool,
     pub polygons: Vec<MultiPolygon<f32>>,
+    /// Reasons images didn't produce a cut, with the index of the image.
+    pub diagnostics: Vec<(usize, CutError)>,
+    /// Contours dropped or changed across all images.
+    pub contour_stats: ContourStats,
+}
+
+/// Counts of contours that were dropped or changed while building cut lines,
+/// to explain why detail may be missing.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ContourStats {
+    /// Contours that didn't make a valid shape.
+    pub invalid: usize,
+    /// Holes without a parent that were cut as outer shapes.
+    pub orphan_holes_promoted: usize,
+    /// Holes without a parent that were discarded.
+    pub orphan_holes_discarded: usize,
+    /// Shapes shorter than the minimum cut length.
+    pub too_short: usize,
+    /// Shapes enclosing less than the minimum cut area.
+    pub too_small: usize,
+    /// Shapes inside other shapes while internal cuts are disabled.
+    pub contained: usize,
+}
+
+impl ContourStats {
+    /// Human readable descriptions of each non-zero count.
+    pub fn messages(&self) -> Vec<String> {
+        [
+            (self.invalid, "contour(s) discarded as invalid"),
+            (self.orphan_holes_promoted, "orphan hole(s) promoted"),
+            (self.orphan_holes_discarded, "orphan hole(s) discarded"),
+            (self.too_short, "contour(s) below the minimum cut length"),
+            (self.too_small, "contour(s) below the minimum cut area"),
+            (self.contained, "contour(s) inside other shapes"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, message)| format!("{count} {message}"))
+        .collect()
+    }
 }
 
-#[derive(Clone)]
+#[derive(Error, Debug)]
+pub enum CutError {
+    #[error("no contours were found")]
+    NoContours,
+    #[error("no contours were valid")]
+    NoValidContours,
+    #[error("all contours were below the minimum cut length or area")]
+    BelowMinimumSize,
+    #[error("all shapes were inside other shapes and internal cuts are disabled")]
+    AllContained,
+    #[error("cut results were no longer needed")]
+    Disconnected,
+}
+
+/// Blade pressure the vendor app sends with every cut.
+pub const DEFAULT_CUT_PRESSURE: u32 = 42;
+
+/// Cutting speed an override starts at. Without an override no speed is
+/// sent, and the device uses its own.
+pub const DEFAULT_CUT_SPEED: u32 = 10;
+
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct CutTuning {
     pub buffer: f32,
     pub minimum_length: f32,
+    /// Area in square canvas pixels a contour must enclose to be cut, so thin
+    /// lines long enough to pass the minimum length are still dropped.
+    pub minimum_area: f32,
     pub smoothing: usize,
+    /// Radius in pixels of the cleanup applied to the mask before finding
+    /// contours, removing specks and closing gaps up to about twice this.
+    pub cleanup_radius: u8,
     pub simplify: f32,
     pub internal: bool,
+    pub preserve_corners: bool,
+    /// Minimum turn, in degrees, for a vertex to be considered a corner.
+    pub corner_angle: f32,
+    /// Width of material removed by the blade in millimeters, only used for
+    /// previews.
+    pub blade_kerf: f32,
+    /// Cut holes without a parent contour as outer shapes instead of
+    /// discarding them.
+    pub promote_orphan_holes: bool,
+    /// Blade pressure for the cutter, [`DEFAULT_CUT_PRESSURE`] if unset.
+    pub cut_pressure: Option<u32>,
+    /// Cutting speed for the cutter, the device's default if unset.
+    pub cut_speed: Option<u32>,
+    /// Show the moves between cuts, only used for previews.
+    pub show_travel: bool,
+    /// Area in square millimeters that cut lines can overlap before it's
+    /// considered a collision, so touching cuts aren't flagged.
+    pub overlap_tolerance: f32,
 }
 
 impl Default for CutTuning {
@@ -40,25 +137,54 @@ impl Default for CutTuning {
         Self {
             buffer: 300.0 / 25.4,         // 1mm
             minimum_length: 0.25 * 300.0, // 1/4in
+            minimum_area: 0.0,
             smoothing: 2,
+            cleanup_radius: 1,
             simplify: 1.5,
             internal: false,
+            preserve_corners: false,
+            corner_angle: 60.0,
+            blade_kerf: 0.3,
+            promote_orphan_holes: true,
+            cut_pressure: None,
+            cut_speed: None,
+            show_travel: false,
+            overlap_tolerance: 0.1,
         }
     }
 }
 
+impl CutTuning {
+    /// Blade kerf in canvas pixels for a device with the given DPI.
+    pub fn blade_kerf_px(&self, dpi: f32) -> f32 {
+        self.blade_kerf * dpi / 25.4
+    }
+
+    /// Overlap tolerance in square canvas pixels for a device with the given
+    /// DPI.
+    pub fn overlap_tolerance_px(&self, dpi: Vec2) -> f32 {
+        self.overlap_tolerance * dpi.x * dpi.y / (25.4 * 25.4)
+    }
+}
+
 pub struct CutGenerator {
     tx: UnboundedSender<CutAction>,
     images: Vec<LoadedImage>,
     tuning: CutTuning,
     canvas_size: &'static CanvasSize,
+    dpi: Vec2,
 }
 
 impl CutGenerator {
+    /// Start generating cut lines for images on a blocking thread.
+    ///
+    /// Progress and the result are sent over the returned channel, which
+    /// callers should forward somewhere that requests a repaint.
     pub fn start(
         images: Vec<LoadedImage>,
         tuning: CutTuning,
         canvas_size: &'static CanvasSize,
+        dpi: Vec2,
     ) -> UnboundedReceiver<CutAction> {
         let (tx, rx) = unbounded();
 
@@ -67,6 +193,7 @@ impl CutGenerator {
             images,
             tuning,
             canvas_size,
+            dpi,
         };
 
         spawn_blocking(move || {
@@ -78,83 +205,66 @@ impl CutGenerator {
         rx
     }
 
-    fn process(self) -> anyhow::Result<()> {
+    fn process(self) -> Result<(), CutError> {
         let total = self.images.len();
 
-        self.tx.unbounded_send(CutAction::Progress {
+        self.send(CutAction::Progress {
             completed: 0,
             total,
         })?;
 
         let mut polygons = Vec::new();
+        let mut diagnostics = Vec::new();
+        let mut contour_stats = ContourStats::default();
 
         for (index, image) in self.images.iter().enumerate() {
-            let polygon = self.image(image);
-
-            if let Some(polygon) = polygon {
-                polygons.push(polygon);
+            match self.image(image, &mut contour_stats) {
+                Ok(polygon) => polygons.push(polygon),
+                Err(err) => {
+                    warn!(index, "image did not produce a cut: {err}");
+                    diagnostics.push((index, err));
+                }
             }
 
-            self.tx.unbounded_send(CutAction::Progress {
+            self.send(CutAction::Progress {
                 completed: index + 1,
                 total,
             })?;
         }
 
-        let has_intersections = polygons
-            .iter()
-            .combinations(2)
-            .any(|polygons| polygons[0].intersects(polygons[1]));
-
-        let offset = (self.canvas_size.size - self.canvas_size.safe_area) / 2.0;
-
-        let canvas_polygon = Rect::new(
-            coord! { x: offset.x, y: offset.y },
-            coord! { x: self.canvas_size.size.x - offset.x, y: self.canvas_size.size.y - offset.y },
-        )
-        .to_polygon();
-
-        let off_canvas = polygons
-            .iter()
-            .any(|polygons| !canvas_polygon.contains(polygons));
+        let (has_intersections, off_canvas) = check_cuts(
+            &polygons,
+            self.canvas_size,
+            self.tuning.overlap_tolerance_px(self.dpi),
+        );
 
-        self.tx.unbounded_send(CutAction::Done(CutResult {
+        self.send(CutAction::Done(CutResult {
             has_intersections,
             off_canvas,
             polygons,
-        }))?;
+            diagnostics,
+            contour_stats,
+        }))
+    }
 
-        Ok(())
+    fn send(&self, action: CutAction) -> Result<(), CutError> {
+        self.tx
+            .unbounded_send(action)
+            .map_err(|_| CutError::Disconnected)
     }
 
-    fn image(&self, image: &LoadedImage) -> Option<MultiPolygon<f32>> {
+    fn image(
+        &self,
+        image: &LoadedImage,
+        stats: &mut ContourStats,
+    ) -> Result<MultiPolygon<f32>, CutError> {
         trace!("starting processing image");
 
-        // Resize image to the expected dimensions. Doesn't need to be a high
-        // quality resize, so nearest filter is fine.
-        let size = image.size();
-        let resized = imageops::resize(
-            &image.image,
-            size.x as u32,
-            size.y as u32,
-            FilterType::Nearest,
-        );
-
-        // Invert the colors, unlike a normal image we need blacks to be visible
-        // but don't care about white. Normally transparent pixels turn black
-        // but we need them to be white for our inversion.
-        let mut im = image::ImageBuffer::from_pixel(
-            resized.width(),
-            resized.height(),
-            image::Rgba([255, 255, 255, 255]),
-        );
-        image::imageops::overlay(&mut im, &resized, 0, 0);
-        imageops::colorops::invert(&mut im);
-
-        // `find_contours` only works on grayscale images, so convert it.
-        let grayscale = imageops::grayscale(&im);
-
-        let contours = imageproc::contours::find_contours::<u32>(&grayscale);
+        let mask = self.mask(image);
+        let contours = imageproc::contours::find_contours::<u32>(&mask);
+        if contours.is_empty() {
+            return Err(CutError::NoContours);
+        }
 
         // Keep track of the outer parts of contours separately from holes, so
         // we can construct a MultiPolygon with an exterior and interiors.
@@ -176,12 +286,13 @@ impl CutGenerator {
 
             if !line_string.is_valid() {
                 warn!("line string was not valid");
+                stats.invalid += 1;
                 continue;
             }
 
             // Based on the border type, determine where to put this polygon.
             // It's also possible for a hole to not have a parent, and in those
-            // cases we can promote it to a outer type.
+            // cases we can promote it to a outer type if enabled.
             match contour.border_type {
                 BorderType::Outer => {
                     line_string.make_cw_winding();
@@ -191,18 +302,21 @@ impl CutGenerator {
                     if let Some(parent) = contour.parent {
                         line_string.make_ccw_winding();
                         holes.entry(parent).or_default().push(line_string);
-                    } else {
+                    } else if self.tuning.promote_orphan_holes {
                         warn!(index, "hole did not have parent, using as outer");
+                        stats.orphan_holes_promoted += 1;
                         line_string.make_cw_winding();
                         outers.insert(index, line_string);
+                    } else {
+                        warn!(index, "hole did not have parent, discarding");
+                        stats.orphan_holes_discarded += 1;
                     };
                 }
             }
         }
 
         if outers.is_empty() {
-            warn!("image had no sections");
-            return None;
+            return Err(CutError::NoValidContours);
         }
 
         // Now we can create polygons from our line strings, filtering out the
@@ -216,11 +330,23 @@ impl CutGenerator {
                     minimum_length = self.tuning.minimum_length,
                     "exterior length was too short"
                 );
+                stats.too_short += 1;
                 continue;
-            } else {
-                debug!(outer_length);
             }
 
+            let outer_area = ring_area(&outer);
+            if outer_area < self.tuning.minimum_area {
+                debug!(
+                    outer_area,
+                    minimum_area = self.tuning.minimum_area,
+                    "exterior area was too small"
+                );
+                stats.too_small += 1;
+                continue;
+            }
+
+            debug!(outer_length, outer_area);
+
             let holes = if self.tuning.internal {
                 holes
                     .remove(&index)
@@ -234,6 +360,10 @@ impl CutGenerator {
             polygons.push(polygon);
         }
 
+        if polygons.is_empty() {
+            return Err(CutError::BelowMinimumSize);
+        }
+
         // And now that we've filtered everything, we can refine the polygons
         // based on our tuning settings to smooth, simplify, and buffer it to
         // make it a reasonable cut path.
@@ -245,12 +375,19 @@ impl CutGenerator {
                     .any(|other| other != polygon && other.contains(polygon))
             {
                 warn!("polygon was contained by other when we didn't want internal holes");
+                stats.contained += 1;
                 continue;
             }
 
-            let simplified_polygon = polygon
-                .chaikin_smoothing(self.tuning.smoothing)
-                .simplify(self.tuning.simplify);
+            // Contours follow pixel edges, so simplify before looking for
+            // corners to avoid treating each pixel step as a corner.
+            let simplified_polygon = if self.tuning.preserve_corners {
+                self.smooth_preserving_corners(&polygon.simplify(self.tuning.simplify))
+            } else {
+                polygon
+                    .chaikin_smoothing(self.tuning.smoothing)
+                    .simplify(self.tuning.simplify)
+            };
 
             let buffered_polygon = simplified_polygon.buffer(self.tuning.buffer);
 
@@ -264,9 +401,50 @@ impl CutGenerator {
             }));
         }
 
+        if refined_polygons.is_empty() {
+            return Err(CutError::AllContained);
+        }
+
         trace!("finished processing image");
 
-        Some(MultiPolygon::new(refined_polygons))
+        Ok(MultiPolygon::new(refined_polygons))
+    }
+
+    /// Make the mask contours are found in, where the image's dark pixels are
+    /// white, at the size it's shown on the canvas.
+    fn mask(&self, image: &LoadedImage) -> image::GrayImage {
+        // Resize image to the expected dimensions. Doesn't need to be a high
+        // quality resize, so nearest filter is fine.
+        let size = image.size();
+        let resized = imageops::resize(
+            image.cropped_image().as_ref(),
+            size.x as u32,
+            size.y as u32,
+            FilterType::Nearest,
+        );
+
+        // Invert the colors, unlike a normal image we need blacks to be visible
+        // but don't care about white. Normally transparent pixels turn black
+        // but we need them to be white for our inversion.
+        let mut im = image::ImageBuffer::from_pixel(
+            resized.width(),
+            resized.height(),
+            image::Rgba([255, 255, 255, 255]),
+        );
+        image::imageops::overlay(&mut im, &resized, 0, 0);
+        imageops::colorops::invert(&mut im);
+
+        // `find_contours` only works on grayscale images, so convert it.
+        let mut mask = imageops::grayscale(&im);
+
+        // Opening removes specks and closing fills small gaps, both of which
+        // are common around compressed images and make jagged outlines.
+        if self.tuning.cleanup_radius > 0 {
+            open_mut(&mut mask, Norm::LInf, self.tuning.cleanup_radius);
+            close_mut(&mut mask, Norm::LInf, self.tuning.cleanup_radius);
+        }
+
+        mask
     }
 
     fn filter_small_holes(
@@ -281,24 +459,461 @@ impl CutGenerator {
                     minimum_length = self.tuning.minimum_length,
                     "interior length was too short"
                 );
-                false
-            } else {
-                debug!(interior_length = length);
-                true
+                return false;
             }
+
+            let area = ring_area(line_string);
+            if area < self.tuning.minimum_area {
+                debug!(
+                    area,
+                    minimum_area = self.tuning.minimum_area,
+                    "interior area was too small"
+                );
+                return false;
+            }
+
+            debug!(interior_length = length, interior_area = area);
+            true
         })
     }
 
-    /// Mirror generated cut lines for sending to the device.
-    #[allow(dead_code)]
+    /// Apply Chaikin smoothing, leaving any vertices that turn more than the
+    /// corner angle in place.
+    fn smooth_preserving_corners(&self, polygon: &Polygon<f32>) -> Polygon<f32> {
+        let smooth = |line_string: &LineString<f32>| {
+            let mut coords = line_string.0.clone();
+            for _ in 0..self.tuning.smoothing {
+                coords = chaikin_step(&coords, self.tuning.corner_angle);
+            }
+
+            let mut line_string = LineString::new(coords);
+            line_string.close();
+            line_string
+        };
+
+        Polygon::new(
+            smooth(polygon.exterior()),
+            polygon.interiors().iter().map(smooth).collect(),
+        )
+    }
+
+    /// Create a 1cm square in the center of the safe area, for testing the
+    /// cutter's calibration.
+    pub fn test_square(canvas_size: &CanvasSize, dpi: Vec2) -> MultiPolygon<f32> {
+        let half_size = dpi / 2.54 / 2.0;
+        let center = canvas_size.safe_rect().center();
+
+        Rect::new(
+            coord! { x: center.x - half_size.x, y: center.y - half_size.y },
+            coord! { x: center.x + half_size.x, y: center.y + half_size.y },
+        )
+        .to_polygon()
+        .into()
+    }
+
+    /// Mirror generated cut lines across an axis for sending to the device.
     pub fn mirror_cuts<'a>(
         polygons: impl IntoIterator<Item = &'a MultiPolygon<f32>>,
         canvas_size: Vec2,
+        axis: Axis,
     ) -> impl Iterator<Item = MultiPolygon<f32>> {
-        let point = Coord::from((canvas_size.x, canvas_size.y / 2.0));
+        let point = Coord::from((canvas_size.x / 2.0, canvas_size.y / 2.0));
+
+        let (x_factor, y_factor) = match axis {
+            Axis::X => (-1.0, 1.0),
+            Axis::Y => (1.0, -1.0),
+        };
 
         polygons
             .into_iter()
-            .map(move |polygon| polygon.scale_around_point(1.0, -1.0, point))
+            .map(move |polygon| polygon.scale_around_point(x_factor, y_factor, point))
+    }
+}
+
+/// Check if cut lines overlap each other by more than the tolerance area in
+/// square canvas pixels, and if any are outside of the safe area.
+pub fn check_cuts(
+    polygons: &[MultiPolygon<f32>],
+    canvas_size: &CanvasSize,
+    overlap_tolerance: f32,
+) -> (bool, bool) {
+    // Finding the overlapping area is expensive, so only do it for cut lines
+    // that touch at all.
+    let has_intersections = polygons
+        .iter()
+        .combinations(2)
+        .filter(|polygons| polygons[0].intersects(polygons[1]))
+        .any(|polygons| polygons[0].intersection(polygons[1]).unsigned_area() > overlap_tolerance);
+
+    let offset = (canvas_size.size - canvas_size.safe_area) / 2.0;
+
+    let canvas_polygon = Rect::new(
+        coord! { x: offset.x, y: offset.y },
+        coord! { x: canvas_size.size.x - offset.x, y: canvas_size.size.y - offset.y },
+    )
+    .to_polygon();
+
+    let off_canvas = polygons
+        .iter()
+        .any(|polygons| !canvas_polygon.contains(polygons));
+
+    (has_intersections, off_canvas)
+}
+
+/// Number of line segments used to approximate each curve in an SVG path.
+const SVG_CURVE_STEPS: usize = 16;
+
+/// Load the paths from an SVG as cut lines, in canvas pixels for a device with
+/// the given DPI.
+///
+/// Each subpath becomes its own polygon, closed if it wasn't already.
+pub fn svg_cut_lines(data: &[u8], dpi: Vec2) -> anyhow::Result<Vec<MultiPolygon<f32>>> {
+    let options = usvg::Options::default();
+    let tree = usvg::Tree::from_data(data, &options)?;
+
+    // Physical units were already converted to pixels at the options' DPI.
+    let scale = dpi / options.dpi;
+
+    let mut cut_lines = Vec::new();
+    svg_group_cut_lines(tree.root(), scale, &mut cut_lines);
+
+    if cut_lines.is_empty() {
+        anyhow::bail!("svg did not contain any paths");
+    }
+
+    Ok(cut_lines)
+}
+
+fn svg_group_cut_lines(group: &usvg::Group, scale: Vec2, cut_lines: &mut Vec<MultiPolygon<f32>>) {
+    use usvg::tiny_skia_path::PathSegment;
+
+    for node in group.children() {
+        let path = match node {
+            usvg::Node::Group(group) => {
+                svg_group_cut_lines(group, scale, cut_lines);
+                continue;
+            }
+            usvg::Node::Path(path) => path,
+            _ => continue,
+        };
+
+        let Some(data) = path.data().clone().transform(path.abs_transform()) else {
+            warn!(id = path.id(), "svg path could not be transformed");
+            continue;
+        };
+
+        let mut rings: Vec<Vec<Coord<f32>>> = Vec::new();
+        let mut last = Coord::zero();
+
+        for segment in data.segments() {
+            let points = match segment {
+                PathSegment::MoveTo(point) => {
+                    rings.push(Vec::new());
+                    vec![point]
+                }
+                PathSegment::LineTo(point) => vec![point],
+                PathSegment::QuadTo(control, point) => (1..=SVG_CURVE_STEPS)
+                    .map(|step| {
+                        let t = step as f32 / SVG_CURVE_STEPS as f32;
+                        let start = usvg::tiny_skia_path::Point::from_xy(last.x, last.y);
+                        let a = lerp(start, control, t);
+                        let b = lerp(control, point, t);
+                        lerp(a, b, t)
+                    })
+                    .collect(),
+                PathSegment::CubicTo(control1, control2, point) => (1..=SVG_CURVE_STEPS)
+                    .map(|step| {
+                        let t = step as f32 / SVG_CURVE_STEPS as f32;
+                        let start = usvg::tiny_skia_path::Point::from_xy(last.x, last.y);
+                        let a = lerp(start, control1, t);
+                        let b = lerp(control1, control2, t);
+                        let c = lerp(control2, point, t);
+                        lerp(lerp(a, b, t), lerp(b, c, t), t)
+                    })
+                    .collect(),
+                PathSegment::Close => Vec::new(),
+            };
+
+            let Some(ring) = rings.last_mut() else {
+                continue;
+            };
+
+            for point in points {
+                last = coord! { x: point.x, y: point.y };
+                ring.push(last);
+            }
+        }
+
+        let polygons = rings
+            .into_iter()
+            .filter(|ring| ring.len() >= 3)
+            .map(|ring| {
+                let mut line_string: LineString<f32> = ring
+                    .into_iter()
+                    .map(|point| coord! { x: point.x * scale.x, y: point.y * scale.y })
+                    .collect();
+                line_string.close();
+                line_string.make_cw_winding();
+                Polygon::new(line_string, Vec::new())
+            })
+            .collect_vec();
+
+        if !polygons.is_empty() {
+            cut_lines.push(MultiPolygon::new(polygons));
+        }
+    }
+}
+
+/// Area enclosed by a closed ring.
+fn ring_area(ring: &LineString<f32>) -> f32 {
+    Polygon::new(ring.clone(), Vec::new()).unsigned_area()
+}
+
+fn lerp(
+    a: usvg::tiny_skia_path::Point,
+    b: usvg::tiny_skia_path::Point,
+    t: f32,
+) -> usvg::tiny_skia_path::Point {
+    usvg::tiny_skia_path::Point::from_xy(a.x + (b.x - a.x) * t, a.y + (b.y - a.y) * t)
+}
+
+/// Perform one iteration of Chaikin smoothing on a closed ring, keeping
+/// vertices with a turn of at least `corner_angle` degrees.
+fn chaikin_step(coords: &[Coord<f32>], corner_angle: f32) -> Vec<Coord<f32>> {
+    // Closed rings repeat the first coordinate, which shouldn't be treated as
+    // another vertex.
+    let points = match coords {
+        [first, .., last] if first == last => &coords[..coords.len() - 1],
+        _ => coords,
+    };
+
+    if points.len() < 3 {
+        return coords.to_vec();
+    }
+
+    let mut smoothed = Vec::with_capacity(points.len() * 2 + 1);
+
+    for (index, point) in points.iter().copied().enumerate() {
+        let prev = points[(index + points.len() - 1) % points.len()];
+        let next = points[(index + 1) % points.len()];
+
+        if turn_angle(prev, point, next) >= corner_angle {
+            smoothed.push(point);
+        } else {
+            smoothed.push(prev * 0.25 + point * 0.75);
+            smoothed.push(point * 0.75 + next * 0.25);
+        }
+    }
+
+    smoothed.push(smoothed[0]);
+
+    smoothed
+}
+
+/// Calculate how far a path turns at a point, in degrees.
+fn turn_angle(prev: Coord<f32>, point: Coord<f32>, next: Coord<f32>) -> f32 {
+    let incoming = point - prev;
+    let outgoing = next - point;
+
+    let lengths = incoming.x.hypot(incoming.y) * outgoing.x.hypot(outgoing.y);
+    if lengths == 0.0 {
+        return 0.0;
+    }
+
+    let cos = (incoming.x * outgoing.x + incoming.y * outgoing.y) / lengths;
+    cos.clamp(-1.0, 1.0).acos().to_degrees()
+}
+
+#[cfg(test)]
+mod tests {
+    use geo::BoundingRect;
+
+    use super::*;
+    use crate::protocol::DEVICES;
+
+    fn generator(tuning: CutTuning) -> CutGenerator {
+        CutGenerator {
+            tx: unbounded().0,
+            images: Vec::new(),
+            tuning,
+            canvas_size: &DEVICES[0].modes[0].canvas_sizes[0],
+            dpi: DEVICES[0].dpi,
+        }
+    }
+
+    /// An image that's black wherever `shape` is true and white elsewhere.
+    fn shape_image(
+        ctx: &egui::Context,
+        width: u32,
+        height: u32,
+        shape: impl Fn(u32, u32) -> bool,
+    ) -> LoadedImage {
+        let im = image::RgbaImage::from_fn(width, height, |x, y| {
+            if shape(x, y) {
+                image::Rgba([0, 0, 0, 255])
+            } else {
+                image::Rgba([255, 255, 255, 255])
+            }
+        });
+
+        LoadedImage::from_image(ctx, im, None)
+    }
+
+    #[test]
+    fn test_minimum_area() {
+        let ctx = egui::Context::default();
+        let tuning = CutTuning {
+            minimum_length: 100.0,
+            ..Default::default()
+        };
+
+        // A line long enough to pass the minimum length, but only 3px tall.
+        let line = shape_image(&ctx, 200, 20, |x, y| {
+            (25..175).contains(&x) && (8..11).contains(&y)
+        });
+
+        let mut stats = ContourStats::default();
+        assert!(generator(tuning.clone()).image(&line, &mut stats).is_ok());
+        assert_eq!(stats, ContourStats::default());
+
+        let tuning = CutTuning {
+            minimum_area: 1000.0,
+            ..tuning
+        };
+        assert!(matches!(
+            generator(tuning).image(&line, &mut stats),
+            Err(CutError::BelowMinimumSize)
+        ));
+        assert_eq!(stats.too_small, 1);
+        assert_eq!(stats.too_short, 0);
+    }
+
+    #[test]
+    fn test_cleanup_removes_specks() {
+        let ctx = egui::Context::default();
+
+        // A square with a single pixel speck off to the side.
+        let image = shape_image(&ctx, 60, 60, |x, y| {
+            ((10..40).contains(&x) && (10..40).contains(&y)) || (x, y) == (50, 50)
+        });
+
+        let uncleaned = generator(CutTuning {
+            cleanup_radius: 0,
+            ..Default::default()
+        })
+        .mask(&image);
+        assert_eq!(uncleaned.get_pixel(50, 50).0, [255]);
+        assert_eq!(
+            imageproc::contours::find_contours::<u32>(&uncleaned).len(),
+            2
+        );
+
+        let cleaned = generator(CutTuning {
+            cleanup_radius: 1,
+            ..Default::default()
+        })
+        .mask(&image);
+        assert_eq!(cleaned.get_pixel(50, 50).0, [0]);
+        assert_eq!(cleaned.get_pixel(25, 25).0, [255]);
+        assert_eq!(imageproc::contours::find_contours::<u32>(&cleaned).len(), 1);
+    }
+
+    #[test]
+    fn test_turn_angle() {
+        let origin = coord! { x: 0.0, y: 0.0 };
+
+        assert_eq!(
+            turn_angle(
+                coord! { x: -1.0, y: 0.0 },
+                origin,
+                coord! { x: 1.0, y: 0.0 }
+            ),
+            0.0
+        );
+        assert_eq!(
+            turn_angle(
+                coord! { x: -1.0, y: 0.0 },
+                origin,
+                coord! { x: 0.0, y: 1.0 }
+            ),
+            90.0
+        );
+        assert_eq!(
+            turn_angle(
+                coord! { x: -1.0, y: 0.0 },
+                origin,
+                coord! { x: -2.0, y: 0.0 }
+            ),
+            180.0
+        );
+    }
+
+    #[test]
+    fn test_chaikin_step_keeps_sharp_corners() {
+        let ring = [
+            coord! { x: 0.0, y: 0.0 },
+            coord! { x: 10.0, y: 1.0 },
+            coord! { x: 20.0, y: 0.0 },
+            coord! { x: 20.0, y: 10.0 },
+            coord! { x: 0.0, y: 10.0 },
+            coord! { x: 0.0, y: 0.0 },
+        ];
+
+        // The bend at the top turns about 11°, the rest about 90°.
+        let smoothed = chaikin_step(&ring, 60.0);
+        assert_eq!(smoothed.len(), 7);
+        assert_eq!(smoothed.first(), smoothed.last());
+        for corner in [ring[0], ring[2], ring[3], ring[4]] {
+            assert!(smoothed.contains(&corner), "{corner:?} was smoothed");
+        }
+        assert!(!smoothed.contains(&ring[1]));
+
+        // Without any corners every vertex is cut.
+        let smoothed = chaikin_step(&ring, 180.0);
+        assert_eq!(smoothed.len(), 11);
+        assert!(ring.iter().all(|point| !smoothed.contains(point)));
+    }
+
+    #[test]
+    fn test_svg_cut_lines() {
+        let svg = br#"<svg xmlns="http://www.w3.org/2000/svg" width="192" height="192">
+            <path d="M 0 0 L 96 0 L 96 96 Z M 120 120 L 150 120 L 150 150" />
+            <path d="M 0 96 Q 48 192 96 96" />
+        </svg>"#;
+
+        // Scaled from 96 dpi, the default for SVG pixels.
+        let cut_lines = svg_cut_lines(svg, Vec2::splat(192.0)).unwrap();
+        let [path, curve] = &cut_lines[..] else {
+            panic!("expected two paths, got {}", cut_lines.len());
+        };
+
+        // Each subpath is its own polygon, closed even if it wasn't.
+        let [first, second] = &path.0[..] else {
+            panic!("expected two subpaths, got {}", path.0.len());
+        };
+        assert!(first.exterior().is_closed());
+        assert!(second.exterior().is_closed());
+        assert_eq!(first.exterior().0.len(), 4);
+        assert_eq!(second.exterior().0.len(), 4);
+
+        let bounds = first.bounding_rect().unwrap();
+        assert_eq!(bounds.min(), coord! { x: 0.0, y: 0.0 });
+        assert_eq!(bounds.max(), coord! { x: 192.0, y: 192.0 });
+
+        // Curves are split into segments, and the peak is halfway along.
+        let [curve] = &curve.0[..] else {
+            panic!("expected one curve, got {}", curve.0.len());
+        };
+        assert_eq!(curve.exterior().0.len(), SVG_CURVE_STEPS + 2);
+        let bounds = curve.bounding_rect().unwrap();
+        assert_eq!(bounds.max().y, 288.0);
+
+        assert!(
+            svg_cut_lines(
+                br#"<svg xmlns="http://www.w3.org/2000/svg" />"#,
+                Vec2::splat(300.0)
+            )
+            .is_err()
+        );
     }
 }
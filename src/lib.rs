@@ -1,6 +1,10 @@
 mod app;
+mod color;
 mod cut;
+mod logs;
+mod project;
 mod protocol;
+mod shortcuts;
 mod transports;
 mod views;
 
@@ -10,6 +14,7 @@ use futures::StreamExt;
 use std::time::Duration;
 
 pub use app::SapodillaApp;
+pub use logs::LogLayer;
 
 #[cfg(target_arch = "wasm32")]
 type Rc<T> = std::rc::Rc<T>;
@@ -70,3 +75,34 @@ fn interval(duration: Duration) -> impl Stream<Item = ()> {
 
     s
 }
+
+/// Wait for a future to complete, giving up after the duration.
+async fn timeout<F>(duration: Duration, future: F) -> Option<F::Output>
+where
+    F: Future,
+{
+    #[cfg(target_arch = "wasm32")]
+    let sleep = gloo_timers::future::sleep(duration);
+
+    #[cfg(not(target_arch = "wasm32"))]
+    let sleep = tokio::time::sleep(duration);
+
+    futures::pin_mut!(future, sleep);
+    match futures::future::select(future, sleep).await {
+        futures::future::Either::Left((output, _)) => Some(output),
+        futures::future::Either::Right(_) => None,
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn current_timestamp_millis() -> u64 {
+    web_sys::window().unwrap().performance().unwrap().now() as u64
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn current_timestamp_millis() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
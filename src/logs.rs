@@ -0,0 +1,95 @@
+use std::{
+    collections::VecDeque,
+    fmt::{Debug, Write},
+    sync::Mutex,
+};
+
+use lazy_static::lazy_static;
+use tracing::{
+    Event, Level, Subscriber,
+    field::{Field, Visit},
+};
+use tracing_subscriber::{Layer, layer::Context};
+
+/// Maximum number of log records to keep for the log viewer.
+const MAX_LOG_RECORDS: usize = 1000;
+
+lazy_static! {
+    static ref LOG_RECORDS: Mutex<VecDeque<LogRecord>> =
+        Mutex::new(VecDeque::with_capacity(MAX_LOG_RECORDS));
+}
+
+#[derive(Clone, Debug)]
+pub struct LogRecord {
+    pub level: Level,
+    pub target: String,
+    pub message: String,
+}
+
+/// A tracing layer that keeps the most recent log records in memory so they
+/// can be displayed in the app.
+#[derive(Default)]
+pub struct LogLayer;
+
+impl<S: Subscriber> Layer<S> for LogLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+
+        let metadata = event.metadata();
+        let record = LogRecord {
+            level: *metadata.level(),
+            target: metadata.target().to_string(),
+            message: visitor.finish(),
+        };
+
+        let Ok(mut records) = LOG_RECORDS.lock() else {
+            return;
+        };
+
+        if records.len() >= MAX_LOG_RECORDS {
+            records.pop_front();
+        }
+        records.push_back(record);
+    }
+}
+
+/// Get a copy of the current log records.
+///
+/// Records are copied so the lock isn't held while drawing, as anything
+/// logged in the meantime would otherwise deadlock.
+pub fn log_records() -> Vec<LogRecord> {
+    LOG_RECORDS
+        .lock()
+        .map(|records| records.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+pub fn clear_log_records() {
+    if let Ok(mut records) = LOG_RECORDS.lock() {
+        records.clear();
+    }
+}
+
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: String,
+}
+
+impl MessageVisitor {
+    fn finish(mut self) -> String {
+        self.message.push_str(&self.fields);
+        self.message
+    }
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn Debug) {
+        if field.name() == "message" {
+            let _ = write!(self.message, "{value:?}");
+        } else {
+            let _ = write!(self.fields, " {}={value:?}", field.name());
+        }
+    }
+}
@@ -1,6 +1,39 @@
+/// Directory to write log files to, if enabled with the `SAPODILLA_LOG_FILE`
+/// environment variable.
+#[cfg(not(target_arch = "wasm32"))]
+fn log_dir() -> Option<std::path::PathBuf> {
+    std::env::var_os("SAPODILLA_LOG_FILE")?;
+    Some(dirs::data_local_dir()?.join("sapodilla").join("logs"))
+}
+
 #[cfg(not(target_arch = "wasm32"))]
 fn main() -> eframe::Result {
-    tracing_subscriber::fmt::init();
+    use tracing_subscriber::{
+        Layer, filter::LevelFilter, fmt, layer::SubscriberExt, util::SubscriberInitExt,
+    };
+
+    // Keep the guard for the lifetime of the app so buffered logs are
+    // flushed on exit.
+    let (file_layer, _guard) = match log_dir() {
+        Some(dir) => {
+            let appender = tracing_appender::rolling::daily(dir, "sapodilla.log");
+            let (writer, guard) = tracing_appender::non_blocking(appender);
+
+            let layer = fmt::layer()
+                .with_ansi(false)
+                .with_writer(writer)
+                .with_filter(LevelFilter::DEBUG);
+
+            (Some(layer), Some(guard))
+        }
+        None => (None, None),
+    };
+
+    tracing_subscriber::registry()
+        .with(fmt::layer().with_filter(LevelFilter::INFO))
+        .with(file_layer)
+        .with(sapodilla::LogLayer.with_filter(LevelFilter::DEBUG))
+        .init();
 
     let rt = tokio::runtime::Runtime::new().unwrap();
     let _guard = rt.enter();
@@ -23,8 +56,17 @@ fn main() -> eframe::Result {
 fn main() {
     use eframe::wasm_bindgen::JsCast as _;
 
+    use tracing_subscriber::{Layer, filter::LevelFilter, layer::SubscriberExt};
+
     console_error_panic_hook::set_once();
-    wasm_tracing::set_as_global_default();
+
+    let subscriber = tracing_subscriber::registry()
+        .with(wasm_tracing::WasmLayer::new(
+            wasm_tracing::WasmLayerConfig::default(),
+        ))
+        .with(sapodilla::LogLayer.with_filter(LevelFilter::DEBUG));
+    tracing::subscriber::set_global_default(subscriber)
+        .expect("could not set global tracing subscriber");
 
     let web_options = eframe::WebOptions::default();
 
@@ -0,0 +1,353 @@
+use std::collections::VecDeque;
+
+use anyhow::{Context, bail};
+use base64::{Engine, engine::general_purpose::STANDARD as BASE64};
+use egui::{Pos2, Vec2};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    app::LoadedImage,
+    color::ColorAdjustment,
+    cut::CutTuning,
+    protocol::{DEVICES, ModeType},
+};
+
+/// Extension for saved projects.
+pub const PROJECT_EXTENSION: &str = "sapo";
+
+/// Version written to new projects, increased when older versions can't be
+/// read the same way.
+pub const PROJECT_VERSION: u32 = 1;
+
+/// A canvas and the settings it's printed with, saved so it can be opened
+/// again.
+///
+/// The device, mode, canvas size, and image format are saved as what
+/// identifies them instead of their position, so projects still open if
+/// they're reordered.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct Project {
+    pub version: u32,
+    pub device: String,
+    pub mode: ModeType,
+    /// Media of the canvas size.
+    pub media_size: u16,
+    pub media_type: u16,
+    pub landscape: bool,
+    /// Document format of the image format.
+    pub document_format: u16,
+    pub copies: usize,
+    pub cut_copies: usize,
+    pub link_copies: bool,
+    pub skip_cut: bool,
+    pub background_color: [u8; 4],
+    pub cut_tuning: CutTuning,
+    pub images: Vec<ProjectImage>,
+}
+
+/// An image on a saved canvas.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct ProjectImage {
+    /// The whole image as a base64 encoded PNG, before it's cropped or its
+    /// color is adjusted.
+    pub png: String,
+    pub offset: [f32; 2],
+    pub initial_offset: [f32; 2],
+    pub scale: [f32; 2],
+    pub scale_locked: bool,
+    pub opacity: f32,
+    pub adjustment: ColorAdjustment,
+    /// Part of the image used, as the fraction of its size at the minimum
+    /// and maximum corners.
+    pub crop: Option<[f32; 4]>,
+}
+
+/// Indexes of a project's device, mode, canvas size, and image format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProjectSelection {
+    pub device: usize,
+    pub mode: usize,
+    pub canvas_size: usize,
+    pub image_format: usize,
+}
+
+impl Project {
+    pub fn encode(&self) -> anyhow::Result<Vec<u8>> {
+        Ok(serde_json::to_vec(self)?)
+    }
+
+    /// Decode a saved project, checking that it can be used with the known
+    /// devices.
+    pub fn decode(data: &[u8]) -> anyhow::Result<Self> {
+        let project: Self = serde_json::from_slice(data)?;
+
+        if project.version > PROJECT_VERSION {
+            bail!(
+                "project is version {}, newer than the supported version {PROJECT_VERSION}",
+                project.version
+            );
+        }
+
+        project.selection()?;
+
+        Ok(project)
+    }
+
+    /// Find the project's device, mode, canvas size, and image format.
+    pub fn selection(&self) -> anyhow::Result<ProjectSelection> {
+        let device = DEVICES
+            .iter()
+            .position(|device| device.name == self.device)
+            .with_context(|| format!("project is for unknown device {}", self.device))?;
+        let modes = &DEVICES[device].modes;
+
+        let mode = modes
+            .iter()
+            .position(|mode| mode.mode_type == self.mode)
+            .with_context(|| format!("{} has no {} mode", self.device, self.mode.name()))?;
+
+        let canvas_size = modes[mode]
+            .canvas_sizes
+            .iter()
+            .position(|canvas_size| {
+                canvas_size.media_size == self.media_size
+                    && canvas_size.media_type == self.media_type
+            })
+            .with_context(|| {
+                format!(
+                    "{} has no canvas size for media {} in {} mode",
+                    self.device,
+                    self.media_size,
+                    self.mode.name()
+                )
+            })?;
+
+        let image_format = DEVICES[device]
+            .image_formats
+            .iter()
+            .position(|format| format.document_format == self.document_format)
+            .with_context(|| {
+                format!(
+                    "{} has no image format {}",
+                    self.device, self.document_format
+                )
+            })?;
+
+        Ok(ProjectSelection {
+            device,
+            mode,
+            canvas_size,
+            image_format,
+        })
+    }
+}
+
+impl ProjectImage {
+    pub fn new(image: &LoadedImage) -> anyhow::Result<Self> {
+        let mut png = Vec::new();
+        image
+            .image
+            .write_with_encoder(image::codecs::png::PngEncoder::new(&mut png))?;
+
+        Ok(Self {
+            png: BASE64.encode(png),
+            offset: image.offset.into(),
+            initial_offset: image.initial_offset.into(),
+            scale: image.scale.into(),
+            scale_locked: image.scale_locked,
+            opacity: image.opacity,
+            adjustment: image.adjustment,
+            crop: image
+                .crop()
+                .map(|crop| [crop.min.x, crop.min.y, crop.max.x, crop.max.y]),
+        })
+    }
+
+    /// Load the image, placed and adjusted the way it was saved.
+    ///
+    /// The image is checked the same way as any other loaded image, so
+    /// images without pixels or larger than the renderer allows are errors.
+    pub fn load(&self, ctx: &egui::Context) -> anyhow::Result<LoadedImage> {
+        let png = BASE64.decode(&self.png)?;
+
+        let mut image = LoadedImage::new(ctx, &png, Some(Pos2::from(self.initial_offset)))?;
+        image.scale = Vec2::from(self.scale);
+        image.scale_locked = self.scale_locked;
+        image.opacity = self.opacity;
+        image.set_adjustment(self.adjustment);
+        image.set_crop(self.crop.map(|[min_x, min_y, max_x, max_y]| {
+            egui::Rect::from_min_max(Pos2::new(min_x, min_y), Pos2::new(max_x, max_y))
+        }));
+        // Cropping moves the image to keep the rest where it was, but the
+        // saved offset is already for the cropped part.
+        image.offset = Pos2::from(self.offset);
+
+        Ok(image)
+    }
+}
+
+/// A project with its images loaded, ready to replace the canvas.
+pub struct OpenedProject {
+    /// File name the project was opened from.
+    pub name: String,
+    pub selection: ProjectSelection,
+    /// The project's settings, without its images.
+    pub project: Project,
+    pub images: Vec<LoadedImage>,
+}
+
+impl OpenedProject {
+    pub fn open(ctx: &egui::Context, name: String, data: &[u8]) -> anyhow::Result<Self> {
+        let mut project = Project::decode(data)?;
+        let selection = project.selection()?;
+
+        let images = std::mem::take(&mut project.images)
+            .iter()
+            .map(|image| image.load(ctx))
+            .collect::<anyhow::Result<_>>()?;
+
+        Ok(Self {
+            name,
+            selection,
+            project,
+            images,
+        })
+    }
+}
+
+/// Saved projects being printed one after another.
+pub struct Batch {
+    /// Projects that haven't been started, in the order they print.
+    pub queue: VecDeque<OpenedProject>,
+    /// Name of the project being printed.
+    pub name: String,
+    /// Position of the project being printed, from 1.
+    pub position: usize,
+    pub total: usize,
+    /// If cut lines are being generated for the project before printing it.
+    pub generating_cuts: bool,
+}
+
+impl Batch {
+    pub fn new(projects: Vec<OpenedProject>) -> Self {
+        Self {
+            total: projects.len(),
+            queue: projects.into(),
+            name: String::new(),
+            position: 0,
+            generating_cuts: false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_project(images: &[LoadedImage]) -> Project {
+        let device = &DEVICES[0];
+        let mode = &device.modes[1];
+
+        Project {
+            version: PROJECT_VERSION,
+            device: device.name.clone(),
+            mode: mode.mode_type.clone(),
+            media_size: mode.canvas_sizes[0].media_size,
+            media_type: mode.canvas_sizes[0].media_type,
+            landscape: false,
+            document_format: device.image_formats[1].document_format,
+            copies: 1,
+            cut_copies: 1,
+            link_copies: true,
+            skip_cut: false,
+            background_color: [255; 4],
+            cut_tuning: Default::default(),
+            images: images
+                .iter()
+                .map(|image| ProjectImage::new(image).unwrap())
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_project_round_trip() {
+        let ctx = egui::Context::default();
+
+        let mut image = LoadedImage::from_image(
+            &ctx,
+            image::RgbaImage::from_fn(10, 10, |x, y| image::Rgba([x as u8, y as u8, 0, 255])),
+            Some(Pos2::new(5.0, 5.0)),
+        );
+        image.rescale(Vec2::splat(2.0));
+        image.opacity = 0.5;
+        image.set_adjustment(ColorAdjustment {
+            brightness: 0.25,
+            ..Default::default()
+        });
+        image.set_crop(Some(egui::Rect::from_min_max(
+            Pos2::new(0.2, 0.0),
+            Pos2::new(0.6, 0.5),
+        )));
+
+        let mut project = new_project(std::slice::from_ref(&image));
+        project.copies = 3;
+
+        let opened =
+            OpenedProject::open(&ctx, "test.sapo".to_string(), &project.encode().unwrap()).unwrap();
+        assert_eq!(
+            opened.selection,
+            ProjectSelection {
+                device: 0,
+                mode: 1,
+                canvas_size: 0,
+                image_format: 1,
+            }
+        );
+        assert_eq!(opened.project.copies, 3);
+        assert!(opened.project.images.is_empty());
+
+        let [loaded] = &opened.images[..] else {
+            panic!("expected one image");
+        };
+        assert_eq!(loaded.image, image.image);
+        assert_eq!(loaded.rect(), image.rect());
+        assert_eq!(loaded.initial_offset, image.initial_offset);
+        assert_eq!(loaded.crop(), image.crop());
+        assert_eq!(loaded.opacity, 0.5);
+        assert_eq!(loaded.adjustment, image.adjustment);
+    }
+
+    #[test]
+    fn test_project_unknown_selection() {
+        let mut project = new_project(&[]);
+        project.device = "Unknown".to_string();
+        assert!(Project::decode(&project.encode().unwrap()).is_err());
+
+        let mut project = new_project(&[]);
+        project.media_size = 0;
+        assert!(Project::decode(&project.encode().unwrap()).is_err());
+
+        let mut project = new_project(&[]);
+        project.document_format = 0;
+        assert!(Project::decode(&project.encode().unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_project_image_too_large() {
+        let ctx = egui::Context::default();
+
+        let mut png = Vec::new();
+        image::RgbaImage::new(LoadedImage::max_dimension(&ctx) + 1, 1)
+            .write_with_encoder(image::codecs::png::PngEncoder::new(&mut png))
+            .unwrap();
+
+        let small = LoadedImage::from_image(&ctx, image::RgbaImage::new(1, 1), None);
+        let mut project = new_project(&[small]);
+        project.images[0].png = BASE64.encode(png);
+
+        let err = OpenedProject::open(&ctx, "test.sapo".to_string(), &project.encode().unwrap())
+            .err()
+            .unwrap();
+        assert!(err.is::<crate::app::ImageTooLarge>());
+    }
+}
@@ -3,8 +3,10 @@ use egui::Vec2;
 use lazy_static::lazy_static;
 use packed_struct::prelude::*;
 use serde::{Deserialize, Serialize};
+use sha1::Digest;
+use strum::IntoEnumIterator;
 use thiserror::Error;
-use tracing::{instrument, trace};
+use tracing::{instrument, trace, warn};
 
 const WRAPPER: u8 = 0x7E;
 
@@ -12,14 +14,35 @@ lazy_static! {
     pub static ref DEVICES: Vec<Device> = vec![Device {
         name: "PixCut S1".to_string(),
         model: "DHP700".to_string(),
-        dpi: 300.0,
+        dpi: Vec2::splat(300.0),
         cutter_calibration: Some(CutterCalibration {
-            scale_factor: 3.38667 * 1.01333,
+            scale_factor: Vec2::splat(3.38667 * 1.01333),
             offset: Vec2::new(-9.0, -13.0),
+            swap_axes: true,
+            mirror: Some(Axis::Y),
         }),
+        // Only JPEG has been seen from the vendor app, PNG's code is from the
+        // protocol's list of document formats.
+        image_formats: vec![
+            ImageFormat {
+                encoding: ImageEncoding::Jpeg,
+                document_format: 9,
+            },
+            ImageFormat {
+                encoding: ImageEncoding::Png,
+                document_format: 10,
+            },
+        ],
+        max_copies: 10,
+        quirks: DeviceQuirks {
+            reserved_byte: 0,
+            hash_method: HashMethod::Sha1,
+            max_data_size: 1023,
+        },
         modes: vec![
             Mode {
                 mode_type: ModeType::Print,
+                output_rotation: Rotation::None,
                 canvas_sizes: vec![CanvasSize {
                     name: "4x6".to_string(),
                     media_size: 5012,
@@ -30,6 +53,18 @@ lazy_static! {
             },
             Mode {
                 mode_type: ModeType::PrintAndCut,
+                output_rotation: Rotation::None,
+                canvas_sizes: vec![CanvasSize {
+                    name: "4x7".to_string(),
+                    media_size: 5013,
+                    media_type: 2030,
+                    size: Vec2::new(4.0 * 300.0, 7.0 * 300.0),
+                    safe_area: Vec2::new(3.62 * 300.0, 6.77 * 300.0),
+                }]
+            },
+            Mode {
+                mode_type: ModeType::CutOnly,
+                output_rotation: Rotation::None,
                 canvas_sizes: vec![CanvasSize {
                     name: "4x7".to_string(),
                     media_size: 5013,
@@ -40,6 +75,19 @@ lazy_static! {
             }
         ]
     }];
+
+    /// Every device's canvas sizes turned sideways, indexed the same way as
+    /// the devices.
+    pub static ref LANDSCAPE_CANVAS_SIZES: Vec<Vec<Vec<CanvasSize>>> = DEVICES
+        .iter()
+        .map(|device| {
+            device
+                .modes
+                .iter()
+                .map(|mode| mode.canvas_sizes.iter().map(CanvasSize::rotated).collect())
+                .collect()
+        })
+        .collect();
 }
 
 #[derive(Error, Debug)]
@@ -48,11 +96,21 @@ pub enum ProtocolError {
     Reader(std::io::Error),
     #[error("invalid data for field: {0}")]
     InvalidData(&'static str),
+    #[error("packet was not an unencrypted json message")]
+    NotJson,
+    #[error("could not decode json: {0}")]
+    Json(serde_json::Error),
+    #[error("{0}")]
+    Device(AvocadoError),
 }
 
-#[derive(Debug, Clone, Serialize)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AvocadoPacket {
     pub version: u8,
+    /// Header byte with no known meaning, set from [`DeviceQuirks`] when
+    /// sending.
+    #[serde(default)]
+    pub reserved: u8,
     pub content_type: ContentType,
     pub interaction_type: InteractionType,
     pub encoding_type: EncodingType,
@@ -67,6 +125,15 @@ pub struct AvocadoPacket {
 
 impl AvocadoPacket {
     pub fn as_json<T>(&self) -> Option<T>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        self.try_json().ok()
+    }
+
+    /// Decode the packet's data as JSON, with the reason it could not be
+    /// decoded on failure.
+    pub fn try_json<T>(&self) -> Result<T, ProtocolError>
     where
         T: serde::de::DeserializeOwned,
     {
@@ -74,9 +141,52 @@ impl AvocadoPacket {
             && self.encryption_mode == EncryptionMode::None
             && self.encoding_type == EncodingType::Json
         {
-            serde_json::from_slice(&self.data).ok()
+            serde_json::from_slice(&self.data).map_err(ProtocolError::Json)
         } else {
-            None
+            Err(ProtocolError::NotJson)
+        }
+    }
+
+    /// Find inconsistencies between a packet's declared types and its data,
+    /// which usually point to a framing or decoding bug.
+    pub fn lint(&self) -> Vec<&'static str> {
+        let mut warnings = Vec::new();
+
+        // Encrypted data can't be inspected.
+        let is_json = self.encryption_mode == EncryptionMode::None
+            && serde_json::from_slice::<serde_json::Value>(&self.data).is_ok();
+
+        match (self.content_type, self.encoding_type) {
+            (ContentType::Message, EncodingType::Json) => {
+                if self.encryption_mode == EncryptionMode::None && !is_json {
+                    warnings.push("Encoding is JSON but data is not valid JSON");
+                }
+            }
+            (ContentType::Message, EncodingType::Hexadecimal) => {
+                warnings.push("Message is not JSON encoded");
+            }
+            (ContentType::Data, EncodingType::Json) => {
+                warnings.push("Data is JSON encoded");
+            }
+            (ContentType::Data, EncodingType::Hexadecimal) => {
+                if is_json {
+                    warnings.push("Data looks like a JSON message");
+                }
+            }
+        }
+
+        warnings
+    }
+
+    /// Decode the packet as a response, turning an error reported by the
+    /// device into [`ProtocolError::Device`].
+    pub fn try_result<T>(&self) -> Result<T, ProtocolError>
+    where
+        T: serde::de::DeserializeOwned,
+    {
+        match self.try_json::<AvocadoResponse<T>>()? {
+            AvocadoResponse::Result(result) => Ok(result),
+            AvocadoResponse::Error(err) => Err(ProtocolError::Device(err)),
         }
     }
 }
@@ -115,8 +225,16 @@ impl AvocadoPacket {
             return Err(ProtocolError::InvalidData("prefix"));
         }
 
+        Self::read_after_prefix(reader)
+    }
+
+    /// Read a packet where the prefix has already been consumed.
+    fn read_after_prefix<R>(reader: &mut R) -> Result<Self, ProtocolError>
+    where
+        R: std::io::Read,
+    {
         let version = reader.read_u8().map_err(ProtocolError::Reader)?;
-        let _reserved = reader.read_u8().map_err(ProtocolError::Reader)?;
+        let reserved = reader.read_u8().map_err(ProtocolError::Reader)?;
 
         let content_type = Self::read_enum(reader, "content_type")?;
         trace!(?content_type);
@@ -165,6 +283,7 @@ impl AvocadoPacket {
 
         Ok(Self {
             version,
+            reserved,
             content_type,
             interaction_type,
             encoding_type,
@@ -178,13 +297,29 @@ impl AvocadoPacket {
         })
     }
 
+    /// Format the packet as a Rust snippet that parses it again, for
+    /// including exact data in bug reports.
+    pub fn repro_snippet(&self) -> String {
+        let fields: String = serde_json::to_string_pretty(self)
+            .unwrap_or_default()
+            .lines()
+            .map(|line| format!("// {line}\n"))
+            .collect();
+
+        format!(
+            "{fields}let data = hex::decode(\"{}\").unwrap();\n\
+             let packet = AvocadoPacket::read_one(&mut std::io::Cursor::new(data)).unwrap();\n",
+            hex::encode(self.encode())
+        )
+    }
+
     #[instrument(skip_all)]
     pub fn encode(&self) -> Vec<u8> {
         let mut buf = Vec::with_capacity(self.data.len() + 22);
 
         buf.push(WRAPPER);
         buf.push(100); // version
-        buf.push(0); // reserved
+        buf.push(self.reserved);
         buf.push(self.content_type.to_primitive());
         buf.push(self.interaction_type.to_primitive());
         buf.push(self.encoding_type.to_primitive());
@@ -207,6 +342,16 @@ impl AvocadoPacket {
         buf
     }
 
+    /// Check if the checksum in an encoded packet matches the one we would
+    /// calculate for it, or `None` if it's too short to be a packet.
+    pub fn checksum_matches(frame: &[u8]) -> Option<bool> {
+        let [_prefix, body @ .., checksum, _suffix] = frame else {
+            return None;
+        };
+
+        Some(Self::checksum(body) == *checksum)
+    }
+
     fn checksum(data: &[u8]) -> u8 {
         data.iter().fold(0u8, |sum, byte| sum.wrapping_add(*byte))
     }
@@ -223,11 +368,28 @@ impl AvocadoPacket {
 
 pub struct AvocadoPacketReader<R> {
     reader: R,
+    lenient: bool,
 }
 
 impl<R> AvocadoPacketReader<R> {
     pub fn new(reader: R) -> Self {
-        Self { reader }
+        Self {
+            reader,
+            lenient: false,
+        }
+    }
+
+    /// Create a reader that skips over any bytes that are not part of a
+    /// packet, such as noise in a serial capture.
+    pub fn new_lenient(reader: R) -> Self {
+        Self {
+            reader,
+            lenient: true,
+        }
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.reader
     }
 }
 
@@ -238,7 +400,13 @@ where
     type Item = Result<AvocadoPacket, ProtocolError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        match AvocadoPacket::read_one(&mut self.reader) {
+        let packet = if self.lenient {
+            self.read_resync()
+        } else {
+            AvocadoPacket::read_one(&mut self.reader)
+        };
+
+        match packet {
             Ok(packet) => Some(Ok(packet)),
             Err(ProtocolError::Reader(err)) if err.kind() == std::io::ErrorKind::UnexpectedEof => {
                 None
@@ -248,7 +416,126 @@ where
     }
 }
 
-#[derive(PrimitiveEnum_u8, Clone, Copy, Debug, PartialEq, Hash, Serialize)]
+/// An error encountered while loading packets, with the byte offset of the
+/// packet that caused it.
+#[derive(Debug)]
+pub struct LoadedPacketError {
+    pub offset: u64,
+    pub error: ProtocolError,
+}
+
+/// Packets loaded from a saved capture.
+///
+/// Unlike collecting an [`AvocadoPacketReader`] into a `Result`, packets that
+/// were successfully parsed before an error are kept.
+#[derive(Debug, Default)]
+pub struct LoadedPackets {
+    pub packets: Vec<AvocadoPacket>,
+    pub errors: Vec<LoadedPacketError>,
+}
+
+impl LoadedPackets {
+    /// Load all packets from some data.
+    ///
+    /// The callback is called after each packet with the number of bytes
+    /// processed and the total number of bytes. Loading stops at the first
+    /// error, as framing can no longer be trusted.
+    ///
+    /// Data may also be a JSON array of serialized packets.
+    pub fn load<F>(data: &[u8], f: F) -> Self
+    where
+        F: Fn(usize, usize),
+    {
+        Self::load_with(AvocadoPacketReader::new(std::io::Cursor::new(data)), f)
+    }
+
+    /// Load all packets from some data like [`Self::load`], but skip over
+    /// any bytes that are not part of a packet, continuing after errors by
+    /// resynchronizing on the next prefix.
+    pub fn load_lenient<F>(data: &[u8], f: F) -> Self
+    where
+        F: Fn(usize, usize),
+    {
+        Self::load_with(
+            AvocadoPacketReader::new_lenient(std::io::Cursor::new(data)),
+            f,
+        )
+    }
+
+    #[instrument(skip_all)]
+    fn load_with<F>(mut reader: AvocadoPacketReader<std::io::Cursor<&[u8]>>, f: F) -> Self
+    where
+        F: Fn(usize, usize),
+    {
+        let data = *reader.get_ref().get_ref();
+
+        if let Ok(packets) = serde_json::from_slice::<Vec<AvocadoPacket>>(data) {
+            f(data.len(), data.len());
+
+            return Self {
+                packets,
+                ..Default::default()
+            };
+        }
+
+        let mut loaded = Self::default();
+
+        loop {
+            let offset = reader.get_ref().position();
+
+            match reader.next() {
+                Some(Ok(packet)) => loaded.packets.push(packet),
+                Some(Err(error)) => {
+                    loaded.errors.push(LoadedPacketError { offset, error });
+
+                    if !reader.lenient {
+                        break;
+                    }
+                }
+                None => break,
+            }
+
+            f(
+                usize::try_from(reader.get_ref().position()).unwrap(),
+                data.len(),
+            );
+        }
+
+        f(data.len(), data.len());
+
+        loaded
+    }
+}
+
+impl<R> AvocadoPacketReader<R>
+where
+    R: std::io::Read,
+{
+    /// Scan forward to the next prefix and read a packet from there.
+    fn read_resync(&mut self) -> Result<AvocadoPacket, ProtocolError> {
+        let mut skipped = 0;
+        while self.reader.read_u8().map_err(ProtocolError::Reader)? != WRAPPER {
+            skipped += 1;
+        }
+
+        // A suffix immediately followed by a prefix means we started reading
+        // at the end of a previous packet, so skip ahead to the real start.
+        let mut version = self.reader.read_u8().map_err(ProtocolError::Reader)?;
+        while version == WRAPPER {
+            version = self.reader.read_u8().map_err(ProtocolError::Reader)?;
+        }
+
+        if skipped > 0 {
+            warn!(skipped, "skipped bytes before packet prefix");
+        }
+
+        let version = [version];
+        let mut reader = std::io::Read::chain(&version[..], &mut self.reader);
+        AvocadoPacket::read_after_prefix(&mut reader)
+    }
+}
+
+#[derive(PrimitiveEnum_u8, Clone, Copy, Debug, PartialEq, Hash, Serialize, Deserialize)]
 pub enum ContentType {
     Message = 1,
     Data = 2,
@@ -263,7 +550,7 @@ impl std::fmt::Display for ContentType {
     }
 }
 
-#[derive(PrimitiveEnum_u8, Clone, Copy, Debug, PartialEq, Hash, Serialize)]
+#[derive(PrimitiveEnum_u8, Clone, Copy, Debug, PartialEq, Hash, Serialize, Deserialize)]
 pub enum InteractionType {
     Request = 6,
     Response = 7,
@@ -278,7 +565,7 @@ impl std::fmt::Display for InteractionType {
     }
 }
 
-#[derive(PrimitiveEnum_u8, Clone, Copy, Debug, PartialEq, Hash, Serialize)]
+#[derive(PrimitiveEnum_u8, Clone, Copy, Debug, PartialEq, Hash, Serialize, Deserialize)]
 pub enum EncodingType {
     Hexadecimal = 2,
     Json = 3,
@@ -293,7 +580,7 @@ impl std::fmt::Display for EncodingType {
     }
 }
 
-#[derive(PrimitiveEnum_u8, Clone, Copy, Debug, PartialEq, Hash, Serialize)]
+#[derive(PrimitiveEnum_u8, Clone, Copy, Debug, PartialEq, Hash, Serialize, Deserialize)]
 pub enum EncryptionMode {
     None = 0b000,
     RC4 = 0b010,
@@ -321,6 +608,22 @@ pub enum JobState {
     Completed = 9,
 }
 
+impl std::fmt::Display for JobState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Waiting => write!(f, "Waiting"),
+            Self::Start => write!(f, "Starting"),
+            Self::Processing => write!(f, "Processing"),
+            Self::ProcessingHeld => write!(f, "Held"),
+            Self::Pending => write!(f, "Pending"),
+            Self::Terminating => write!(f, "Finishing"),
+            Self::Aborted => write!(f, "Aborted"),
+            Self::Cancelled => write!(f, "Cancelled"),
+            Self::Completed => write!(f, "Completed"),
+        }
+    }
+}
+
 #[derive(PrimitiveEnum_u16, Clone, Copy, Debug, PartialEq, Hash, Serialize)]
 pub enum JobSubState {
     WaitingNone = 1000,
@@ -339,6 +642,47 @@ pub enum JobSubState {
     CompletedNone = 9000,
 }
 
+impl JobSubState {
+    /// A rough estimate of how far along the job is, as the sub-states move
+    /// through a mostly fixed pipeline.
+    pub fn progress(&self) -> f32 {
+        match self {
+            Self::WaitingNone => 0.0,
+            Self::StartNone => 0.05,
+            Self::ProcessingNone => 0.1,
+            Self::ProcessingPrintingDataDownloading => 0.2,
+            Self::ProcessingPrintingDataUploading => 0.3,
+            Self::ProcessingPrintingDataCloudRendering
+            | Self::ProcessingPrintingDataLocalRendering => 0.4,
+            Self::ProcessingPrinting => 0.6,
+            Self::ProcessingHeldNone | Self::PendingNone => 0.6,
+            Self::TerminatingNone => 0.95,
+            Self::AbortedNone | Self::CancelledNone | Self::CompletedNone => 1.0,
+        }
+    }
+}
+
+impl std::fmt::Display for JobSubState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WaitingNone => write!(f, "Waiting"),
+            Self::StartNone => write!(f, "Starting"),
+            Self::ProcessingNone => write!(f, "Processing"),
+            Self::ProcessingPrintingDataDownloading => write!(f, "Downloading print data…"),
+            Self::ProcessingPrintingDataUploading => write!(f, "Uploading print data…"),
+            Self::ProcessingPrintingDataCloudRendering => write!(f, "Rendering on cloud…"),
+            Self::ProcessingPrintingDataLocalRendering => write!(f, "Rendering…"),
+            Self::ProcessingPrinting => write!(f, "Printing…"),
+            Self::ProcessingHeldNone => write!(f, "Held"),
+            Self::PendingNone => write!(f, "Pending"),
+            Self::TerminatingNone => write!(f, "Finishing"),
+            Self::AbortedNone => write!(f, "Aborted"),
+            Self::CancelledNone => write!(f, "Cancelled"),
+            Self::CompletedNone => write!(f, "Completed"),
+        }
+    }
+}
+
 #[derive(PrimitiveEnum_u8, Clone, Copy, Debug, PartialEq, Hash, Serialize)]
 pub enum PrinterState {
     Initializing = 10,
@@ -349,6 +693,19 @@ pub enum PrinterState {
     Error = 60,
 }
 
+impl std::fmt::Display for PrinterState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Initializing => write!(f, "Initializing"),
+            Self::Idle => write!(f, "Idle"),
+            Self::Sleep => write!(f, "Sleeping"),
+            Self::Processing => write!(f, "Processing"),
+            Self::Off => write!(f, "Off"),
+            Self::Error => write!(f, "Error"),
+        }
+    }
+}
+
 #[derive(PrimitiveEnum_u16, Clone, Copy, Debug, PartialEq, Hash, Serialize)]
 pub enum PrinterSubState {
     InitNone = 1000,
@@ -386,6 +743,59 @@ pub enum PrinterSubState {
     ErrorNone = 6000,
 }
 
+impl std::fmt::Display for PrinterSubState {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InitNone => write!(f, "Initializing"),
+            Self::IdleNone => write!(f, "Idle"),
+            Self::Printing => write!(f, "Printing…"),
+            Self::FileTransferring => write!(f, "Receiving file…"),
+            Self::Cancelling => write!(f, "Cancelling…"),
+            Self::Upgrading => write!(f, "Upgrading firmware…"),
+            Self::Calibrating => write!(f, "Calibrating…"),
+            Self::SemiAutoPrinting => write!(f, "Printing…"),
+            Self::SemiAutoScanRequired => write!(f, "Waiting for scan"),
+            Self::SemiAutoScanning => write!(f, "Scanning…"),
+            Self::ScanWaiting => write!(f, "Waiting for scan"),
+            Self::CopyWaiting => write!(f, "Waiting for copy"),
+            Self::Rendering => write!(f, "Rendering…"),
+            Self::Initializing => write!(f, "Initializing…"),
+            Self::Decoding => write!(f, "Decoding…"),
+            Self::LoadingPaper => write!(f, "Loading paper…"),
+            Self::PrintingYellow => write!(f, "Printing yellow…"),
+            Self::PrintingMagenta => write!(f, "Printing magenta…"),
+            Self::PrintingCyan => write!(f, "Printing cyan…"),
+            Self::PrintingOC => write!(f, "Applying overcoat…"),
+            Self::Preheating => write!(f, "Preheating…"),
+            Self::Cooldown => write!(f, "Cooling down…"),
+            Self::Cleaning => write!(f, "Cleaning…"),
+            Self::HomeFeed => write!(f, "Feeding paper…"),
+            Self::EjectingPaper => write!(f, "Ejecting paper…"),
+            Self::SmartSheet => write!(f, "Reading smart sheet…"),
+            Self::CutPick => write!(f, "Picking sheet…"),
+            Self::CutHome => write!(f, "Homing cutter…"),
+            Self::Cutting => write!(f, "Cutting…"),
+            Self::CutEject => write!(f, "Ejecting sheet…"),
+            Self::Normal => write!(f, "Normal"),
+            Self::NotRealOff => write!(f, "Standby"),
+            Self::ErrorNone => write!(f, "Error"),
+        }
+    }
+}
+
+impl PrinterSubState {
+    /// If the printer is waiting on someone to do something before it can
+    /// continue.
+    pub fn requires_action(&self) -> bool {
+        matches!(
+            self,
+            PrinterSubState::SemiAutoScanRequired
+                | PrinterSubState::ScanWaiting
+                | PrinterSubState::CopyWaiting
+        )
+    }
+}
+
 macro_rules! impl_de_str_primitive {
     ($t:ty) => {
         impl<'de> serde::Deserialize<'de> for $t {
@@ -414,6 +824,27 @@ macro_rules! impl_de_str_primitive {
     };
 }
 
+/// Deserialize a number that may be encoded as a string.
+fn deserialize_str_or_number<'de, D, T>(deserializer: D) -> Result<T, D::Error>
+where
+    D: serde::Deserializer<'de>,
+    T: Deserialize<'de> + std::str::FromStr,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StrOrNumber<T> {
+        Str(String),
+        Number(T),
+    }
+
+    match StrOrNumber::<T>::deserialize(deserializer)? {
+        StrOrNumber::Str(s) => s
+            .parse()
+            .map_err(|_| serde::de::Error::custom("value was not a number")),
+        StrOrNumber::Number(val) => Ok(val),
+    }
+}
+
 impl_de_str_primitive!(JobState);
 impl_de_str_primitive!(JobSubState);
 impl_de_str_primitive!(PrinterState);
@@ -424,15 +855,116 @@ pub struct AvocadoId {
     pub id: u32,
 }
 
+/// A response to a request, with either the result or the reason the device
+/// rejected the request.
+#[derive(Debug)]
+pub enum AvocadoResponse<T> {
+    Result(T),
+    Error(AvocadoError),
+}
+
+impl<'de, T> Deserialize<'de> for AvocadoResponse<T>
+where
+    T: serde::de::DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // Decode the result separately so its errors aren't hidden behind a
+        // generic untagged enum error.
+        #[derive(Deserialize)]
+        struct Response {
+            #[serde(default)]
+            result: serde_json::Value,
+            error: Option<AvocadoError>,
+        }
+
+        let response = Response::deserialize(deserializer)?;
+        match response.error {
+            Some(err) => Ok(Self::Error(err)),
+            None => T::deserialize(response.result)
+                .map(Self::Result)
+                .map_err(serde::de::Error::custom),
+        }
+    }
+}
+
+/// An error reported by the device instead of a result.
+#[derive(Debug, Clone, Deserialize, Error)]
+#[error("device returned error {code}: {message}")]
+pub struct AvocadoError {
+    #[serde(deserialize_with = "deserialize_str_or_number")]
+    pub code: i64,
+    #[serde(default)]
+    pub message: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AvocadoMethod {
+    pub id: u32,
+    pub method: String,
+}
+
+/// The result of starting a job.
 #[derive(Debug, Deserialize)]
-pub struct AvocadoResult<T> {
-    pub result: T,
+#[serde(rename_all = "kebab-case")]
+pub struct JobResult {
+    #[serde(deserialize_with = "deserialize_str_or_number")]
+    pub job_id: u32,
+}
+
+/// A method with a response that can be validated against its expected type.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum KnownMethod {
+    GetJobInfo,
+    PrintJob,
+    ComboJob,
+    CutJob,
+}
+
+impl KnownMethod {
+    pub fn from_name(method: &str) -> Option<Self> {
+        match method {
+            "get-job-info" => Some(Self::GetJobInfo),
+            "print-job" => Some(Self::PrintJob),
+            "combo-job" => Some(Self::ComboJob),
+            "cut-job" => Some(Self::CutJob),
+            _ => None,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::GetJobInfo => "get-job-info",
+            Self::PrintJob => "print-job",
+            Self::ComboJob => "combo-job",
+            Self::CutJob => "cut-job",
+        }
+    }
+
+    /// If sending this method creates a job on the device.
+    pub fn creates_job(&self) -> bool {
+        matches!(self, Self::PrintJob | Self::ComboJob | Self::CutJob)
+    }
+
+    /// Check that a response packet decodes as the expected type for this
+    /// method, returning the decode error if not.
+    pub fn validate_response(&self, packet: &AvocadoPacket) -> Result<(), ProtocolError> {
+        match self {
+            Self::GetJobInfo => packet.try_result::<Vec<JobStatusInfo>>().map(|_| ()),
+            Self::PrintJob | Self::ComboJob | Self::CutJob => {
+                packet.try_result::<JobResult>().map(|_| ())
+            }
+        }
+    }
 }
 
 #[allow(dead_code)]
 #[derive(Debug, Clone, Deserialize)]
 #[serde(rename_all = "kebab-case")]
 pub struct JobStatusInfo {
+    #[serde(deserialize_with = "deserialize_str_or_number")]
     pub job_id: u32,
     pub job_state: JobState,
     pub job_sub_state: JobSubState,
@@ -454,30 +986,174 @@ pub struct JobStatusInfo {
 pub struct Device {
     pub name: String,
     pub model: String,
-    pub dpi: f32,
+    /// Horizontal and vertical resolution, which may differ on print heads
+    /// without square pixels.
+    pub dpi: Vec2,
     pub cutter_calibration: Option<CutterCalibration>,
+    /// Image formats supported for printing, the first is the default.
+    pub image_formats: Vec<ImageFormat>,
+    /// Most copies the device accepts in a single job.
+    pub max_copies: usize,
+    pub quirks: DeviceQuirks,
     pub modes: Vec<Mode>,
 }
 
+impl Device {
+    /// Resolution for lengths that aren't along a single axis, like cut
+    /// buffers and gaps between images.
+    pub fn average_dpi(&self) -> f32 {
+        (self.dpi.x + self.dpi.y) / 2.0
+    }
+}
+
+/// Model specific behavior that isn't described by the rest of the device,
+/// kept together so it's not scattered around as conditionals.
+#[derive(Debug, Clone)]
+pub struct DeviceQuirks {
+    /// Value for the reserved byte in the header of sent packets.
+    pub reserved_byte: u8,
+    /// How print job data is hashed.
+    pub hash_method: HashMethod,
+    /// Largest data size the device accepts in a packet, including the job
+    /// ID, regardless of the configured data size.
+    pub max_data_size: usize,
+}
+
+impl Default for DeviceQuirks {
+    /// How the vendor app behaves, for when the device isn't known yet.
+    fn default() -> Self {
+        Self {
+            reserved_byte: 0,
+            hash_method: HashMethod::Sha1,
+            max_data_size: crate::transports::MAX_DATA_SIZE,
+        }
+    }
+}
+
+/// How the data for a print job is hashed, so the device can check it.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, strum::EnumIter)]
+pub enum HashMethod {
+    Sha1,
+    Md5,
+}
+
+impl HashMethod {
+    /// Value for the `hash-method` field of a print job.
+    pub fn code(&self) -> u8 {
+        match self {
+            Self::Sha1 => 1,
+            Self::Md5 => 2,
+        }
+    }
+
+    pub fn from_code(code: u64) -> Option<Self> {
+        Self::iter().find(|method| u64::from(method.code()) == code)
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::Sha1 => "SHA-1",
+            Self::Md5 => "MD5",
+        }
+    }
+
+    pub fn digest(&self, data: &[u8]) -> Vec<u8> {
+        match self {
+            Self::Sha1 => sha1::Sha1::digest(data).to_vec(),
+            Self::Md5 => md5::Md5::digest(data).to_vec(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ImageFormat {
+    pub encoding: ImageEncoding,
+    /// Value for the `document-format` field of a print job.
+    pub document_format: u16,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageEncoding {
+    Jpeg,
+    Png,
+}
+
+impl ImageEncoding {
+    pub fn name(&self) -> &'static str {
+        match self {
+            ImageEncoding::Jpeg => "JPEG",
+            ImageEncoding::Png => "PNG",
+        }
+    }
+
+    pub fn extension(&self) -> &'static str {
+        match self {
+            ImageEncoding::Jpeg => "jpeg",
+            ImageEncoding::Png => "png",
+        }
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct CutterCalibration {
-    pub scale_factor: f32,
+    /// Cutter units per canvas pixel along each axis, before any swap.
+    pub scale_factor: Vec2,
     pub offset: Vec2,
+    /// If the cutter expects the Y coordinate before the X coordinate.
+    pub swap_axes: bool,
+    /// An axis to mirror the cut lines across before sending them.
+    pub mirror: Option<Axis>,
+}
+
+impl CutterCalibration {
+    /// Convert a point on the canvas into a coordinate pair for the cutter.
+    pub fn to_cutter(&self, point: geo::Coord<f32>) -> CutterPoint {
+        let x = (point.x + self.offset.x) * self.scale_factor.x;
+        let y = (point.y + self.offset.y) * self.scale_factor.y;
+
+        if self.swap_axes {
+            CutterPoint(y, x)
+        } else {
+            CutterPoint(x, y)
+        }
+    }
 }
 
 impl Default for CutterCalibration {
     fn default() -> Self {
         Self {
-            scale_factor: 1.0,
+            scale_factor: Vec2::splat(1.0),
             offset: Vec2::ZERO,
+            swap_axes: false,
+            mirror: None,
         }
     }
 }
 
-#[derive(Debug, Clone)]
+/// A point in the cutter's coordinate system, formatted as used in PLT data.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CutterPoint(pub f32, pub f32);
+
+impl std::fmt::Display for CutterPoint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:.0},{:.0}", self.0, self.1)
+    }
+}
+
+/// An axis on the canvas.
+#[allow(dead_code)]
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Axis {
+    X,
+    Y,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub enum ModeType {
     Print,
     PrintAndCut,
+    /// Cut an already printed sheet, without printing anything.
+    CutOnly,
 }
 
 impl ModeType {
@@ -485,41 +1161,126 @@ impl ModeType {
         match self {
             ModeType::Print => "Print",
             ModeType::PrintAndCut => "Print and Cut",
+            ModeType::CutOnly => "Cut Only",
         }
     }
 
     pub fn channel(&self) -> u16 {
         match self {
             ModeType::Print => 30784,
-            ModeType::PrintAndCut => 30960,
+            ModeType::PrintAndCut | ModeType::CutOnly => 30960,
         }
     }
 
     pub fn job_type(&self) -> u16 {
         match self {
             ModeType::Print => 0,
-            ModeType::PrintAndCut => 600,
+            ModeType::PrintAndCut | ModeType::CutOnly => 600,
         }
     }
 
     pub fn link_type(&self) -> u16 {
         match self {
             ModeType::Print => 1000,
-            ModeType::PrintAndCut => 0,
+            ModeType::PrintAndCut | ModeType::CutOnly => 0,
         }
     }
 
+    pub fn has_printing(&self) -> bool {
+        matches!(self, ModeType::Print | ModeType::PrintAndCut)
+    }
+
     pub fn has_cutting(&self) -> bool {
-        matches!(self, ModeType::PrintAndCut)
+        matches!(self, ModeType::PrintAndCut | ModeType::CutOnly)
     }
 }
 
 #[derive(Debug, Clone)]
 pub struct Mode {
     pub mode_type: ModeType,
+    /// Rotation applied to the rendered canvas before sending it, for
+    /// printers that feed in a different orientation than the canvas.
+    pub output_rotation: Rotation,
     pub canvas_sizes: Vec<CanvasSize>,
 }
 
+/// A clockwise rotation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[allow(dead_code)]
+pub enum Rotation {
+    None,
+    Rotate90,
+    Rotate180,
+    Rotate270,
+}
+
+impl Rotation {
+    pub fn apply(&self, im: image::DynamicImage) -> image::DynamicImage {
+        match self {
+            Rotation::None => im,
+            Rotation::Rotate90 => im.rotate90(),
+            Rotation::Rotate180 => im.rotate180(),
+            Rotation::Rotate270 => im.rotate270(),
+        }
+    }
+
+    /// Where points on an image of the given size end up once it's rotated.
+    pub fn transform(&self, size: Vec2) -> geo::AffineTransform<f32> {
+        match self {
+            Rotation::None => geo::AffineTransform::identity(),
+            Rotation::Rotate90 => geo::AffineTransform::new(0.0, -1.0, size.y, 1.0, 0.0, 0.0),
+            Rotation::Rotate180 => geo::AffineTransform::new(-1.0, 0.0, size.x, 0.0, -1.0, size.y),
+            Rotation::Rotate270 => geo::AffineTransform::new(0.0, 1.0, 0.0, -1.0, 0.0, size.x),
+        }
+    }
+
+    /// The size of an image once it's rotated.
+    pub fn rotated_size(&self, size: Vec2) -> Vec2 {
+        match self {
+            Rotation::None | Rotation::Rotate180 => size,
+            Rotation::Rotate90 | Rotation::Rotate270 => Vec2::new(size.y, size.x),
+        }
+    }
+}
+
+/// Names for known media size codes.
+///
+/// Codes that aren't listed are still sent as is, so add them here as
+/// they're seen.
+pub const MEDIA_SIZES: &[(u16, &str)] = &[(5012, "4 × 6 in"), (5013, "4 × 7 in")];
+
+/// Names for known media type codes.
+pub const MEDIA_TYPES: &[(u16, &str)] = &[(2010, "Photo paper"), (2030, "Sticker sheet")];
+
+/// Look up the name of a media code in one of the media tables.
+pub fn media_name(table: &[(u16, &'static str)], code: u16) -> Option<&'static str> {
+    table
+        .iter()
+        .find_map(|(known, name)| (*known == code).then_some(*name))
+}
+
+/// The media the device reports as loaded.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct LoadedMedia {
+    #[serde(deserialize_with = "deserialize_str_or_number")]
+    pub media_size: u16,
+    #[serde(deserialize_with = "deserialize_str_or_number")]
+    pub media_type: u16,
+}
+
+impl LoadedMedia {
+    pub fn describe(&self) -> String {
+        describe_media(self.media_size, self.media_type)
+    }
+}
+
+fn describe_media(media_size: u16, media_type: u16) -> String {
+    let size = media_name(MEDIA_SIZES, media_size).unwrap_or("Unknown size");
+    let kind = media_name(MEDIA_TYPES, media_type).unwrap_or("unknown type");
+
+    format!("{size}, {kind} ({media_size}/{media_type})")
+}
+
 #[derive(Debug, Clone)]
 pub struct CanvasSize {
     pub name: String,
@@ -529,6 +1290,43 @@ pub struct CanvasSize {
     pub safe_area: Vec2,
 }
 
+impl CanvasSize {
+    /// The same media turned sideways, with the width and height swapped.
+    pub fn rotated(&self) -> Self {
+        Self {
+            size: Vec2::new(self.size.y, self.size.x),
+            safe_area: Vec2::new(self.safe_area.y, self.safe_area.x),
+            ..self.clone()
+        }
+    }
+
+    /// The name along with the physical dimensions, so similar sizes can be
+    /// told apart.
+    pub fn label(&self, dpi: Vec2) -> String {
+        format!(
+            "{} ({:.2} × {:.2} in)",
+            self.name,
+            self.size.x / dpi.x,
+            self.size.y / dpi.y
+        )
+    }
+
+    /// Human names for the media codes sent for this size.
+    pub fn media_description(&self) -> String {
+        describe_media(self.media_size, self.media_type)
+    }
+
+    /// If the loaded media is what jobs for this size expect.
+    pub fn matches_media(&self, media: &LoadedMedia) -> bool {
+        self.media_size == media.media_size && self.media_type == media.media_type
+    }
+
+    /// The safe area, centered within the canvas.
+    pub fn safe_rect(&self) -> egui::Rect {
+        egui::Rect::from_center_size((self.size / 2.0).to_pos2(), self.safe_area)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::io::Cursor;
@@ -555,10 +1353,185 @@ mod tests {
         assert!(packet.is_ok());
     }
 
+    #[test]
+    fn test_rotation_transform() {
+        let im = image::RgbaImage::from_fn(3, 2, |x, y| image::Rgba([x as u8, y as u8, 0, 255]));
+        let size = Vec2::new(3.0, 2.0);
+
+        for rotation in [
+            Rotation::None,
+            Rotation::Rotate90,
+            Rotation::Rotate180,
+            Rotation::Rotate270,
+        ] {
+            let rotated = rotation.apply(im.clone().into()).into_rgba8();
+            let rotated_size = rotation.rotated_size(size);
+            assert_eq!(
+                (rotated.width() as f32, rotated.height() as f32),
+                (rotated_size.x, rotated_size.y)
+            );
+
+            // Follow the center of each pixel to where it was rotated to.
+            let transform = rotation.transform(size);
+            for (x, y, pixel) in im.enumerate_pixels() {
+                let coord = transform.apply(geo::coord! { x: x as f32 + 0.5, y: y as f32 + 0.5 });
+                let moved = rotated.get_pixel(coord.x as u32, coord.y as u32);
+                assert_eq!(moved, pixel, "{rotation:?} moved ({x}, {y}) wrong");
+            }
+        }
+    }
+
+    #[test]
+    fn test_checksum_matches() {
+        assert_eq!(
+            AvocadoPacket::checksum_matches(JSON_REQUEST_DATA),
+            Some(true)
+        );
+
+        let mut data = JSON_REQUEST_DATA.to_vec();
+        data[30] ^= 0xFF;
+        assert_eq!(AvocadoPacket::checksum_matches(&data), Some(false));
+
+        assert_eq!(AvocadoPacket::checksum_matches(&[WRAPPER]), None);
+    }
+
+    #[test]
+    fn test_repro_snippet() {
+        let packet = AvocadoPacket::read_one(&mut Cursor::new(JSON_REQUEST_DATA)).unwrap();
+        let snippet = packet.repro_snippet();
+
+        let encoded = snippet
+            .lines()
+            .find_map(|line| line.strip_prefix("let data = hex::decode(\""))
+            .and_then(|line| line.strip_suffix("\").unwrap();"))
+            .unwrap();
+        let parsed =
+            AvocadoPacket::read_one(&mut Cursor::new(hex::decode(encoded).unwrap())).unwrap();
+        assert_eq!(parsed.data, packet.data);
+    }
+
+    #[test]
+    fn test_media_names() {
+        let canvas_size = &DEVICES[0].modes[0].canvas_sizes[0];
+        assert_eq!(
+            canvas_size.media_description(),
+            "4 × 6 in, Photo paper (5012/2010)"
+        );
+
+        let media: LoadedMedia = serde_json::from_str(r#"["5013", 2030]"#).unwrap();
+        assert_eq!(media.describe(), "4 × 7 in, Sticker sheet (5013/2030)");
+        assert!(!canvas_size.matches_media(&media));
+        assert!(DEVICES[0].modes[1].canvas_sizes[0].matches_media(&media));
+
+        assert_eq!(media_name(MEDIA_SIZES, 1), None);
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let packet = AvocadoPacket::read_one(&mut Cursor::new(JSON_REQUEST_DATA)).unwrap();
+
+        let json = serde_json::to_vec(&[&packet]).unwrap();
+        let loaded = LoadedPackets::load(&json, |_, _| ());
+        assert!(loaded.errors.is_empty());
+
+        let [parsed] = &loaded.packets[..] else {
+            panic!("expected one packet, got {}", loaded.packets.len());
+        };
+        assert_eq!(parsed.encode(), packet.encode());
+    }
+
+    #[test]
+    fn test_load_keeps_packets_before_error() {
+        let mut data = JSON_REQUEST_DATA.to_vec();
+        data.extend_from_slice(&[0x00, 0x01, 0x02]);
+
+        let loaded = LoadedPackets::load(&data, |_, _| ());
+        assert_eq!(loaded.packets.len(), 1);
+        assert_eq!(loaded.errors.len(), 1);
+        assert_eq!(loaded.errors[0].offset, JSON_REQUEST_DATA.len() as u64);
+        assert!(matches!(
+            loaded.errors[0].error,
+            ProtocolError::InvalidData("prefix")
+        ));
+    }
+
+    #[test]
+    fn test_load_lenient_continues_after_error() {
+        let mut data = JSON_REQUEST_DATA.to_vec();
+        data.extend_from_slice(&[0x7E, 0x64, 0x00, 0xFF]);
+        data.extend_from_slice(JSON_REQUEST_DATA);
+
+        let loaded = LoadedPackets::load_lenient(&data, |_, _| ());
+        assert_eq!(loaded.packets.len(), 2);
+        assert_eq!(loaded.errors.len(), 1);
+        assert!(matches!(
+            loaded.errors[0].error,
+            ProtocolError::InvalidData("content_type")
+        ));
+    }
+
+    #[test]
+    fn test_lenient_reader_skips_noise() {
+        let mut data = vec![0x00, 0x13, 0x37];
+        data.extend_from_slice(JSON_REQUEST_DATA);
+        data.extend_from_slice(&[0xDE, 0xAD, 0xBE, 0xEF]);
+        data.extend_from_slice(JSON_REQUEST_DATA);
+
+        let strict: Result<Vec<_>, _> = AvocadoPacketReader::new(Cursor::new(&data)).collect();
+        assert!(matches!(strict, Err(ProtocolError::InvalidData("prefix"))));
+
+        let packets: Result<Vec<_>, _> =
+            AvocadoPacketReader::new_lenient(Cursor::new(&data)).collect();
+        assert_eq!(packets.unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_validate_response() {
+        let packet = |data: serde_json::Value| AvocadoPacket {
+            version: 100,
+            reserved: 0,
+            content_type: ContentType::Message,
+            interaction_type: InteractionType::Response,
+            encoding_type: EncodingType::Json,
+            encryption_mode: EncryptionMode::None,
+            terminal_id: 1,
+            msg_number: 1,
+            msg_package_total: 1,
+            msg_package_num: 1,
+            is_subpackage: false,
+            data: serde_json::to_vec(&data).unwrap(),
+        };
+
+        let valid = packet(serde_json::json!({ "id": 1, "result": { "job-id": 123 } }));
+        assert!(KnownMethod::PrintJob.validate_response(&valid).is_ok());
+
+        let invalid = packet(serde_json::json!({ "id": 1, "result": { "job": 123 } }));
+        let err = KnownMethod::PrintJob
+            .validate_response(&invalid)
+            .unwrap_err();
+        assert!(matches!(err, ProtocolError::Json(_)));
+        assert!(err.to_string().contains("job-id"));
+    }
+
+    #[test]
+    fn test_job_id_str_or_number() {
+        let result: JobResult =
+            serde_json::from_value(serde_json::json!({ "job-id": 123 })).unwrap();
+        assert_eq!(result.job_id, 123);
+
+        let result: JobResult =
+            serde_json::from_value(serde_json::json!({ "job-id": "123" })).unwrap();
+        assert_eq!(result.job_id, 123);
+
+        let result = serde_json::from_value::<JobResult>(serde_json::json!({ "job-id": "abc" }));
+        assert!(result.is_err());
+    }
+
     #[test]
     fn test_encode() {
         let packet = AvocadoPacket {
             version: 100,
+            reserved: 0,
             content_type: ContentType::Message,
             interaction_type: InteractionType::Request,
             encoding_type: EncodingType::Json,
@@ -595,4 +1568,50 @@ mod tests {
             ]
         );
     }
+
+    #[test]
+    fn test_avocado_response() {
+        let response: AvocadoResponse<JobResult> =
+            serde_json::from_str(r#"{"id": 1, "result": {"job-id": "7"}}"#).unwrap();
+        assert!(matches!(
+            response,
+            AvocadoResponse::Result(JobResult { job_id: 7 })
+        ));
+
+        let response: AvocadoResponse<JobResult> = serde_json::from_str(
+            r#"{"id": 1, "error": {"code": -32602, "message": "invalid media"}}"#,
+        )
+        .unwrap();
+        let AvocadoResponse::Error(err) = response else {
+            panic!("response was not an error");
+        };
+        assert_eq!(err.code, -32602);
+        assert_eq!(
+            err.to_string(),
+            "device returned error -32602: invalid media"
+        );
+
+        let err = serde_json::from_str::<AvocadoResponse<JobResult>>(r#"{"id": 1}"#).unwrap_err();
+        assert!(err.to_string().contains("invalid type: null"));
+    }
+
+    #[test]
+    fn test_packet_lint() {
+        let mut packet = AvocadoPacket::read_one(&mut Cursor::new(JSON_REQUEST_DATA)).unwrap();
+        assert!(packet.lint().is_empty());
+
+        packet.data.truncate(packet.data.len() - 1);
+        assert_eq!(
+            packet.lint(),
+            vec!["Encoding is JSON but data is not valid JSON"]
+        );
+
+        packet.data = br#"{"id": 1}"#.to_vec();
+        packet.content_type = ContentType::Data;
+        packet.encoding_type = EncodingType::Hexadecimal;
+        assert_eq!(packet.lint(), vec!["Data looks like a JSON message"]);
+
+        packet.data = vec![1, 0, 0, 0, 0xff, 0xd8];
+        assert!(packet.lint().is_empty());
+    }
 }
@@ -0,0 +1,23 @@
+use egui::{Key, KeyboardShortcut, Modifiers};
+
+pub const ADD_IMAGE: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::COMMAND.plus(Modifiers::SHIFT), Key::U);
+pub const GENERATE_CUT_LINES: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::G);
+pub const TOGGLE_CONNECTION: KeyboardShortcut = KeyboardShortcut::new(Modifiers::COMMAND, Key::K);
+pub const REMOVE_IMAGE: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::Delete);
+pub const REMOVE_IMAGE_BACKSPACE: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::NONE, Key::Backspace);
+pub const SHOW_SHORTCUTS: KeyboardShortcut =
+    KeyboardShortcut::new(Modifiers::NONE, Key::Questionmark);
+
+/// Every shortcut with what it does, for listing them in the help window.
+pub const SHORTCUTS: &[(&str, &[KeyboardShortcut])] = &[
+    ("Add image", &[ADD_IMAGE]),
+    ("Generate cut lines", &[GENERATE_CUT_LINES]),
+    ("Connect or disconnect", &[TOGGLE_CONNECTION]),
+    (
+        "Remove the image under the pointer",
+        &[REMOVE_IMAGE, REMOVE_IMAGE_BACKSPACE],
+    ),
+    ("Show keyboard shortcuts", &[SHOW_SHORTCUTS]),
+];
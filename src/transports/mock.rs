@@ -1,18 +1,298 @@
 use std::borrow::Cow;
 
 use async_trait::async_trait;
+use egui::ahash::HashMap;
 use futures::{
     SinkExt,
     channel::{mpsc, oneshot},
 };
+use tracing::{debug, warn};
 
 use crate::{
-    protocol::AvocadoPacket,
-    transports::{TransportControl, TransportEvent, TransportStatus},
+    protocol::{
+        AvocadoPacket, ContentType, DEVICES, EncodingType, EncryptionMode, HashMethod,
+        InteractionType, JobState, JobSubState, PrinterState, PrinterSubState,
+    },
+    transports::{DiscoveredDevice, TransportControl, TransportEvent, TransportStatus},
 };
 
+/// A transport that emulates a printer, accepting jobs and completing them
+/// once all of their data has been received.
 #[derive(Default)]
-pub struct MockTransport {}
+pub struct MockTransport {
+    event_tx: Option<mpsc::UnboundedSender<TransportEvent>>,
+    next_job_id: u32,
+    jobs: HashMap<u32, MockJob>,
+    /// Every packet sent to the transport.
+    sent: Vec<AvocadoPacket>,
+    /// Encoded job packets, when recording.
+    recording: Option<Vec<u8>>,
+    /// Media set with `set-prop`, replacing the default loaded media.
+    media: Option<(u64, u64)>,
+    /// How many more jobs to corrupt a byte of data for.
+    corrupt_jobs: usize,
+    /// If jobs for media other than what's loaded should be aborted.
+    check_media: bool,
+}
+
+struct MockJob {
+    file_size: usize,
+    received: Vec<u8>,
+    /// The hash sent with the job and the length of the data at the end
+    /// that it's for.
+    hash: Option<(String, usize)>,
+    /// The `hash-method` sent with the job.
+    hash_method: u64,
+    cancelled: bool,
+    /// The media size and type the job was sent for.
+    media: (u64, u64),
+    /// If a byte of the received data should be changed, like a noisy
+    /// connection would.
+    corrupt: bool,
+}
+
+impl MockTransport {
+    /// Create a transport that records the bytes of every packet sent for
+    /// jobs, for comparing against known good output.
+    ///
+    /// Status polling isn't recorded, and fields that change between
+    /// sessions are zeroed so recordings of the same job always match.
+    #[cfg(test)]
+    pub fn recording() -> Self {
+        Self {
+            recording: Some(Vec::new()),
+            ..Default::default()
+        }
+    }
+
+    /// Get the bytes recorded since the transport was created, empty if it
+    /// isn't recording.
+    #[cfg(test)]
+    pub fn recorded_bytes(&self) -> &[u8] {
+        self.recording.as_deref().unwrap_or_default()
+    }
+
+    /// Create a transport that corrupts the data received for the given
+    /// number of jobs, like a noisy connection would.
+    #[cfg(test)]
+    pub fn corrupting(corrupt_jobs: usize) -> Self {
+        Self {
+            corrupt_jobs,
+            ..Default::default()
+        }
+    }
+
+    /// Create a transport that aborts jobs for media other than what's
+    /// loaded, like the printer does.
+    #[cfg(test)]
+    pub fn checking_media() -> Self {
+        Self {
+            check_media: true,
+            ..Default::default()
+        }
+    }
+
+    /// The mock has media for the first canvas size loaded, unless other
+    /// media was set.
+    fn loaded_media(&self) -> (u64, u64) {
+        self.media.unwrap_or_else(|| {
+            let canvas_size = &DEVICES[0].modes[0].canvas_sizes[0];
+            (canvas_size.media_size.into(), canvas_size.media_type.into())
+        })
+    }
+
+    /// Get every packet sent to the transport since it was created.
+    #[cfg(test)]
+    pub fn sent_packets(&self) -> &[AvocadoPacket] {
+        &self.sent
+    }
+
+    fn record(&mut self, packet: &AvocadoPacket) {
+        let Some(recording) = self.recording.as_mut() else {
+            return;
+        };
+
+        let mut packet = packet.clone();
+        packet.terminal_id = 0;
+        packet.msg_number = 0;
+
+        if let Some(mut request) = packet.as_json::<serde_json::Value>() {
+            if matches!(
+                request["method"].as_str(),
+                Some("get-prop" | "get-job-info")
+            ) {
+                return;
+            }
+
+            request["id"] = 0.into();
+            packet.data = serde_json::to_vec(&request).unwrap();
+        }
+
+        recording.extend(packet.encode());
+    }
+
+    /// Build the response to a request, if the request needs one.
+    fn respond(&mut self, packet: &AvocadoPacket) -> Option<serde_json::Value> {
+        if packet.content_type == ContentType::Data {
+            let (job_id, data) = packet.data.split_first_chunk::<4>()?;
+            match self.jobs.get_mut(&u32::from_le_bytes(*job_id)) {
+                Some(job) => job.received.extend_from_slice(data),
+                None => warn!("got data for unknown job"),
+            }
+
+            return None;
+        }
+
+        let request: serde_json::Value = packet.as_json()?;
+        let method = request["method"].as_str().unwrap_or_default();
+        let params = &request["params"];
+        debug!(method, "mock got request");
+
+        let loaded_media = self.loaded_media();
+
+        let result = match method {
+            "print-job" | "cut-job" | "combo-job" => {
+                // Combo jobs have params for each part, with data for both.
+                let (file_size, first) = match params.as_array() {
+                    Some(parts) => (
+                        parts.iter().map(|part| file_size(&part["params"])).sum(),
+                        parts.first().map(|part| &part["params"]).unwrap_or(params),
+                    ),
+                    None => (file_size(params), params),
+                };
+
+                // Only prints are hashed, which come last in combo jobs.
+                let hash = first["hash-value"]
+                    .as_str()
+                    .map(|hash| (hash.to_string(), self::file_size(first)));
+
+                self.next_job_id += 1;
+                self.jobs.insert(
+                    self.next_job_id,
+                    MockJob {
+                        file_size,
+                        received: Vec::new(),
+                        hash,
+                        hash_method: first["hash-method"].as_u64().unwrap_or_default(),
+                        cancelled: false,
+                        media: media(first),
+                        corrupt: self.corrupt_jobs > 0,
+                    },
+                );
+                self.corrupt_jobs = self.corrupt_jobs.saturating_sub(1);
+
+                serde_json::json!({ "job-id": self.next_job_id })
+            }
+            "get-job-info" => {
+                let job_id = params["job-id"].as_u64().unwrap_or_default() as u32;
+                let Some(job) = self.jobs.get(&job_id) else {
+                    return Some(serde_json::json!({ "id": request["id"], "result": [] }));
+                };
+
+                // Like the printer, refuse jobs with data that doesn't match
+                // its hash, and for media that isn't loaded when checking.
+                let received_all = job.received.len() >= job.file_size;
+                let media_mismatch = self.check_media && job.media != loaded_media;
+                let (job_state, job_sub_state) = if job.cancelled {
+                    (JobState::Cancelled, JobSubState::CancelledNone)
+                } else if media_mismatch || (received_all && !job.hash_matches()) {
+                    (JobState::Aborted, JobSubState::AbortedNone)
+                } else if received_all {
+                    (JobState::Completed, JobSubState::CompletedNone)
+                } else {
+                    (
+                        JobState::Processing,
+                        JobSubState::ProcessingPrintingDataDownloading,
+                    )
+                };
+
+                serde_json::json!([{
+                    "job-id": job_id,
+                    "job-state": job_state as u8,
+                    "job-sub-state": job_sub_state as u16,
+                    "copies": 1,
+                    "printing-page-number": 0,
+                    "user-account": "",
+                    "channel": 0,
+                    "media-size": job.media.0,
+                    "media-type": job.media.1,
+                    "job-type": 0,
+                    "document-format": 0,
+                    "file-size": job.file_size,
+                    "transfer-status": 0,
+                    "transfer-size": job.received.len(),
+                }])
+            }
+            "cancel-job" => {
+                let job_id = params["job-id"].as_u64().unwrap_or_default() as u32;
+                if let Some(job) = self.jobs.get_mut(&job_id) {
+                    job.cancelled = true;
+                }
+
+                serde_json::Value::Null
+            }
+            "get-prop" => {
+                let (media_size, media_type) = loaded_media;
+
+                params
+                    .as_array()
+                    .into_iter()
+                    .flatten()
+                    .map(|prop| match prop.as_str().unwrap_or_default() {
+                        "printer-state" => serde_json::json!(PrinterState::Idle as u8),
+                        "printer-sub-state" => serde_json::json!(PrinterSubState::IdleNone as u16),
+                        "media-size" => serde_json::json!(media_size),
+                        "media-type" => serde_json::json!(media_type),
+                        _ => serde_json::json!(""),
+                    })
+                    .collect()
+            }
+            "set-prop" => {
+                if params.get("media-size").is_some() || params.get("media-type").is_some() {
+                    self.media = Some(media(params));
+                }
+
+                serde_json::Value::Null
+            }
+            _ => serde_json::Value::Null,
+        };
+
+        Some(serde_json::json!({ "id": request["id"], "result": result }))
+    }
+}
+
+impl MockJob {
+    /// If the received data matches the hash sent with the job, with a byte
+    /// changed first if the job is being corrupted.
+    fn hash_matches(&self) -> bool {
+        let Some((hash, len)) = &self.hash else {
+            return true;
+        };
+
+        let mut data = self.received[self.received.len().saturating_sub(*len)..].to_vec();
+        if self.corrupt
+            && let Some(byte) = data.first_mut()
+        {
+            *byte ^= 0xFF;
+        }
+
+        // Methods we can't compute the hash for never match, like a device
+        // that doesn't support them.
+        HashMethod::from_code(self.hash_method)
+            .is_some_and(|method| hex::encode(method.digest(&data)) == *hash)
+    }
+}
+
+fn file_size(params: &serde_json::Value) -> usize {
+    params["file-size"].as_u64().unwrap_or_default() as usize
+}
+
+fn media(params: &serde_json::Value) -> (u64, u64) {
+    (
+        params["media-size"].as_u64().unwrap_or_default(),
+        params["media-type"].as_u64().unwrap_or_default(),
+    )
+}
 
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
@@ -28,27 +308,69 @@ impl TransportControl for MockTransport {
     async fn start(
         &mut self,
         mut event_tx: mpsc::UnboundedSender<TransportEvent>,
+        _target: Option<DiscoveredDevice>,
     ) -> anyhow::Result<()> {
         event_tx
-            .send(TransportEvent::TransportStatus(
-                TransportStatus::Disconnected,
-            ))
+            .send(TransportEvent::TransportStatus(TransportStatus::Connected))
             .await?;
 
+        self.event_tx = Some(event_tx);
+
         Ok(())
     }
 
     async fn disconnect(&mut self) -> anyhow::Result<()> {
+        if let Some(mut event_tx) = self.event_tx.take() {
+            event_tx
+                .send(TransportEvent::TransportStatus(
+                    TransportStatus::Disconnected,
+                ))
+                .await?;
+        }
+
         Ok(())
     }
 
     async fn send_packet(
         &mut self,
-        _packet: AvocadoPacket,
+        packet: AvocadoPacket,
     ) -> anyhow::Result<oneshot::Receiver<()>> {
         let (tx, rx) = oneshot::channel();
         tx.send(()).unwrap();
 
+        if let Some(event_tx) = self.event_tx.as_mut() {
+            event_tx
+                .send(TransportEvent::BytesWritten(packet.encode().len()))
+                .await?;
+        }
+
+        self.record(&packet);
+        let response = self.respond(&packet);
+        let msg_number = packet.msg_number;
+        let terminal_id = packet.terminal_id;
+        self.sent.push(packet);
+
+        if let Some(response) = response
+            && let Some(event_tx) = self.event_tx.as_mut()
+        {
+            event_tx
+                .send(TransportEvent::Packet(AvocadoPacket {
+                    version: 100,
+                    reserved: 0,
+                    content_type: ContentType::Message,
+                    interaction_type: InteractionType::Response,
+                    encoding_type: EncodingType::Json,
+                    encryption_mode: EncryptionMode::None,
+                    terminal_id,
+                    msg_number,
+                    msg_package_total: 1,
+                    msg_package_num: 1,
+                    is_subpackage: false,
+                    data: serde_json::to_vec(&response)?,
+                }))
+                .await?;
+        }
+
         Ok(rx)
     }
 }
@@ -1,5 +1,5 @@
 use std::borrow::Cow;
-use std::sync::atomic::{AtomicBool, AtomicU32};
+use std::sync::atomic::{AtomicBool, AtomicU32, AtomicU64, AtomicUsize};
 use std::time::Duration;
 
 use anyhow::bail;
@@ -11,16 +11,21 @@ use futures::{
     channel::{mpsc, oneshot},
     lock::Mutex,
 };
+use thiserror::Error;
 use tracing::{debug, error, info, instrument, trace, warn};
 
 use crate::protocol::*;
 
 use crate::transports::mock::MockTransport;
+#[cfg(not(target_arch = "wasm32"))]
+use crate::transports::tcp::TcpTransport;
 #[cfg(target_arch = "wasm32")]
 use crate::transports::web_serial::WebSerialTransport;
-use crate::{Rc, interval, spawn};
+use crate::{Rc, current_timestamp_millis, interval, spawn, timeout};
 
 pub mod mock;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tcp;
 #[cfg(target_arch = "wasm32")]
 pub mod web_serial;
 
@@ -29,8 +34,29 @@ pub mod web_serial;
 /// [`TransportManager::next_message_id`].
 static MESSAGE_ID: AtomicU32 = AtomicU32::new(1);
 
-/// Maximum size of data within a message.
-pub const MAX_DATA_SIZE: usize = 896;
+/// Default size of data within a message, including the 4 byte job ID.
+pub const DEFAULT_DATA_SIZE: usize = 896;
+
+/// Largest data size that fits in a packet's 10 bit length field.
+pub const MAX_DATA_SIZE: usize = 1023;
+
+/// Size of the job ID at the start of each data packet.
+pub const JOB_ID_SIZE: usize = 4;
+
+/// Seconds a job can go without changing before it's considered stuck.
+const JOB_STALL_SECS: u64 = 60;
+
+/// Default time to wait for the device to answer its first status request.
+pub const DEFAULT_HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Status requests sent within the handshake timeout before giving up.
+const HANDSHAKE_ATTEMPTS: u32 = 3;
+
+/// Time without a response before the connection is considered stale.
+const STALE_CONNECTION_MILLIS: u64 = 5_000;
+
+/// Time without a response before the connection is considered lost.
+const LOST_CONNECTION_MILLIS: u64 = 15_000;
 
 /// A transport for sending packet data.
 ///
@@ -41,12 +67,17 @@ pub const MAX_DATA_SIZE: usize = 896;
 pub enum Transport {
     #[cfg(target_arch = "wasm32")]
     WebSerialTransport,
+    #[cfg(not(target_arch = "wasm32"))]
+    TcpTransport,
     MockTransport,
 }
 
 /// Information about a discovered device.
-#[allow(dead_code)]
+#[derive(Debug, Clone)]
 pub struct DiscoveredDevice {
+    /// Identifies the device to the transport when starting, such as a port
+    /// path.
+    pub id: String,
     /// The primary name of the device.
     pub name: String,
     /// An optional detail string about the device.
@@ -59,44 +90,129 @@ pub struct DiscoveredDevice {
 pub enum TransportEvent {
     /// Sent when the transport is connecting, disconnected, etc.
     TransportStatus(TransportStatus),
+    /// Sent as connecting moves through each step, so a failed connection
+    /// shows where it stopped.
+    ConnectionStep(ConnectionStep),
     /// Info about the status of the device, automatically fetched every few
     /// seconds when the transport is not sending large data.
     DeviceStatus((PrinterState, PrinterSubState, String)),
     /// Info about a job, sent after calling [`TransportManager::poll_job`]
     /// until the job reaches a terminal state.
     JobStatus(JobStatusInfo),
+    /// Sent by [`TransportManager::poll_job`] when a job starts or stops
+    /// needing attention.
+    JobAttention(Option<JobAttention>),
     /// Sent for all received packets.
     Packet(AvocadoPacket),
+    /// Sent for requests once they've been written by
+    /// [`TransportManager::wait_for_response`].
+    PacketSent(AvocadoPacket),
+    /// Sent by transports for each received packet, with if its checksum
+    /// matched the one we calculate.
+    Checksum(bool),
+    /// Sent by transports with the number of bytes written to the device,
+    /// once the write has been accepted.
+    BytesWritten(usize),
     /// An error from the transport.
     Error(anyhow::Error),
 }
 
+/// Why a job may need someone to look at it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum JobAttention {
+    /// The printer is waiting on someone, such as to insert media or scan.
+    ActionRequired(PrinterSubState),
+    /// The job hasn't changed for this many seconds.
+    NoProgress(u64),
+}
+
+/// How recently the device has shown it's still reachable.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConnectionHealth {
+    Healthy,
+    /// Responses have stopped for a few polls.
+    Stale,
+    /// Responses have stopped long enough that the link is probably broken.
+    Lost,
+}
+
 /// The transport's current device connection status.
 #[allow(dead_code)]
 #[derive(Debug, Clone, Copy, PartialEq, Hash)]
 pub enum TransportStatus {
     Connecting,
     Connected,
+    /// Waiting for a data transfer to stop before disconnecting.
+    FinishingTransfer,
     Disconnecting,
     Disconnected,
 }
 
+/// A step of connecting to a device.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ConnectionStep {
+    /// Waiting for the user to pick a port.
+    RequestingPort,
+    /// Opening the connection, described by what's being opened.
+    Opening(String),
+    /// Waiting for the device to answer a status request.
+    Handshaking,
+    /// The device responded and is ready for jobs.
+    Ready,
+}
+
+impl std::fmt::Display for ConnectionStep {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RequestingPort => write!(f, "requesting port"),
+            Self::Opening(target) => write!(f, "opening {target}"),
+            Self::Handshaking => write!(f, "waiting for the device to respond"),
+            Self::Ready => write!(f, "ready"),
+        }
+    }
+}
+
+/// What to do with a data transfer in progress when disconnecting.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DisconnectMode {
+    /// Disconnect right away, which may leave the device with a partially
+    /// received job.
+    Immediate,
+    /// Wait for the transfer to finish.
+    FinishTransfer,
+    /// Stop the transfer and cancel its job on the device.
+    CancelTransfer,
+}
+
 #[cfg_attr(target_arch = "wasm32", async_trait(?Send))]
 #[cfg_attr(not(target_arch = "wasm32"), async_trait)]
 #[enum_dispatch]
 pub trait TransportControl {
     fn name(&self) -> Cow<'static, str>;
-    #[allow(dead_code)]
     fn supports_discovery(&self) -> bool;
 
-    #[allow(dead_code)]
+    /// If the transport can be used in the current environment.
+    ///
+    /// This should be cheap, as it's checked while drawing.
+    fn is_available(&self) -> bool {
+        true
+    }
+
+    /// The address to connect to, for transports that need one.
+    fn address_mut(&mut self) -> Option<&mut String> {
+        None
+    }
+
     async fn discover_devices(&mut self) -> anyhow::Result<Vec<DiscoveredDevice>> {
         bail!("discovery not supported for transport");
     }
 
+    /// Start the transport, connecting to the target device if one was
+    /// picked from [`Self::discover_devices`].
     async fn start(
         &mut self,
         mut event_tx: mpsc::UnboundedSender<TransportEvent>,
+        target: Option<DiscoveredDevice>,
     ) -> Result<(), anyhow::Error>;
 
     async fn disconnect(&mut self) -> anyhow::Result<()>;
@@ -111,8 +227,24 @@ pub trait TransportControl {
 pub struct TransportManager {
     transport: Rc<Mutex<Transport>>,
     event_tx: mpsc::UnboundedSender<TransportEvent>,
+    terminal_id: u32,
+    data_size: Rc<AtomicUsize>,
+    quirks: Rc<std::sync::Mutex<DeviceQuirks>>,
+    handshake_timeout_millis: Rc<AtomicU64>,
+    /// The most recent printer sub-state from device status polling.
+    printer_sub_state: Rc<std::sync::Mutex<Option<PrinterSubState>>>,
+
+    /// When the device last responded or accepted data.
+    last_activity_millis: Rc<AtomicU64>,
+    /// The most recent request that failed to get a response, kept so it
+    /// can be sent again with [`Self::resend_last`].
+    last_failed: Rc<std::sync::Mutex<Option<AvocadoPacket>>>,
 
     sending: Rc<AtomicBool>,
+    /// Set to stop the current data transfer before its next chunk.
+    cancel_sending: Rc<AtomicBool>,
+    /// Set once a disconnect is requested, so jobs in progress can stop.
+    disconnecting: Rc<AtomicBool>,
     pending: Rc<Mutex<HashMap<u32, oneshot::Sender<AvocadoPacket>>>>,
 }
 
@@ -121,7 +253,11 @@ impl TransportManager {
     ///
     /// Handles starting the transport, polling device status, and attaching
     /// incoming packets to waiting requests.
-    pub fn new<F>(transport: Rc<Mutex<Transport>>, cb: F) -> Rc<Self>
+    pub fn new<F>(
+        transport: Rc<Mutex<Transport>>,
+        target: Option<DiscoveredDevice>,
+        cb: F,
+    ) -> Rc<Self>
     where
         F: Fn(TransportEvent) + Send + Sync + 'static,
     {
@@ -131,11 +267,28 @@ impl TransportManager {
         let sending = Rc::new(AtomicBool::new(false));
         let pending: Rc<Mutex<HashMap<u32, oneshot::Sender<AvocadoPacket>>>> = Default::default();
 
+        let terminal_id = uuid::Uuid::new_v4().as_u128() as u32;
+        debug!(terminal_id, "generated session terminal id");
+
+        let printer_sub_state: Rc<std::sync::Mutex<Option<PrinterSubState>>> = Default::default();
+        let last_activity_millis = Rc::new(AtomicU64::new(current_timestamp_millis()));
+
         let manager = Rc::new(Self {
             transport: transport.clone(),
             event_tx: event_tx.clone(),
+            terminal_id,
+            data_size: Rc::new(AtomicUsize::new(DEFAULT_DATA_SIZE)),
+            quirks: Default::default(),
+            handshake_timeout_millis: Rc::new(AtomicU64::new(
+                DEFAULT_HANDSHAKE_TIMEOUT.as_millis() as u64,
+            )),
+            printer_sub_state: printer_sub_state.clone(),
+            last_activity_millis: last_activity_millis.clone(),
+            last_failed: Default::default(),
 
             sending: sending.clone(),
+            cancel_sending: Default::default(),
+            disconnecting: Default::default(),
             pending: pending.clone(),
         });
 
@@ -149,7 +302,27 @@ impl TransportManager {
                     return;
                 }
 
-                info!("connection marked as ready, starting info polling");
+                info!("connection marked as ready, waiting for device");
+                let _ = event_tx
+                    .send(TransportEvent::ConnectionStep(ConnectionStep::Handshaking))
+                    .await;
+
+                // Without a first response, the device is probably not what
+                // we think it is, and polling would wait on it forever.
+                if let Err(err) = manager.handshake().await {
+                    error!("device did not respond: {err}");
+                    let _ = event_tx.send(TransportEvent::Error(err)).await;
+
+                    if let Err(err) = manager.disconnect(DisconnectMode::Immediate).await {
+                        error!("could not disconnect after handshake failed: {err}");
+                    }
+                    return;
+                }
+
+                info!("device responded, starting info polling");
+                let _ = event_tx
+                    .send(TransportEvent::ConnectionStep(ConnectionStep::Ready))
+                    .await;
 
                 let mut stream = interval(Duration::from_secs(1));
                 while stream.next().await.is_some() {
@@ -163,29 +336,7 @@ impl TransportManager {
                         continue;
                     }
 
-                    let id = manager.next_message_id();
-                    let packet = AvocadoPacket {
-                        version: 100,
-                        content_type: ContentType::Message,
-                        interaction_type: InteractionType::Request,
-                        encoding_type: EncodingType::Json,
-                        encryption_mode: EncryptionMode::None,
-                        terminal_id: id,
-                        msg_number: id,
-                        msg_package_total: 1,
-                        msg_package_num: 1,
-                        is_subpackage: false,
-                        data: serde_json::to_vec(&serde_json::json!({
-                            "id" : id,
-                            "method" : "get-prop",
-                            "params" : [
-                                "printer-state",
-                                "printer-sub-state",
-                                "printer-state-alerts",
-                            ]
-                        }))
-                        .unwrap(),
-                    };
+                    let packet = manager.status_request();
                     trace!(?packet, "prepared get-prop request");
 
                     let packet = match manager.wait_for_response(packet).await {
@@ -197,20 +348,18 @@ impl TransportManager {
                     };
                     trace!(?packet, "got get-prop response");
 
-                    if let Some(result) =
-                        packet.as_json::<AvocadoResult<(PrinterState, PrinterSubState, String)>>()
-                    {
-                        debug!("got status: {:?}", result.result);
+                    match packet.try_result::<(PrinterState, PrinterSubState, String)>() {
+                        Ok(status) => {
+                            debug!("got status: {status:?}");
 
-                        if let Err(err) = event_tx
-                            .send(TransportEvent::DeviceStatus(result.result))
-                            .await
-                        {
-                            error!("could not send device status: {err:?}");
-                            break;
+                            if let Err(err) =
+                                event_tx.send(TransportEvent::DeviceStatus(status)).await
+                            {
+                                error!("could not send device status: {err:?}");
+                                break;
+                            }
                         }
-                    } else {
-                        error!("could not decode printer status: {packet:?}");
+                        Err(err) => error!("could not decode printer status: {err}, {packet:?}"),
                     }
                 }
 
@@ -221,14 +370,23 @@ impl TransportManager {
         spawn(async move {
             let mut ready_tx = Some(ready_tx);
 
+            let mark_activity = || {
+                last_activity_millis.store(
+                    current_timestamp_millis(),
+                    std::sync::atomic::Ordering::SeqCst,
+                );
+            };
+
             while let Some(event) = event_rx.next().await {
                 match &event {
                     TransportEvent::Packet(packet) => {
                         if let Some(data) = packet.as_json::<AvocadoId>() {
-                            if let Some(pending) = pending.lock().await.remove(&data.id)
-                                && pending.send(packet.clone()).is_err()
-                            {
-                                error!("could not send packet to pending");
+                            if let Some(pending) = pending.lock().await.remove(&data.id) {
+                                mark_activity();
+
+                                if pending.send(packet.clone()).is_err() {
+                                    error!("could not send packet to pending");
+                                }
                             }
                         } else if packet.content_type == ContentType::Message
                             && packet.encoding_type == EncodingType::Json
@@ -236,11 +394,21 @@ impl TransportManager {
                             warn!("got json message without id");
                         }
                     }
+                    // Status polling is paused while sending, so accepted
+                    // writes show the link is still alive.
+                    TransportEvent::BytesWritten(_) => mark_activity(),
                     TransportEvent::TransportStatus(TransportStatus::Connected) => {
+                        mark_activity();
+
                         if let Some(ready_tx) = ready_tx.take() {
                             let _ = ready_tx.send(());
                         }
                     }
+                    TransportEvent::DeviceStatus((_, sub_state, _)) => {
+                        if let Ok(mut printer_sub_state) = printer_sub_state.lock() {
+                            *printer_sub_state = Some(*sub_state);
+                        }
+                    }
                     _ => trace!("got other event: {event:?}"),
                 }
 
@@ -250,21 +418,127 @@ impl TransportManager {
 
         spawn(async move {
             let mut transport = transport.lock().await;
-            if let Err(err) = transport.start(event_tx.clone()).await
-                && let Err(err) = event_tx.send(TransportEvent::Error(err)).await
-            {
-                error!("could not send transport start error: {err}");
+            if let Err(err) = transport.start(event_tx.clone(), target).await {
+                // Not every transport reports it's disconnected when starting
+                // fails, which is needed to be able to retry.
+                let _ = event_tx
+                    .send(TransportEvent::TransportStatus(
+                        TransportStatus::Disconnected,
+                    ))
+                    .await;
+
+                if let Err(err) = event_tx.send(TransportEvent::Error(err)).await {
+                    error!("could not send transport start error: {err}");
+                }
             }
         });
 
         manager
     }
 
-    /// Disconnect transport.
-    pub async fn disconnect(&self) -> anyhow::Result<()> {
+    /// Build a request for the printer's state.
+    fn status_request(&self) -> AvocadoPacket {
+        let id = self.next_message_id();
+
+        AvocadoPacket {
+            version: 100,
+            reserved: 0,
+            content_type: ContentType::Message,
+            interaction_type: InteractionType::Request,
+            encoding_type: EncodingType::Json,
+            encryption_mode: EncryptionMode::None,
+            terminal_id: self.terminal_id(),
+            msg_number: id,
+            msg_package_total: 1,
+            msg_package_num: 1,
+            is_subpackage: false,
+            data: serde_json::to_vec(&serde_json::json!({
+                "id" : id,
+                "method" : "get-prop",
+                "params" : [
+                    "printer-state",
+                    "printer-sub-state",
+                    "printer-state-alerts",
+                ]
+            }))
+            .unwrap(),
+        }
+    }
+
+    /// Wait for the device to answer a status request, sending it again a
+    /// few times within the handshake timeout.
+    async fn handshake(&self) -> anyhow::Result<()> {
+        let handshake_timeout = self.handshake_timeout();
+
+        for attempt in 1..=HANDSHAKE_ATTEMPTS {
+            let packet = self.status_request();
+            let id = packet.msg_number;
+
+            match timeout(
+                handshake_timeout / HANDSHAKE_ATTEMPTS,
+                self.wait_for_response(packet),
+            )
+            .await
+            {
+                Some(res) => return res.map(|_| ()),
+                None => {
+                    warn!(attempt, "no response to status request");
+                    self.pending.lock().await.remove(&id);
+                }
+            }
+        }
+
+        bail!(
+            "device did not respond within {} seconds, check that the right device is \
+            selected and the baud rate is correct",
+            handshake_timeout.as_secs()
+        )
+    }
+
+    /// Get how long to wait for the device to first respond after connecting.
+    pub fn handshake_timeout(&self) -> Duration {
+        Duration::from_millis(
+            self.handshake_timeout_millis
+                .load(std::sync::atomic::Ordering::SeqCst),
+        )
+    }
+
+    /// Set how long to wait for the device to first respond after connecting,
+    /// which only has an effect before the connection is ready.
+    pub fn set_handshake_timeout(&self, handshake_timeout: Duration) {
+        self.handshake_timeout_millis.store(
+            handshake_timeout.as_millis() as u64,
+            std::sync::atomic::Ordering::SeqCst,
+        );
+    }
+
+    /// Disconnect transport, handling any data transfer in progress based on
+    /// the mode.
+    pub async fn disconnect(&self, mode: DisconnectMode) -> anyhow::Result<()> {
+        let mut event_tx = self.event_tx.clone();
+        self.disconnecting
+            .store(true, std::sync::atomic::Ordering::SeqCst);
+
+        if mode != DisconnectMode::Immediate && self.is_sending() {
+            info!(?mode, "stopping data transfer before disconnecting");
+            event_tx
+                .send(TransportEvent::TransportStatus(
+                    TransportStatus::FinishingTransfer,
+                ))
+                .await?;
+
+            if mode == DisconnectMode::CancelTransfer {
+                self.cancel_sending();
+            }
+
+            // Cancelled transfers also cancel their job before they stop
+            // sending.
+            let mut stream = interval(Duration::from_millis(100));
+            while self.is_sending() && stream.next().await.is_some() {}
+        }
+
         info!("disconnecting transport");
-        self.event_tx
-            .clone()
+        event_tx
             .send(TransportEvent::TransportStatus(
                 TransportStatus::Disconnecting,
             ))
@@ -272,6 +546,91 @@ impl TransportManager {
         self.transport.lock().await.disconnect().await
     }
 
+    /// If data is currently being sent, which pauses device status polling.
+    pub fn is_sending(&self) -> bool {
+        self.sending.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// If a disconnect has been requested, after which jobs shouldn't wait
+    /// on the device any longer.
+    pub fn is_disconnecting(&self) -> bool {
+        self.disconnecting.load(std::sync::atomic::Ordering::SeqCst)
+    }
+
+    /// How long it's been since the device last responded, as a health
+    /// state. Status polling provides a response every second when idle.
+    pub fn health(&self) -> ConnectionHealth {
+        let last_activity = self
+            .last_activity_millis
+            .load(std::sync::atomic::Ordering::SeqCst);
+
+        match current_timestamp_millis().saturating_sub(last_activity) {
+            elapsed if elapsed >= LOST_CONNECTION_MILLIS => ConnectionHealth::Lost,
+            elapsed if elapsed >= STALE_CONNECTION_MILLIS => ConnectionHealth::Stale,
+            _ => ConnectionHealth::Healthy,
+        }
+    }
+
+    /// Stop the data transfer in progress, if any.
+    ///
+    /// [`Self::send_data`] will cancel the job on the device and return
+    /// [`TransferCancelled`] before sending its next chunk.
+    pub fn cancel_sending(&self) {
+        if self.is_sending() {
+            info!("cancelling data transfer");
+            self.cancel_sending
+                .store(true, std::sync::atomic::Ordering::SeqCst);
+        }
+    }
+
+    /// Get the terminal ID used for all packets sent during this session.
+    pub fn terminal_id(&self) -> u32 {
+        self.terminal_id
+    }
+
+    /// Get the size of data within each packet sent by [`Self::send_data`],
+    /// limited by the device's quirks.
+    pub fn data_size(&self) -> usize {
+        self.data_size
+            .load(std::sync::atomic::Ordering::SeqCst)
+            .min(self.quirks().max_data_size)
+    }
+
+    /// Get the quirks of the device being sent to.
+    pub fn quirks(&self) -> DeviceQuirks {
+        self.quirks
+            .lock()
+            .map(|quirks| quirks.clone())
+            .unwrap_or_default()
+    }
+
+    /// Set the quirks of the device being sent to, which change how packets
+    /// are built.
+    pub fn set_quirks(&self, quirks: DeviceQuirks) {
+        if let Ok(mut current) = self.quirks.lock() {
+            *current = quirks;
+        }
+    }
+
+    /// Set the size of data within each packet sent by [`Self::send_data`],
+    /// including the job ID.
+    ///
+    /// Will return an error if the size is larger than [`MAX_DATA_SIZE`] or
+    /// leaves no room after the job ID.
+    pub fn set_data_size(&self, data_size: usize) -> anyhow::Result<()> {
+        if data_size > MAX_DATA_SIZE {
+            bail!("data size {data_size} is larger than maximum of {MAX_DATA_SIZE}");
+        }
+
+        if data_size <= JOB_ID_SIZE {
+            bail!("data size {data_size} does not leave room for data after job id");
+        }
+
+        self.data_size
+            .store(data_size, std::sync::atomic::Ordering::SeqCst);
+        Ok(())
+    }
+
     /// Get the next message ID.
     pub fn next_message_id(&self) -> u32 {
         let id = MESSAGE_ID.fetch_add(1, std::sync::atomic::Ordering::SeqCst);
@@ -283,30 +642,229 @@ impl TransportManager {
     ///
     /// This does not have a timeout.
     #[instrument(skip_all, fields(msg_number = packet.msg_number))]
-    pub async fn wait_for_response(&self, packet: AvocadoPacket) -> anyhow::Result<AvocadoPacket> {
+    pub async fn wait_for_response(
+        &self,
+        mut packet: AvocadoPacket,
+    ) -> anyhow::Result<AvocadoPacket> {
         let (tx, rx) = oneshot::channel();
+        packet.reserved = self.quirks().reserved_byte;
 
         debug!("sending packet");
-        self.pending.lock().await.insert(packet.msg_number, tx);
-        self.transport
-            .lock()
-            .await
-            .send_packet(packet)
-            .await?
-            .await?;
+        let msg_number = packet.msg_number;
+        self.pending.lock().await.insert(msg_number, tx);
+
+        let sent = async {
+            self.transport
+                .lock()
+                .await
+                .send_packet(packet.clone())
+                .await?
+                .await?;
+            anyhow::Ok(())
+        }
+        .await;
+
+        if let Err(err) = sent {
+            self.pending.lock().await.remove(&msg_number);
+            self.set_last_failed(packet);
+            return Err(RequestFailed { msg_number, err }.into());
+        }
         trace!("packet marked as sent");
 
-        rx.await.map_err(Into::into)
+        if let Err(err) = self
+            .event_tx
+            .unbounded_send(TransportEvent::PacketSent(packet.clone()))
+        {
+            warn!("could not send sent packet event: {err}");
+        }
+
+        match rx.await {
+            Ok(response) => {
+                // The device is responding again, so an older failed request
+                // is out of date.
+                if let Ok(mut last_failed) = self.last_failed.lock() {
+                    *last_failed = None;
+                }
+
+                Ok(response)
+            }
+            Err(err) => {
+                self.set_last_failed(packet);
+                Err(RequestFailed {
+                    msg_number,
+                    err: err.into(),
+                }
+                .into())
+            }
+        }
+    }
+
+    fn set_last_failed(&self, packet: AvocadoPacket) {
+        // Sending a job request again would create another job on the device
+        // that never gets its data.
+        let creates_job = packet
+            .as_json::<AvocadoMethod>()
+            .and_then(|request| KnownMethod::from_name(&request.method))
+            .is_some_and(|method| method.creates_job());
+
+        if let Ok(mut last_failed) = self.last_failed.lock() {
+            *last_failed = (!creates_job).then_some(packet);
+        }
+    }
+
+    /// If the request with the given message number failed and can be sent
+    /// again with [`Self::resend_last`].
+    pub fn has_failed_request(&self, msg_number: u32) -> bool {
+        self.last_failed.lock().is_ok_and(|last_failed| {
+            last_failed
+                .as_ref()
+                .is_some_and(|packet| packet.msg_number == msg_number)
+        })
+    }
+
+    /// Send the most recent request that failed to get a response again,
+    /// with a new message ID.
+    pub async fn resend_last(&self) -> anyhow::Result<AvocadoPacket> {
+        let Some(mut packet) = self
+            .last_failed
+            .lock()
+            .ok()
+            .and_then(|mut last_failed| last_failed.take())
+        else {
+            bail!("no failed request to send again");
+        };
+
+        // Responses are matched by the ID in the request body, which has to
+        // match the message number.
+        let id = self.next_message_id();
+        let mut request: serde_json::Value = packet
+            .as_json()
+            .ok_or_else(|| anyhow::anyhow!("failed request was not json"))?;
+        request["id"] = id.into();
+
+        packet.msg_number = id;
+        packet.data = serde_json::to_vec(&request)?;
+        info!(id, "resending failed request");
+
+        self.wait_for_response(packet).await
+    }
+
+    /// Get the media the device reports as loaded.
+    pub async fn get_media(&self) -> anyhow::Result<LoadedMedia> {
+        let id = self.next_message_id();
+        let packet = AvocadoPacket {
+            version: 100,
+            reserved: 0,
+            content_type: ContentType::Message,
+            interaction_type: InteractionType::Request,
+            encoding_type: EncodingType::Json,
+            encryption_mode: EncryptionMode::None,
+            terminal_id: self.terminal_id,
+            msg_number: id,
+            msg_package_total: 1,
+            msg_package_num: 1,
+            is_subpackage: false,
+            data: serde_json::to_vec(&serde_json::json!({
+                "id": id,
+                "method": "get-prop",
+                "params": ["media-size", "media-type"],
+            }))?,
+        };
+        trace!(?packet, "prepared media get-prop request");
+
+        let packet = self.wait_for_response(packet).await?;
+        let media = packet.try_result::<LoadedMedia>()?;
+        debug!(?media, "got loaded media");
+
+        Ok(media)
+    }
+
+    /// Set properties on the device, given as an object of property names
+    /// to their new values.
+    pub async fn set_prop(&self, props: serde_json::Value) -> anyhow::Result<()> {
+        let id = self.next_message_id();
+        let packet = AvocadoPacket {
+            version: 100,
+            reserved: 0,
+            content_type: ContentType::Message,
+            interaction_type: InteractionType::Request,
+            encoding_type: EncodingType::Json,
+            encryption_mode: EncryptionMode::None,
+            terminal_id: self.terminal_id,
+            msg_number: id,
+            msg_package_total: 1,
+            msg_package_num: 1,
+            is_subpackage: false,
+            data: serde_json::to_vec(&serde_json::json!({
+                "id": id,
+                "method": "set-prop",
+                "params": props,
+            }))?,
+        };
+        trace!(?packet, "prepared set-prop request");
+
+        let packet = self.wait_for_response(packet).await?;
+        debug!(?packet, "got set-prop response");
+        packet.try_result::<serde_json::Value>()?;
+
+        Ok(())
+    }
+
+    /// Tell the device which media is loaded, so jobs for it aren't aborted.
+    pub async fn set_media(&self, media_size: u16, media_type: u16) -> anyhow::Result<()> {
+        self.set_prop(serde_json::json!({
+            "media-size": media_size,
+            "media-type": media_type,
+        }))
+        .await
+    }
+
+    /// Ask the device to cancel a job.
+    pub async fn cancel_job(&self, job_id: u32) -> anyhow::Result<()> {
+        let id = self.next_message_id();
+        let packet = AvocadoPacket {
+            version: 100,
+            reserved: 0,
+            content_type: ContentType::Message,
+            interaction_type: InteractionType::Request,
+            encoding_type: EncodingType::Json,
+            encryption_mode: EncryptionMode::None,
+            terminal_id: self.terminal_id,
+            msg_number: id,
+            msg_package_total: 1,
+            msg_package_num: 1,
+            is_subpackage: false,
+            data: serde_json::to_vec(&serde_json::json!({
+                "id": id,
+                "method": "cancel-job",
+                "params": { "job-id": job_id },
+            }))?,
+        };
+        trace!(?packet, "prepared cancel-job request");
+
+        let packet = self.wait_for_response(packet).await?;
+        debug!(?packet, "got cancel-job response");
+        packet.try_result::<serde_json::Value>()?;
+
+        Ok(())
     }
 
     /// Poll a job for status updates.
     ///
     /// Updates are sent through the manager's event stream. This method returns
-    /// after the job has reached a terminal state.
+    /// after the job has reached a terminal state, or [`MediaMismatch`] if the
+    /// device aborted the job because the wrong media was loaded.
+    ///
+    /// If the job was sent with a user account, it's checked against the one
+    /// the device reports for the job.
     #[instrument(skip(self))]
-    pub async fn poll_job(&self, job_id: u32) -> anyhow::Result<()> {
+    pub async fn poll_job(&self, job_id: u32, user_account: Option<&str>) -> anyhow::Result<()> {
         let mut event_tx = self.event_tx.clone();
 
+        let mut attention = None;
+        let mut last_progress = None;
+        let mut last_change_millis = current_timestamp_millis();
+
         let mut stream = interval(Duration::from_secs(1));
         while stream.next().await.is_some() {
             if event_tx.is_closed() {
@@ -317,11 +875,12 @@ impl TransportManager {
             let id = self.next_message_id();
             let packet = AvocadoPacket {
                 version: 100,
+                reserved: 0,
                 content_type: ContentType::Message,
                 interaction_type: InteractionType::Request,
                 encoding_type: EncodingType::Json,
                 encryption_mode: EncryptionMode::None,
-                terminal_id: id,
+                terminal_id: self.terminal_id,
                 msg_number: id,
                 msg_package_total: 1,
                 msg_package_num: 1,
@@ -344,33 +903,118 @@ impl TransportManager {
             };
             trace!(?packet, "got get-job-info response");
 
-            if let Some(mut result) = packet.as_json::<AvocadoResult<Vec<JobStatusInfo>>>() {
-                debug!("got get-job-info info: {:?}", result.result);
+            let mut result = match packet.try_result::<Vec<JobStatusInfo>>() {
+                Ok(result) => result,
+                // The device rejecting the request won't change by polling
+                // again, so let the caller know why.
+                Err(ProtocolError::Device(err)) => return Err(err.into()),
+                Err(err) => {
+                    error!(
+                        "could not decode job status: {err}, {packet:?}, {:?}",
+                        packet.as_json::<serde_json::Value>()
+                    );
+                    break;
+                }
+            };
+            debug!("got get-job-info info: {result:?}");
 
-                let Some(info) = result.result.pop() else {
-                    warn!("result was missing job info");
-                    continue;
-                };
+            let Some(info) = result.pop() else {
+                warn!("result was missing job info");
+                continue;
+            };
 
-                let is_complete = matches!(
-                    info.job_state,
-                    JobState::Aborted | JobState::Cancelled | JobState::Completed
+            if let Some(user_account) = user_account
+                && !info.user_account.is_empty()
+                && info.user_account != user_account
+            {
+                warn!(
+                    info.user_account,
+                    "job info is for another user account, it may be another job"
                 );
+            }
 
-                if let Err(err) = event_tx.send(TransportEvent::JobStatus(info)).await {
-                    error!("could not send job status: {err:?}");
-                    break;
+            let is_complete = matches!(
+                info.job_state,
+                JobState::Aborted | JobState::Cancelled | JobState::Completed
+            );
+
+            // Requests can take longer than the polling interval, so measure
+            // how long the job has been stuck by time instead of by polls.
+            let progress = (
+                info.job_state,
+                info.job_sub_state,
+                info.transfer_size,
+                info.printing_page_number,
+            );
+            let now_millis = current_timestamp_millis();
+            if last_progress != Some(progress) {
+                last_progress = Some(progress);
+                last_change_millis = now_millis;
+            }
+            let unchanged_secs = now_millis.saturating_sub(last_change_millis) / 1000;
+
+            let printer_sub_state = self
+                .printer_sub_state
+                .lock()
+                .ok()
+                .and_then(|sub_state| *sub_state);
+
+            let new_attention = if is_complete {
+                None
+            } else if let Some(sub_state) =
+                printer_sub_state.filter(PrinterSubState::requires_action)
+            {
+                Some(JobAttention::ActionRequired(sub_state))
+            } else if unchanged_secs >= JOB_STALL_SECS {
+                Some(JobAttention::NoProgress(unchanged_secs))
+            } else {
+                None
+            };
+
+            if new_attention != attention {
+                if attention.is_none()
+                    && let Some(new_attention) = &new_attention
+                {
+                    warn!(job_id, ?new_attention, "job needs attention");
                 }
 
-                if is_complete {
-                    info!("job reached terminal state, ending status polling");
+                attention = new_attention;
+                if let Err(err) = event_tx.send(TransportEvent::JobAttention(attention)).await {
+                    error!("could not send job attention: {err:?}");
                     break;
                 }
-            } else {
-                error!(
-                    "could not decode job status: {packet:?}, {:?}",
-                    packet.as_json::<serde_json::Value>()
-                );
+            }
+
+            let job_state = info.job_state;
+            let expected = LoadedMedia {
+                media_size: info.media_size as u16,
+                media_type: info.media_type as u16,
+            };
+            let transfer_finished = info.file_size > 0 && info.transfer_size >= info.file_size;
+
+            if let Err(err) = event_tx.send(TransportEvent::JobStatus(info)).await {
+                error!("could not send job status: {err:?}");
+                break;
+            }
+
+            if job_state == JobState::Aborted {
+                let media_matches = self.check_aborted_media(expected).await?;
+
+                // The device doesn't say when data didn't match the hash sent
+                // with the job, so only guess that it was corrupted when all
+                // of the data was received for the right media and nothing
+                // else, like a paper jam, needs attention.
+                let needs_action = printer_sub_state
+                    .as_ref()
+                    .is_some_and(PrinterSubState::requires_action);
+                if media_matches && transfer_finished && !needs_action {
+                    warn!(job_id, "job was aborted after receiving all data");
+                    return Err(TransferCorrupted { job_id }.into());
+                }
+            }
+
+            if is_complete {
+                info!("job reached terminal state, ending status polling");
                 break;
             }
         }
@@ -378,9 +1022,30 @@ impl TransportManager {
         Ok(())
     }
 
+    /// Check if an aborted job was for different media than is loaded,
+    /// returning if the loaded media was confirmed to match.
+    ///
+    /// The device doesn't report why a job was aborted, but the loaded media
+    /// not matching is the most common reason and is easy to check.
+    async fn check_aborted_media(&self, expected: LoadedMedia) -> anyhow::Result<bool> {
+        match self.get_media().await {
+            Ok(loaded) if loaded != expected => {
+                warn!(?loaded, ?expected, "job was aborted with mismatched media");
+                Err(MediaMismatch { loaded, expected }.into())
+            }
+            Ok(_) => Ok(true),
+            Err(err) => {
+                warn!("could not get media for aborted job: {err}");
+                Ok(false)
+            }
+        }
+    }
+
     /// Send binary data to the device for a given job.
     ///
-    /// Will return an error if data is already being sent.
+    /// Will return an error if data is already being sent, or
+    /// [`TransferCancelled`] if [`Self::cancel_sending`] was called, after
+    /// asking the device to cancel the job it's left waiting on.
     #[instrument(skip(self, data, f))]
     pub async fn send_data<F>(&self, job_id: u32, data: &[u8], f: F) -> anyhow::Result<()>
     where
@@ -390,22 +1055,48 @@ impl TransportManager {
             bail!("cannot start sending data while other send is in progress");
         };
 
-        let count = usize::div_ceil(data.len(), MAX_DATA_SIZE - 4);
-        debug!(chunks = count, "sending data with {} bytes", data.len());
+        self.cancel_sending
+            .store(false, std::sync::atomic::Ordering::SeqCst);
+
+        let data_size = self.data_size();
+        let chunk_size = data_size - JOB_ID_SIZE;
+        let reserved = self.quirks().reserved_byte;
 
-        for (index, chunk) in data.chunks(MAX_DATA_SIZE - 4).enumerate() {
-            let mut buf: Vec<u8> = Vec::with_capacity(MAX_DATA_SIZE);
+        let count = usize::div_ceil(data.len(), chunk_size);
+        debug!(
+            chunks = count,
+            data_size,
+            "sending data with {} bytes",
+            data.len()
+        );
+
+        for (index, chunk) in data.chunks(chunk_size).enumerate() {
+            if self
+                .cancel_sending
+                .swap(false, std::sync::atomic::Ordering::SeqCst)
+            {
+                warn!(index, chunks = count, "data transfer was cancelled");
+
+                if let Err(err) = self.cancel_job(job_id).await {
+                    warn!(job_id, "could not cancel job: {err}");
+                }
+
+                return Err(TransferCancelled.into());
+            }
+
+            let mut buf: Vec<u8> = Vec::with_capacity(data_size);
             buf.extend(&job_id.to_le_bytes());
             buf.extend_from_slice(chunk);
 
             let id = self.next_message_id();
             let packet = AvocadoPacket {
                 version: 100,
+                reserved,
                 content_type: ContentType::Data,
                 interaction_type: InteractionType::Request,
                 encoding_type: EncodingType::Hexadecimal,
                 encryption_mode: EncryptionMode::None,
-                terminal_id: id,
+                terminal_id: self.terminal_id,
                 msg_number: id,
                 msg_package_total: u16::try_from(count).unwrap(),
                 msg_package_num: u16::try_from(index + 1).unwrap(),
@@ -430,6 +1121,43 @@ impl TransportManager {
     }
 }
 
+/// Returned by [`TransportManager::wait_for_response`] when a request
+/// couldn't be sent or never got a response.
+#[derive(Debug, Error)]
+#[error("{err:#}")]
+pub struct RequestFailed {
+    pub msg_number: u32,
+    err: anyhow::Error,
+}
+
+/// Returned by [`TransportManager::send_data`] when the transfer was
+/// cancelled before it finished.
+#[derive(Debug, Error)]
+#[error("data transfer was cancelled")]
+pub struct TransferCancelled;
+
+/// Returned by [`TransportManager::poll_job`] when the device aborted a job
+/// after receiving all of its data with the right media loaded and nothing
+/// needing attention, so its data probably didn't match its hash.
+///
+/// The device doesn't give a reason for aborting, so this is only a guess.
+#[derive(Debug, Clone, Error)]
+#[error(
+    "job {job_id} was aborted after all of its data was received, the transfer may have been corrupted"
+)]
+pub struct TransferCorrupted {
+    pub job_id: u32,
+}
+
+/// Returned by [`TransportManager::poll_job`] when the device aborted a job
+/// and the loaded media doesn't match what the job was for.
+#[derive(Debug, Clone, Error)]
+#[error("loaded media ({}) does not match the job ({})", loaded.describe(), expected.describe())]
+pub struct MediaMismatch {
+    pub loaded: LoadedMedia,
+    pub expected: LoadedMedia,
+}
+
 /// Helper to set and remove the sending flag in a [`TransportManager`].
 ///
 /// Automatically marks it as sending upon creation and unmarks it when dropped.
@@ -457,3 +1185,126 @@ impl Drop for SendingDropGuard {
             .store(false, std::sync::atomic::Ordering::SeqCst);
     }
 }
+
+/// Reassembles packets from bytes as they arrive from a transport.
+///
+/// Transports may deliver partial packets or several packets at once, so
+/// bytes are buffered until complete packets can be read.
+#[derive(Default)]
+pub struct PacketFramer {
+    buf: Vec<u8>,
+}
+
+impl PacketFramer {
+    /// Add newly read bytes, returning any packets that are now complete and
+    /// if their checksums matched.
+    ///
+    /// Returns an error if the buffered data is not a valid packet.
+    pub fn push(&mut self, data: &[u8]) -> Result<Vec<(AvocadoPacket, bool)>, ProtocolError> {
+        self.buf.extend_from_slice(data);
+        trace!(
+            "read {} bytes, total buffer is {} bytes",
+            data.len(),
+            self.buf.len()
+        );
+
+        let mut packets = Vec::new();
+
+        loop {
+            let mut cursor = std::io::Cursor::new(&self.buf);
+            let packet = match AvocadoPacket::read_one(&mut cursor) {
+                Ok(packet) => packet,
+                Err(ProtocolError::Reader(err))
+                    if err.kind() == std::io::ErrorKind::UnexpectedEof =>
+                {
+                    trace!("had eof, waiting for more data");
+                    break;
+                }
+                Err(err) => return Err(err),
+            };
+
+            let read_bytes = usize::try_from(cursor.position()).unwrap();
+            // Checksums aren't validated yet, only reported to verify our
+            // calculation matches the device's.
+            let checksum_matches =
+                AvocadoPacket::checksum_matches(&self.buf[..read_bytes]).unwrap_or_default();
+            self.buf.drain(0..read_bytes);
+
+            debug!(read_bytes, checksum_matches, "got packet: {packet:?}");
+            packets.push((packet, checksum_matches));
+        }
+
+        Ok(packets)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packet(msg_number: u32) -> AvocadoPacket {
+        AvocadoPacket {
+            version: 100,
+            reserved: 0,
+            content_type: ContentType::Message,
+            interaction_type: InteractionType::Response,
+            encoding_type: EncodingType::Json,
+            encryption_mode: EncryptionMode::None,
+            terminal_id: 1,
+            msg_number,
+            msg_package_total: 1,
+            msg_package_num: 1,
+            is_subpackage: false,
+            data: format!(r#"{{"id":{msg_number}}}"#).into_bytes(),
+        }
+    }
+
+    #[test]
+    fn test_packet_framer() {
+        let mut data = packet(1).encode();
+        data.extend(packet(2).encode());
+        data.extend(packet(3).encode());
+
+        // Split partway through the first packet and between the header and
+        // body of the second.
+        let first_len = packet(1).encode().len();
+        let (start, rest) = data.split_at(first_len / 2);
+        let (middle, end) = rest.split_at(first_len - start.len() + 8);
+
+        let mut framer = PacketFramer::default();
+        assert!(framer.push(start).unwrap().is_empty());
+
+        let packets = framer.push(middle).unwrap();
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].0.msg_number, 1);
+
+        let packets = framer.push(end).unwrap();
+        assert_eq!(
+            packets
+                .iter()
+                .map(|(packet, _)| packet.msg_number)
+                .collect::<Vec<_>>(),
+            [2, 3]
+        );
+        assert!(
+            packets
+                .iter()
+                .all(|(_, checksum_matches)| *checksum_matches)
+        );
+        assert!(framer.buf.is_empty());
+    }
+
+    #[test]
+    fn test_packet_framer_byte_at_a_time() {
+        let data = packet(7).encode();
+
+        let mut framer = PacketFramer::default();
+        let packets: Vec<_> = data
+            .chunks(1)
+            .flat_map(|byte| framer.push(byte).unwrap())
+            .collect();
+
+        assert_eq!(packets.len(), 1);
+        assert_eq!(packets[0].0.data, packet(7).data);
+    }
+}
@@ -0,0 +1,222 @@
+use std::borrow::Cow;
+use std::io::{Read, Write};
+use std::net::{Shutdown, TcpStream};
+use std::sync::Arc;
+
+use anyhow::bail;
+use async_trait::async_trait;
+use futures::{
+    SinkExt, StreamExt,
+    channel::{mpsc, oneshot},
+};
+use tracing::{debug, error, info};
+
+use crate::{
+    protocol::AvocadoPacket,
+    spawn, spawn_blocking,
+    transports::{
+        ConnectionStep, DiscoveredDevice, PacketFramer, TransportControl, TransportEvent,
+        TransportStatus,
+    },
+};
+
+#[derive(Debug)]
+enum TransportAction {
+    SendPacket((AvocadoPacket, oneshot::Sender<()>)),
+    Disconnect,
+}
+
+/// A transport for devices reachable over TCP, such as through a serial to
+/// TCP bridge.
+pub struct TcpTransport {
+    address: String,
+    tx: Option<mpsc::UnboundedSender<TransportAction>>,
+}
+
+impl Default for TcpTransport {
+    fn default() -> Self {
+        Self {
+            address: "127.0.0.1:9100".to_string(),
+            tx: None,
+        }
+    }
+}
+
+#[async_trait]
+impl TransportControl for TcpTransport {
+    fn name(&self) -> Cow<'static, str> {
+        "TCP".into()
+    }
+
+    fn supports_discovery(&self) -> bool {
+        false
+    }
+
+    fn address_mut(&mut self) -> Option<&mut String> {
+        Some(&mut self.address)
+    }
+
+    async fn start(
+        &mut self,
+        mut event_tx: mpsc::UnboundedSender<TransportEvent>,
+        _target: Option<DiscoveredDevice>,
+    ) -> anyhow::Result<()> {
+        event_tx
+            .send(TransportEvent::TransportStatus(TransportStatus::Connecting))
+            .await?;
+
+        event_tx
+            .send(TransportEvent::ConnectionStep(ConnectionStep::Opening(
+                self.address.clone(),
+            )))
+            .await?;
+
+        // Connecting blocks, so do it away from the async runtime.
+        let (connect_tx, connect_rx) = oneshot::channel();
+        let address = self.address.clone();
+        spawn_blocking(move || {
+            let _ = connect_tx.send(TcpStream::connect(address));
+        });
+
+        let stream = match connect_rx.await? {
+            Ok(stream) => stream,
+            Err(err) => {
+                event_tx
+                    .send(TransportEvent::TransportStatus(
+                        TransportStatus::Disconnected,
+                    ))
+                    .await?;
+                bail!("could not connect to {}: {err}", self.address);
+            }
+        };
+        stream.set_nodelay(true)?;
+        info!(address = self.address, "connected to tcp transport");
+
+        let reader = stream.try_clone()?;
+        let (action_tx, action_rx) = mpsc::unbounded();
+
+        event_tx
+            .send(TransportEvent::TransportStatus(TransportStatus::Connected))
+            .await?;
+
+        spawn(action_task(Arc::new(stream), action_rx, event_tx.clone()));
+        spawn_blocking(move || read_task(reader, event_tx));
+
+        self.tx = Some(action_tx);
+
+        Ok(())
+    }
+
+    async fn disconnect(&mut self) -> anyhow::Result<()> {
+        let Some(tx) = self.tx.as_mut() else {
+            bail!("transport was not started");
+        };
+
+        tx.send(TransportAction::Disconnect).await?;
+        Ok(())
+    }
+
+    async fn send_packet(
+        &mut self,
+        packet: AvocadoPacket,
+    ) -> anyhow::Result<oneshot::Receiver<()>> {
+        let Some(tx) = self.tx.as_mut() else {
+            bail!("transport was not started");
+        };
+
+        let (send_tx, send_rx) = oneshot::channel();
+        tx.send(TransportAction::SendPacket((packet, send_tx)))
+            .await?;
+        Ok(send_rx)
+    }
+}
+
+async fn action_task(
+    stream: Arc<TcpStream>,
+    mut action_rx: mpsc::UnboundedReceiver<TransportAction>,
+    event_tx: mpsc::UnboundedSender<TransportEvent>,
+) {
+    while let Some(action) = action_rx.next().await {
+        debug!("got action: {action:?}");
+
+        match action {
+            TransportAction::SendPacket((packet, tx)) => {
+                let data = packet.encode();
+                let len = data.len();
+
+                // Writing blocks until the device accepts the data, so do it
+                // away from the async runtime.
+                let (write_tx, write_rx) = oneshot::channel();
+                let writer = stream.clone();
+                spawn_blocking(move || {
+                    let _ = write_tx.send((&*writer).write_all(&data));
+                });
+
+                match write_rx.await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(err)) => {
+                        error!("could not write packet: {err}");
+                        break;
+                    }
+                    Err(_) => {
+                        error!("write task ended without a result");
+                        break;
+                    }
+                }
+
+                let _ = event_tx.unbounded_send(TransportEvent::BytesWritten(len));
+
+                if tx.send(()).is_err() {
+                    error!("could not send message completion");
+                }
+            }
+
+            TransportAction::Disconnect => break,
+        }
+    }
+
+    // Shutting down the stream also ends the read task, which sends the
+    // disconnected status.
+    if let Err(err) = stream.shutdown(Shutdown::Both) {
+        error!("could not shut down stream: {err}");
+    }
+}
+
+fn read_task(mut reader: TcpStream, event_tx: mpsc::UnboundedSender<TransportEvent>) {
+    if let Err(err) = read_packets(&mut reader, &event_tx) {
+        error!("read task errored: {err}");
+        let _ = event_tx.unbounded_send(TransportEvent::Error(err));
+    }
+
+    let _ = event_tx.unbounded_send(TransportEvent::TransportStatus(
+        TransportStatus::Disconnected,
+    ));
+
+    info!("tcp read task stopped");
+}
+
+fn read_packets(
+    reader: &mut TcpStream,
+    event_tx: &mpsc::UnboundedSender<TransportEvent>,
+) -> anyhow::Result<()> {
+    let mut framer = PacketFramer::default();
+    let mut chunk = [0; 4096];
+
+    loop {
+        let len = match reader.read(&mut chunk) {
+            Ok(0) => {
+                info!("read done");
+                return Ok(());
+            }
+            Ok(len) => len,
+            // The stream was shut down by disconnecting.
+            Err(err) if err.kind() == std::io::ErrorKind::ConnectionAborted => return Ok(()),
+            Err(err) => return Err(err.into()),
+        };
+
+        for (packet, checksum_matches) in framer.push(&chunk[..len])? {
+            event_tx.unbounded_send(TransportEvent::Checksum(checksum_matches))?;
+            event_tx.unbounded_send(TransportEvent::Packet(packet))?;
+        }
+    }
+}
@@ -1,5 +1,4 @@
 use std::borrow::Cow;
-use std::io::Cursor;
 
 use anyhow::{anyhow, bail};
 use async_trait::async_trait;
@@ -13,9 +12,15 @@ use web_sys::{
 
 use crate::{
     protocol::{self, AvocadoPacket},
-    transports::{TransportControl, TransportEvent, TransportStatus},
+    transports::{
+        ConnectionStep, DiscoveredDevice, PacketFramer, TransportControl, TransportEvent,
+        TransportStatus,
+    },
 };
 
+/// Baud rate the printer's serial port runs at.
+const BAUD_RATE: u32 = 9600;
+
 #[derive(Debug)]
 enum TransportAction {
     SendPacket(
@@ -42,9 +47,16 @@ impl TransportControl for WebSerialTransport {
         false
     }
 
+    fn is_available(&self) -> bool {
+        web_sys::window().is_some_and(|window| {
+            js_sys::Reflect::has(&window.navigator(), &JsValue::from_str("serial")).unwrap_or(false)
+        })
+    }
+
     async fn start(
         &mut self,
         mut event_tx: mpsc::UnboundedSender<TransportEvent>,
+        _target: Option<DiscoveredDevice>,
     ) -> anyhow::Result<()> {
         let navigator = web_sys::window().unwrap().navigator();
         if !js_sys::Reflect::has(&navigator, &JsValue::from_str("serial")).unwrap() {
@@ -55,6 +67,12 @@ impl TransportControl for WebSerialTransport {
             .send(TransportEvent::TransportStatus(TransportStatus::Connecting))
             .await?;
 
+        event_tx
+            .send(TransportEvent::ConnectionStep(
+                ConnectionStep::RequestingPort,
+            ))
+            .await?;
+
         let serial = navigator.serial();
         let port = JsFuture::from(serial.request_port())
             .await
@@ -64,7 +82,13 @@ impl TransportControl for WebSerialTransport {
 
         let (action_tx, action_rx) = mpsc::unbounded();
 
-        JsFuture::from(port.open(&SerialOptions::new(9600)))
+        event_tx
+            .send(TransportEvent::ConnectionStep(ConnectionStep::Opening(
+                format!("serial port at {BAUD_RATE} baud"),
+            )))
+            .await?;
+
+        JsFuture::from(port.open(&SerialOptions::new(BAUD_RATE)))
             .await
             .map_err(|err| anyhow!("could not open port: {err:?}"))?;
 
@@ -130,7 +154,9 @@ impl WebSerialHandler {
         let reader = ReadableStreamDefaultReader::new(&self.port.readable()).unwrap();
         let writer = self.port.writable().get_writer().unwrap();
 
-        let mut action_task = Box::pin(Self::action_task(self.action_rx, stop_tx, &writer).fuse());
+        let mut action_task = Box::pin(
+            Self::action_task(self.action_rx, stop_tx, &writer, self.event_tx.clone()).fuse(),
+        );
         let mut read_task = Box::pin(Self::read_task(&reader, self.event_tx.clone()).fuse());
 
         futures::select! {
@@ -185,6 +211,7 @@ impl WebSerialHandler {
         mut action_rx: mpsc::UnboundedReceiver<TransportAction>,
         stop_tx: oneshot::Sender<()>,
         writer: &WritableStreamDefaultWriter,
+        mut event_tx: mpsc::UnboundedSender<TransportEvent>,
     ) -> anyhow::Result<()> {
         while let Some(action) = action_rx.next().await {
             debug!("got action: {action:?}");
@@ -192,12 +219,22 @@ impl WebSerialHandler {
             match action {
                 TransportAction::SendPacket((packet, tx)) => {
                     let data = packet.encode();
+                    let len = data.len();
                     let data = js_sys::Uint8Array::new_from_slice(&data);
 
                     JsFuture::from(writer.write_with_chunk(&data))
                         .await
                         .map_err(|err| anyhow!("could not write chunk: {err:?}"))?;
 
+                    // The stream may still be buffering the chunk, so wait
+                    // until it signals it can accept more data before marking
+                    // this packet as sent. This keeps us from overrunning the
+                    // device during large transfers.
+                    JsFuture::from(writer.ready())
+                        .await
+                        .map_err(|err| anyhow!("writer did not become ready: {err:?}"))?;
+                    event_tx.send(TransportEvent::BytesWritten(len)).await?;
+
                     if tx.send(()).is_err() {
                         error!("could not send message completion");
                     }
@@ -220,7 +257,7 @@ impl WebSerialHandler {
         reader: &ReadableStreamDefaultReader,
         mut event_tx: mpsc::UnboundedSender<TransportEvent>,
     ) -> anyhow::Result<()> {
-        let mut buf: Vec<u8> = Vec::new();
+        let mut framer = PacketFramer::default();
 
         loop {
             let result = JsFuture::from(reader.read())
@@ -245,35 +282,12 @@ impl WebSerialHandler {
                 continue;
             }
 
-            let existing_buf_len = buf.len();
-            let new_data_len = usize::try_from(data.length()).unwrap();
-
-            buf.resize(buf.len() + new_data_len, 0);
-            data.copy_to(&mut buf[existing_buf_len..existing_buf_len + new_data_len]);
-            trace!(
-                "read {} bytes, total buffer is {} bytes",
-                data.length(),
-                buf.len()
-            );
-
-            let mut cursor = Cursor::new(&mut buf);
-            let packet = match protocol::AvocadoPacket::read_one(&mut cursor) {
-                Ok(packet) => packet,
-                Err(protocol::ProtocolError::Reader(err))
-                    if err.kind() == std::io::ErrorKind::UnexpectedEof =>
-                {
-                    trace!("had eof, continuing to next read");
-                    continue;
-                }
-                Err(err) => return Err(err.into()),
-            };
-
-            let read_bytes = usize::try_from(cursor.position()).unwrap();
-            buf.drain(0..read_bytes);
-
-            debug!(read_bytes, "got packet: {packet:?}");
-
-            event_tx.send(TransportEvent::Packet(packet)).await?;
+            for (packet, checksum_matches) in framer.push(&data.to_vec())? {
+                event_tx
+                    .send(TransportEvent::Checksum(checksum_matches))
+                    .await?;
+                event_tx.send(TransportEvent::Packet(packet)).await?;
+            }
         }
     }
 }
@@ -1,17 +1,20 @@
 use egui::{
-    Color32, Frame, Key, KeyboardShortcut, Modifiers, Painter, Pos2, Rect, Scene, Sense, Shape,
-    Stroke, Ui,
+    Area, Color32, Frame, Key, Mesh, Order, Painter, Pos2, Rect, Scene, Sense, Shape, Stroke, Ui,
+    Vec2,
     emath::{self, RectTransform},
 };
-use geo::MultiPolygon;
+use geo::{AffineOps, MultiPolygon};
 use tracing::instrument;
 
-use crate::{SapodillaApp, protocol::DEVICES};
-
-const CUT_LINE_WIDTH: f32 = 3.0;
+use crate::{
+    SapodillaApp,
+    app::LoadedImage,
+    protocol::DEVICES,
+    shortcuts::{REMOVE_IMAGE, REMOVE_IMAGE_BACKSPACE},
+};
 
-const DELETE_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::Delete);
-const BACKSPACE_SHORTCUT: KeyboardShortcut = KeyboardShortcut::new(Modifiers::NONE, Key::Backspace);
+/// Distance in screen pixels at which dragged images snap to the safe area.
+const SNAP_DISTANCE: f32 = 8.0;
 
 const NORMAL_UV: Rect = Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(1.0, 1.0));
 
@@ -30,15 +33,24 @@ pub fn canvas_editor(ui: &mut Ui, state: &mut SapodillaApp) {
 
     let mut inner_rect = Rect::NAN;
     let mut canvas_rect = state.canvas_rect;
+    let canvas_size = if state.show_as_printed {
+        state.printed_transform().1
+    } else {
+        state.get_canvas().size
+    };
 
     let response = scene
         .show(ui, &mut canvas_rect, |ui| {
             Frame::canvas(ui.style())
-                .fill(Color32::WHITE)
+                .fill(state.background_color)
                 .inner_margin(0.0)
                 .stroke(Stroke::new(4.0, Color32::BLACK))
                 .show(ui, |ui| {
-                    frame(ui, state);
+                    if state.show_as_printed {
+                        printed_frame(ui, state);
+                    } else {
+                        frame(ui, state);
+                    }
                 });
             inner_rect = ui.min_rect();
         })
@@ -46,9 +58,74 @@ pub fn canvas_editor(ui: &mut Ui, state: &mut SapodillaApp) {
 
     state.canvas_rect = canvas_rect;
 
-    if response.double_clicked() || state.previous_canvas_size != state.get_canvas().size {
+    if response.double_clicked() || state.previous_canvas_size != canvas_size {
         state.canvas_rect = inner_rect.shrink(ui.style().spacing.menu_spacing);
-        state.previous_canvas_size = state.get_canvas().size;
+        state.previous_canvas_size = canvas_size;
+    }
+}
+
+/// Draw the canvas turned the way it comes out of the printer, without any
+/// way to edit it.
+fn printed_frame(ui: &mut Ui, state: &SapodillaApp) {
+    let (transform, size) = state.printed_transform();
+
+    ui.set_min_size(size);
+    ui.set_max_size(size);
+
+    let (response, painter) = ui.allocate_painter(size, Sense::empty());
+
+    let to_screen = emath::RectTransform::from_to(
+        Rect::from_min_size(Pos2::ZERO, response.rect.size()),
+        response.rect,
+    );
+    let to_printed = |pos: Pos2| {
+        let coord = transform.apply(geo::coord! { x: pos.x, y: pos.y });
+        to_screen.transform_pos(Pos2::new(coord.x, coord.y))
+    };
+
+    // Images may be turned, so draw them as meshes with each corner moved.
+    for image in &state.loaded_images {
+        let mut mesh = Mesh::with_texture(image.sized_texture.id);
+        mesh.add_rect_with_uv(
+            Rect::from_min_size(image.offset, image.size()),
+            image.uv(),
+            Color32::WHITE.gamma_multiply(image.opacity),
+        );
+
+        for vertex in &mut mesh.vertices {
+            vertex.pos = to_printed(vertex.pos);
+        }
+
+        painter.add(mesh);
+    }
+
+    let cut_shapes: Vec<_> = state
+        .cut_shapes
+        .iter()
+        .map(|shape| shape.affine_transform(&transform))
+        .collect();
+    paint_polygons(
+        &to_screen,
+        &painter,
+        &cut_shapes,
+        state
+            .cut_tuning
+            .blade_kerf_px(DEVICES[state.selected_device].average_dpi()),
+    );
+
+    let canvas = state.get_canvas();
+    if canvas.safe_area != canvas.size {
+        let safe_rect = canvas.safe_rect();
+        let safe_rect = Rect::from_two_pos(to_printed(safe_rect.min), to_printed(safe_rect.max));
+
+        paint_bleed(&painter, response.rect, safe_rect);
+
+        painter.rect_stroke(
+            safe_rect,
+            0,
+            Stroke::new(5.0, Color32::from_rgba_unmultiplied(139, 0, 0, 128)),
+            egui::StrokeKind::Outside,
+        );
     }
 }
 
@@ -68,14 +145,62 @@ fn frame(ui: &mut Ui, state: &mut SapodillaApp) {
     let mut hovers = Vec::new();
     let mut remove = None;
 
+    // The scene zooms everything within it, so convert the snap distance
+    // from screen pixels to canvas pixels. Holding alt disables snapping.
+    let zoom = ui
+        .ctx()
+        .layer_transform_to_global(ui.layer_id())
+        .map_or(1.0, |transform| transform.scaling);
+    let snap_threshold = SNAP_DISTANCE / zoom;
+    let snapping = !ui.input(|i| i.modifiers.alt);
+    let safe_rect = state.get_canvas().safe_rect();
+
     for (idx, image) in state.loaded_images.iter_mut().enumerate() {
         let pos_in_screen = to_screen.transform_pos(image.offset);
         let image_rect = Rect::from_min_size(pos_in_screen, image.size());
 
-        let rect_id = response.id.with(idx);
-        let rect_response = ui.interact(image_rect, rect_id, Sense::drag());
+        // Use the texture for the ID so interactions follow the image when
+        // the images are reordered.
+        let rect_id = response.id.with(image.sized_texture.id);
 
-        image.offset += rect_response.drag_delta();
+        if state.cropping && state.selected_image == Some(image.sized_texture.id) {
+            crop_editor(ui, &to_screen, &painter, rect_id, image);
+            continue;
+        }
+
+        let rect_response = ui.interact(image_rect, rect_id, Sense::click_and_drag());
+
+        if rect_response.clicked() || rect_response.drag_started() {
+            if state.selected_image != Some(image.sized_texture.id) {
+                state.cropping = false;
+            }
+            state.selected_image = Some(image.sized_texture.id);
+        }
+
+        if rect_response.dragged() {
+            // Follow the pointer from where the image was grabbed rather than
+            // adding up deltas, so a snapped image can be pulled back out.
+            let grab_id = rect_id.with("grab");
+            let pointer = rect_response.interact_pointer_pos();
+            if rect_response.drag_started()
+                && let Some(pointer) = pointer
+            {
+                ui.data_mut(|data| data.insert_temp(grab_id, image.offset - pointer));
+            }
+
+            image.offset = match (pointer, ui.data(|data| data.get_temp::<Vec2>(grab_id))) {
+                (Some(pointer), Some(grab)) => pointer + grab,
+                _ => image.offset + rect_response.drag_delta(),
+            };
+
+            if snapping {
+                image.offset += safe_area_snap(
+                    Rect::from_min_size(image.offset, image.size()),
+                    safe_rect,
+                    snap_threshold,
+                );
+            }
+        }
 
         let pos_in_screen = to_screen.transform_pos(image.offset);
 
@@ -85,7 +210,7 @@ fn frame(ui: &mut Ui, state: &mut SapodillaApp) {
 
         if rect_response.hovered()
             && ui.input_mut(|i| {
-                i.consume_shortcut(&DELETE_SHORTCUT) || i.consume_shortcut(&BACKSPACE_SHORTCUT)
+                i.consume_shortcut(&REMOVE_IMAGE) || i.consume_shortcut(&REMOVE_IMAGE_BACKSPACE)
             })
         {
             remove = Some(idx);
@@ -93,20 +218,37 @@ fn frame(ui: &mut Ui, state: &mut SapodillaApp) {
             painter.image(
                 image.sized_texture.id,
                 Rect::from_min_size(pos_in_screen, image.size()),
-                NORMAL_UV,
-                Color32::WHITE,
+                image.uv(),
+                Color32::WHITE.gamma_multiply(image.opacity),
             );
         }
     }
 
-    paint_polygons(&to_screen, &painter, &state.cut_shapes);
+    // The scene applies its zoom to everything painted within it, so the
+    // kerf only needs to be in canvas pixels to match the physical width.
+    paint_polygons(
+        &to_screen,
+        &painter,
+        &state.cut_shapes,
+        state
+            .cut_tuning
+            .blade_kerf_px(DEVICES[state.selected_device].average_dpi()),
+    );
+
+    if state.cut_tuning.show_travel {
+        paint_travel(&to_screen, &painter, &state.travel_moves());
+    }
+
+    let canvas = state.get_canvas();
 
-    let safe_area = DEVICES[state.selected_device].modes[state.selected_mode].canvas_sizes
-        [state.selected_canvas_size]
-        .safe_area;
+    if canvas.safe_area != size {
+        let safe_lines = canvas.safe_rect();
 
-    if safe_area != size {
-        let safe_lines = Rect::from_center_size((size / 2.0).to_pos2(), safe_area);
+        paint_bleed(
+            &painter,
+            to_screen.transform_rect(Rect::from_min_size(Pos2::ZERO, size)),
+            to_screen.transform_rect(safe_lines),
+        );
 
         painter.rect_stroke(
             to_screen.transform_rect(safe_lines),
@@ -116,6 +258,25 @@ fn frame(ui: &mut Ui, state: &mut SapodillaApp) {
         );
     }
 
+    // Cut lines are grown by the buffer, so content needs to stay this far
+    // inside the safe area for its cut to stay inside too.
+    let has_cutting = DEVICES[state.selected_device].modes[state.selected_mode]
+        .mode_type
+        .has_cutting();
+    if has_cutting && state.cut_tuning.buffer > 0.0 {
+        let margin = to_screen.transform_rect(canvas.safe_rect().shrink(state.cut_tuning.buffer));
+        let stroke = Stroke::new(2.0, Color32::from_rgba_unmultiplied(139, 0, 0, 64));
+
+        let corners = [
+            margin.left_top(),
+            margin.right_top(),
+            margin.right_bottom(),
+            margin.left_bottom(),
+            margin.left_top(),
+        ];
+        painter.extend(Shape::dashed_line(&corners, stroke, 12.0, 8.0));
+    }
+
     painter.set_clip_rect(ui.clip_rect());
 
     let stroke = Stroke::new(5.0, Color32::from_rgba_unmultiplied(173, 216, 230, 192));
@@ -123,19 +284,227 @@ fn frame(ui: &mut Ui, state: &mut SapodillaApp) {
         painter.rect_stroke(rect, 0, stroke, egui::StrokeKind::Outside);
     }
 
+    if ui.input(|i| i.key_pressed(Key::Escape)) {
+        if state.cropping {
+            state.cropping = false;
+        } else {
+            state.selected_image = None;
+        }
+    }
+
+    let dpi = DEVICES[state.selected_device].dpi;
+    if let Some(image) = state
+        .loaded_images
+        .iter_mut()
+        .find(|image| Some(image.sized_texture.id) == state.selected_image)
+    {
+        let image_rect = to_screen.transform_rect(Rect::from_min_size(image.offset, image.size()));
+        painter.rect_stroke(
+            image_rect,
+            0,
+            Stroke::new(2.0, Color32::from_rgb(30, 144, 255)),
+            egui::StrokeKind::Outside,
+        );
+
+        // Keep the overlay below the parts that are cropped out too, so it
+        // doesn't cover them while cropping.
+        let anchor = if state.cropping {
+            image_rect.union(to_screen.transform_rect(image.uncropped_rect()))
+        } else {
+            image_rect
+        };
+
+        if !placement_overlay(ui, anchor, dpi, size, image, &mut state.cropping) {
+            state.selected_image = None;
+        }
+    }
+
+    if state.selected_image.is_none() {
+        state.cropping = false;
+    }
+
     if let Some(remove) = remove {
         state.loaded_images.remove(remove);
     }
 }
 
+/// Show the exact position and size of the selected image just below it,
+/// returning if it should stay selected.
+fn placement_overlay(
+    ui: &Ui,
+    image_rect: Rect,
+    dpi: Vec2,
+    canvas_size: Vec2,
+    image: &mut LoadedImage,
+    cropping: &mut bool,
+) -> bool {
+    // The overlay isn't part of the scene, so it needs to be placed in
+    // screen coordinates and stays the same size when zooming.
+    let image_rect = ui
+        .ctx()
+        .layer_transform_to_global(ui.layer_id())
+        .map_or(image_rect, |transform| transform * image_rect);
+
+    let mut keep = true;
+
+    Area::new(ui.id().with("placement_overlay"))
+        .order(Order::Foreground)
+        .fixed_pos(image_rect.left_bottom() + Vec2::new(0.0, 8.0))
+        .show(ui.ctx(), |ui| {
+            Frame::popup(ui.style()).show(ui, |ui| {
+                ui.spacing_mut().interact_size.x = 72.0;
+
+                super::image_placement(ui, dpi, canvas_size, image);
+
+                ui.horizontal(|ui| {
+                    if ui
+                        .selectable_label(*cropping, "Crop")
+                        .on_hover_text("Drag across the image to choose the part to keep")
+                        .clicked()
+                    {
+                        *cropping = !*cropping;
+                    }
+
+                    if ui
+                        .add_enabled(
+                            image.crop().is_some(),
+                            egui::Button::new("Reset Crop").small(),
+                        )
+                        .clicked()
+                    {
+                        image.set_crop(None);
+                    }
+
+                    if ui.small_button("Done").clicked() {
+                        keep = false;
+                    }
+                });
+            });
+        });
+
+    keep
+}
+
+/// Draw the whole image with the parts outside the crop dimmed, dragging
+/// across it to choose a new crop.
+fn crop_editor(
+    ui: &Ui,
+    to_screen: &RectTransform,
+    painter: &Painter,
+    id: egui::Id,
+    image: &mut LoadedImage,
+) {
+    let uncropped_rect = to_screen.transform_rect(image.uncropped_rect());
+    let response = ui.interact(uncropped_rect, id.with("crop"), Sense::drag());
+
+    let to_uv = RectTransform::from_to(uncropped_rect, NORMAL_UV);
+    let start_id = id.with("crop_start");
+    if let Some(pointer) = response.interact_pointer_pos() {
+        let pointer = to_uv.transform_pos_clamped(pointer);
+
+        // Dragging only starts once the pointer has moved a little, so start
+        // from where it was pressed, which is in screen coordinates.
+        if response.drag_started() {
+            let origin = ui
+                .input(|i| i.pointer.press_origin())
+                .map(|origin| {
+                    ui.ctx()
+                        .layer_transform_from_global(ui.layer_id())
+                        .map_or(origin, |transform| transform * origin)
+                })
+                .map_or(pointer, |origin| to_uv.transform_pos_clamped(origin));
+            ui.data_mut(|data| data.insert_temp(start_id, origin));
+        }
+
+        if response.dragged()
+            && let Some(start) = ui.data(|data| data.get_temp::<Pos2>(start_id))
+        {
+            image.set_crop(Some(Rect::from_two_pos(start, pointer)));
+        }
+    }
+
+    painter.image(
+        image.sized_texture.id,
+        uncropped_rect,
+        NORMAL_UV,
+        Color32::WHITE.gamma_multiply(image.opacity * 0.3),
+    );
+    painter.image(
+        image.sized_texture.id,
+        to_screen.transform_rect(image.rect()),
+        image.uv(),
+        Color32::WHITE.gamma_multiply(image.opacity),
+    );
+}
+
+/// How far to move a rect so its edges line up with the edges of the safe
+/// area, if they're within the threshold.
+fn safe_area_snap(rect: Rect, safe_area: Rect, threshold: f32) -> Vec2 {
+    let snap = |min: f32, max: f32, safe_min: f32, safe_max: f32| {
+        [safe_min - min, safe_max - max]
+            .into_iter()
+            .filter(|delta| delta.abs() <= threshold)
+            .min_by(|a, b| a.abs().total_cmp(&b.abs()))
+            .unwrap_or(0.0)
+    };
+
+    Vec2::new(
+        snap(rect.min.x, rect.max.x, safe_area.min.x, safe_area.max.x),
+        snap(rect.min.y, rect.max.y, safe_area.min.y, safe_area.max.y),
+    )
+}
+
+/// Shade the area between the canvas edge and safe area, where content may
+/// be trimmed.
+fn paint_bleed(painter: &Painter, canvas: Rect, safe_area: Rect) {
+    let fill = Color32::from_black_alpha(24);
+
+    let bands = [
+        Rect::from_min_max(canvas.min, Pos2::new(canvas.max.x, safe_area.min.y)),
+        Rect::from_min_max(Pos2::new(canvas.min.x, safe_area.max.y), canvas.max),
+        Rect::from_min_max(
+            Pos2::new(canvas.min.x, safe_area.min.y),
+            Pos2::new(safe_area.min.x, safe_area.max.y),
+        ),
+        Rect::from_min_max(
+            Pos2::new(safe_area.max.x, safe_area.min.y),
+            Pos2::new(canvas.max.x, safe_area.max.y),
+        ),
+    ];
+
+    for band in bands {
+        if band.is_positive() {
+            painter.rect_filled(band, 0, fill);
+        }
+    }
+}
+
+/// Draw the moves between cuts as dashed lines.
+fn paint_travel(to_screen: &RectTransform, painter: &Painter, moves: &[(Pos2, Pos2)]) {
+    let stroke = Stroke::new(2.0, Color32::from_gray(96));
+
+    for (start, end) in moves {
+        let points = [
+            to_screen.transform_pos(*start),
+            to_screen.transform_pos(*end),
+        ];
+        painter.extend(Shape::dashed_line(&points, stroke, 8.0, 6.0));
+    }
+}
+
 #[instrument(skip_all)]
-fn paint_polygons(to_screen: &RectTransform, painter: &Painter, cut_shapes: &[MultiPolygon<f32>]) {
+fn paint_polygons(
+    to_screen: &RectTransform,
+    painter: &Painter,
+    cut_shapes: &[MultiPolygon<f32>],
+    line_width: f32,
+) {
     let mut count = 0;
 
     for multi_polygon in cut_shapes.iter() {
         for polygon in multi_polygon.iter() {
             // Make each cut line visually distinguishable.
-            let stroke = Stroke::new(CUT_LINE_WIDTH, FUN_COLORS[count % FUN_COLORS.len()]);
+            let stroke = Stroke::new(line_width, FUN_COLORS[count % FUN_COLORS.len()]);
 
             // Get the lines for the exterior and interior shapes.
             let lines = polygon.exterior().lines().chain(
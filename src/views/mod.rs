@@ -1,6 +1,9 @@
-use std::{collections::VecDeque, io::Cursor, ops::RangeInclusive};
+use std::{
+    collections::{HashMap, VecDeque},
+    ops::RangeInclusive,
+};
 
-use egui::{Id, Modal, Pos2, ProgressBar, Ui, Vec2};
+use egui::{Id, Modal, ProgressBar, Response, Ui, Vec2};
 use egui_extras::{
     Column, TableBuilder,
     syntax_highlighting::{CodeTheme, code_view_ui},
@@ -8,10 +11,14 @@ use egui_extras::{
 use tracing::debug;
 
 use crate::{
-    app::{Action, ContextSender, LoadedImage},
-    cut::CutTuning,
-    protocol::{self, AvocadoId, AvocadoPacket, AvocadoPacketReader, ProtocolError},
-    spawn,
+    app::{Action, ContextSender, JobRecord, LoadedImage, LoggedPacket, TileLayout},
+    cut::{ContourStats, CutError, CutTuning, DEFAULT_CUT_PRESSURE, DEFAULT_CUT_SPEED},
+    logs,
+    protocol::{
+        self, AvocadoId, AvocadoMethod, AvocadoPacket, Device, InteractionType, KnownMethod,
+        LoadedPackets,
+    },
+    shortcuts, spawn,
 };
 
 pub use canvas::canvas_editor;
@@ -50,16 +57,17 @@ pub fn pretty_hex(id: impl std::hash::Hash, ui: &mut Ui, data: &[u8]) {
 
 pub fn protocol_packets_table(
     ui: &mut Ui,
-    packets: &VecDeque<protocol::AvocadoPacket>,
+    packets: &VecDeque<LoggedPacket>,
     viewing_packet: &mut Option<protocol::AvocadoPacket>,
 ) {
     TableBuilder::new(ui)
         .auto_shrink(false)
         .striped(true)
-        .columns(Column::auto().resizable(true), 10)
+        .columns(Column::auto().resizable(true), 11)
         .column(Column::remainder().resizable(true))
         .header(20.0, |mut header| {
             const FIELDS: &[&str] = &[
+                "Direction",
                 "Message ID",
                 "Request ID",
                 "Content Type",
@@ -81,7 +89,12 @@ pub fn protocol_packets_table(
         })
         .body(|body| {
             body.rows(20.0, packets.len(), |mut row| {
-                let packet = &packets[row.index()];
+                let logged = &packets[row.index()];
+                let packet = &logged.packet;
+
+                row.col(|ui| {
+                    ui.label(if logged.sent { "Sent" } else { "Received" });
+                });
 
                 row.col(|ui| {
                     ui.label(packet.msg_number.to_string());
@@ -140,6 +153,110 @@ pub fn protocol_packets_table(
             });
         });
 
+    packet_modal(ui, viewing_packet);
+}
+
+/// A request and its response, matched by ID.
+struct Exchange<'a> {
+    id: u32,
+    request: Option<&'a LoggedPacket>,
+    response: Option<&'a LoggedPacket>,
+}
+
+/// Show the packet log as requests paired with their responses, with the
+/// time between sending and receiving them.
+pub fn packet_timeline(
+    ui: &mut Ui,
+    packets: &VecDeque<LoggedPacket>,
+    viewing_packet: &mut Option<protocol::AvocadoPacket>,
+) {
+    let mut exchanges: Vec<Exchange> = Vec::new();
+    let mut indexes: HashMap<u32, usize> = HashMap::new();
+    let mut unmatched = 0;
+
+    // The log is newest first, so go from the oldest packet.
+    for logged in packets.iter().rev() {
+        let Some(AvocadoId { id }) = logged.packet.as_json() else {
+            unmatched += 1;
+            continue;
+        };
+
+        let index = *indexes.entry(id).or_insert_with(|| {
+            exchanges.push(Exchange {
+                id,
+                request: None,
+                response: None,
+            });
+            exchanges.len() - 1
+        });
+
+        match logged.packet.interaction_type {
+            InteractionType::Request => exchanges[index].request = Some(logged),
+            InteractionType::Response => exchanges[index].response = Some(logged),
+        }
+    }
+
+    if unmatched > 0 {
+        ui.label(format!("{unmatched} packets without an ID are not shown"));
+    }
+
+    egui::ScrollArea::vertical()
+        .auto_shrink(false)
+        .show(ui, |ui| {
+            for exchange in exchanges.iter().rev() {
+                let method = exchange
+                    .request
+                    .and_then(|request| request.packet.as_json::<AvocadoMethod>())
+                    .map(|method| method.method)
+                    .unwrap_or_else(|| "unknown".to_string());
+
+                let latency = match (exchange.request, exchange.response) {
+                    (Some(request), Some(response)) => format!(
+                        "{} ms",
+                        response
+                            .timestamp_millis
+                            .saturating_sub(request.timestamp_millis)
+                    ),
+                    (Some(_), None) => "no response".to_string(),
+                    (None, _) => "request not logged".to_string(),
+                };
+
+                egui::CollapsingHeader::new(format!("{} {method} ({latency})", exchange.id))
+                    .id_salt(("exchange", exchange.id))
+                    .show(ui, |ui| {
+                        for (label, logged) in [
+                            ("Request", exchange.request),
+                            ("Response", exchange.response),
+                        ] {
+                            let Some(logged) = logged else {
+                                continue;
+                            };
+
+                            ui.horizontal(|ui| {
+                                ui.strong(label);
+                                if ui.small_button("View").clicked() {
+                                    *viewing_packet = Some(logged.packet.clone());
+                                }
+                            });
+
+                            if let Some(data) = logged.packet.as_json::<serde_json::Value>() {
+                                let theme = CodeTheme::from_memory(ui.ctx(), ui.style());
+                                code_view_ui(
+                                    ui,
+                                    &theme,
+                                    &serde_json::to_string_pretty(&data).unwrap_or_default(),
+                                    "json",
+                                );
+                            }
+                        }
+                    });
+            }
+        });
+
+    packet_modal(ui, viewing_packet);
+}
+
+fn packet_modal(ui: &mut Ui, viewing_packet: &mut Option<protocol::AvocadoPacket>) {
     if let Some(packet) = viewing_packet {
         let modal = Modal::new(Id::new(packet.msg_number)).show(ui.ctx(), |ui| {
             ui.set_width(380.0);
@@ -159,9 +276,19 @@ pub fn protocol_packets_table(
                 );
             };
 
-            if ui.button("Close").clicked() {
-                ui.close();
-            }
+            ui.horizontal(|ui| {
+                if ui
+                    .button("Copy as Snippet")
+                    .on_hover_text("Copy code to parse this exact packet, for bug reports")
+                    .clicked()
+                {
+                    ui.ctx().copy_text(packet.repro_snippet());
+                }
+
+                if ui.button("Close").clicked() {
+                    ui.close();
+                }
+            });
         });
 
         if modal.should_close() {
@@ -174,7 +301,8 @@ pub fn packet_debug(
     ctx: &egui::Context,
     tx: &ContextSender<Action>,
     show: &mut bool,
-    packets: &Option<Result<Vec<AvocadoPacket>, ProtocolError>>,
+    packets: &Option<LoadedPackets>,
+    progress: Option<f32>,
 ) {
     egui::Window::new("Saved Packet Debugger")
         .open(show)
@@ -183,7 +311,10 @@ pub fn packet_debug(
         .resizable([true, true])
         .scroll(true)
         .show(ctx, |ui| {
-            if ui.button("Select File").clicked() {
+            if ui
+                .add_enabled(progress.is_none(), egui::Button::new("Select File"))
+                .clicked()
+            {
                 let ctx = ctx.clone();
                 let tx = tx.clone();
 
@@ -198,9 +329,12 @@ pub fn packet_debug(
                         let data = hex::decode(&maybe_hex_data).unwrap_or(data);
                         debug!("processed data: {}", hex::encode(&data));
 
-                        let cursor = Cursor::new(data);
-                        let avocado_packets: Result<Vec<_>, _> =
-                            AvocadoPacketReader::new(cursor).collect();
+                        let _ = tx.send(Action::AvocadoPacketsProgress(0.0));
+                        let avocado_packets = LoadedPackets::load_lenient(&data, |read, total| {
+                            let _ = tx.send(Action::AvocadoPacketsProgress(
+                                read as f32 / total.max(1) as f32,
+                            ));
+                        });
 
                         let _ = tx.send(Action::LoadedAvocadoPackets(avocado_packets));
                         ctx.request_repaint();
@@ -208,16 +342,40 @@ pub fn packet_debug(
                 });
             }
 
+            if let Some(progress) = progress {
+                ui.add(ProgressBar::new(progress).show_percentage().animate(true));
+            }
+
             match packets {
-                Some(Ok(packets)) => {
-                    let has_exactly_one = packets.len() == 1;
+                Some(loaded) => {
+                    ui.label(loaded_packets_summary(loaded));
 
-                    for (index, packet) in packets.iter().enumerate() {
-                        packet_details(ui, has_exactly_one, index, packet);
+                    for error in &loaded.errors {
+                        ui.label(format!("Error at offset {}: {}", error.offset, error.error));
+                    }
+
+                    let has_exactly_one = loaded.packets.len() == 1;
+
+                    // Responses don't include the method, so find it from the
+                    // matching request.
+                    let methods: HashMap<u32, KnownMethod> = loaded
+                        .packets
+                        .iter()
+                        .filter(|packet| packet.interaction_type == InteractionType::Request)
+                        .filter_map(|packet| packet.as_json::<AvocadoMethod>())
+                        .filter_map(|method| {
+                            Some((method.id, KnownMethod::from_name(&method.method)?))
+                        })
+                        .collect();
+
+                    for (index, packet) in loaded.packets.iter().enumerate() {
+                        let method = packet
+                            .as_json::<AvocadoId>()
+                            .filter(|_| packet.interaction_type == InteractionType::Response)
+                            .and_then(|id| methods.get(&id.id).copied());
+
+                        packet_details(ui, has_exactly_one, index, packet, method);
                     }
-                }
-                Some(Err(err)) => {
-                    ui.label(format!("Error! {err}"));
                 }
                 None => {
                     ui.label("No packets loaded");
@@ -226,8 +384,163 @@ pub fn packet_debug(
         });
 }
 
-fn packet_details(ui: &mut Ui, has_exactly_one: bool, index: usize, packet: &AvocadoPacket) {
-    egui::CollapsingHeader::new(format!("Packet {}", index + 1))
+pub fn log_viewer(ctx: &egui::Context, show: &mut bool, level: &mut tracing::Level) {
+    const LEVELS: [tracing::Level; 5] = [
+        tracing::Level::ERROR,
+        tracing::Level::WARN,
+        tracing::Level::INFO,
+        tracing::Level::DEBUG,
+        tracing::Level::TRACE,
+    ];
+
+    egui::Window::new("Logs")
+        .open(show)
+        .default_size([800.0, 300.0])
+        .show(ctx, |ui| {
+            ui.horizontal(|ui| {
+                egui::ComboBox::from_label("Level")
+                    .selected_text(level.as_str())
+                    .show_ui(ui, |ui| {
+                        for option in LEVELS {
+                            ui.selectable_value(level, option, option.as_str());
+                        }
+                    });
+
+                if ui.button("Clear Log").clicked() {
+                    logs::clear_log_records();
+                }
+            });
+
+            ui.separator();
+
+            // More verbose levels compare as greater.
+            let records: Vec<_> = logs::log_records()
+                .into_iter()
+                .filter(|record| record.level <= *level)
+                .collect();
+
+            let row_height = ui.text_style_height(&egui::TextStyle::Monospace);
+
+            egui::ScrollArea::both()
+                .auto_shrink(false)
+                .stick_to_bottom(true)
+                .show_rows(ui, row_height, records.len(), |ui, range| {
+                    for record in &records[range] {
+                        let color = match record.level {
+                            tracing::Level::ERROR => ui.visuals().error_fg_color,
+                            tracing::Level::WARN => ui.visuals().warn_fg_color,
+                            _ => ui.visuals().text_color(),
+                        };
+
+                        ui.add(
+                            egui::Label::new(
+                                egui::RichText::new(format!(
+                                    "{:>5} {}: {}",
+                                    record.level, record.target, record.message
+                                ))
+                                .monospace()
+                                .color(color),
+                            )
+                            .extend(),
+                        );
+                    }
+                });
+        });
+}
+
+/// List every keyboard shortcut.
+pub fn shortcuts_help(ctx: &egui::Context, show: &mut bool) {
+    egui::Window::new("Keyboard Shortcuts")
+        .open(show)
+        .resizable(false)
+        .show(ctx, |ui| {
+            egui::Grid::new("shortcuts_help")
+                .num_columns(2)
+                .striped(true)
+                .show(ui, |ui| {
+                    for (description, shortcuts) in shortcuts::SHORTCUTS {
+                        ui.label(*description);
+
+                        let keys = shortcuts
+                            .iter()
+                            .map(|shortcut| ctx.format_shortcut(shortcut))
+                            .collect::<Vec<_>>()
+                            .join(" or ");
+                        ui.label(egui::RichText::new(keys).monospace());
+
+                        ui.end_row();
+                    }
+                });
+        });
+}
+
+/// Show completed jobs, returning the index of a job to send again.
+pub fn job_history(
+    ctx: &egui::Context,
+    show: &mut bool,
+    history: &VecDeque<JobRecord>,
+    can_send: bool,
+) -> Option<usize> {
+    let mut reprint = None;
+
+    egui::Window::new("Job History").open(show).show(ctx, |ui| {
+        if history.is_empty() {
+            ui.label("No jobs have been completed.");
+        }
+
+        for (index, record) in history.iter().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(&record.name);
+                ui.label(
+                    egui::RichText::new(format!(
+                        "{}, {:.1} KiB",
+                        record.method,
+                        record.data.len() as f32 / 1024.0
+                    ))
+                    .weak(),
+                );
+
+                if ui
+                    .add_enabled(can_send, egui::Button::new("Reprint").small())
+                    .clicked()
+                {
+                    reprint = Some(index);
+                }
+            });
+        }
+    });
+
+    reprint
+}
+
+fn loaded_packets_summary(loaded: &LoadedPackets) -> String {
+    let mut summary = format!("Parsed {} packets", loaded.packets.len());
+
+    match loaded.errors.as_slice() {
+        [] => (),
+        [error] => summary.push_str(&format!(", 1 error at offset {}", error.offset)),
+        errors => summary.push_str(&format!(", {} errors", errors.len())),
+    }
+
+    summary
+}
+
+fn packet_details(
+    ui: &mut Ui,
+    has_exactly_one: bool,
+    index: usize,
+    packet: &AvocadoPacket,
+    method: Option<KnownMethod>,
+) {
+    let warnings = packet.lint();
+    let title = if warnings.is_empty() {
+        format!("Packet {}", index + 1)
+    } else {
+        format!("Packet {} ⚠", index + 1)
+    };
+
+    egui::CollapsingHeader::new(title)
+        .id_salt(("packet", index))
         .default_open(has_exactly_one)
         .show(ui, |ui| {
             let theme = CodeTheme::from_memory(ui.ctx(), ui.style());
@@ -240,6 +553,10 @@ fn packet_details(ui: &mut Ui, has_exactly_one: bool, index: usize, packet: &Avo
                 "json",
             );
 
+            for warning in &warnings {
+                ui.colored_label(ui.visuals().warn_fg_color, format!("⚠ {warning}"));
+            }
+
             ui.heading("Packet Data (hex)");
             pretty_hex(format!("packet-{index}"), ui, &packet.data);
 
@@ -252,42 +569,98 @@ fn packet_details(ui: &mut Ui, has_exactly_one: bool, index: usize, packet: &Avo
                     "json",
                 );
             }
+
+            if let Some(method) = method {
+                ui.heading("Response Validation");
+
+                match method.validate_response(packet) {
+                    Ok(()) => {
+                        ui.label(format!("Valid {} response", method.name()));
+                    }
+                    Err(err) => {
+                        ui.colored_label(
+                            egui::Color32::RED,
+                            format!("Invalid {} response: {err}", method.name()),
+                        );
+                    }
+                }
+            }
         });
 }
 
 pub fn loaded_images(
     ui: &mut Ui,
-    dpi: f32,
+    device: &Device,
     canvas_size: Vec2,
+    safe_area: egui::Rect,
     loaded_images: &mut Vec<LoadedImage>,
+    tiling: &mut Option<TileLayout>,
 ) {
     ui.heading("Images");
 
-    let mut remove = None;
+    let mut action = None;
+    let dpi = device.dpi;
 
     ui.spacing_mut().scroll.floating = false;
 
+    let count = loaded_images.len();
     egui::ScrollArea::vertical()
         .auto_shrink([false, true])
         .show(ui, |ui| {
             for (index, image) in loaded_images.iter_mut().enumerate() {
-                image_controls(ui, dpi, canvas_size, image, index, &mut remove);
+                image_controls(ui, dpi, canvas_size, image, index, count, &mut action);
                 ui.add_space(16.0);
             }
         });
 
-    if let Some(remove) = remove {
-        loaded_images.remove(remove);
+    match action {
+        Some((index, ImageAction::Remove)) => {
+            loaded_images.remove(index);
+        }
+        Some((index, ImageAction::Duplicate)) => {
+            // Offset the copy by 1/8in so it's not hidden behind the original.
+            let duplicate = loaded_images[index].duplicate(ui.ctx(), dpi / 8.0);
+            loaded_images.insert(index + 1, duplicate);
+        }
+        Some((index, ImageAction::Tile)) => {
+            // Start with a small gap, 1/16in, so cut lines have room.
+            *tiling = Some(TileLayout::fill(
+                index,
+                loaded_images[index].size(),
+                safe_area,
+                device.average_dpi() / 16.0,
+            ));
+        }
+        Some((index, ImageAction::BringForward)) => loaded_images.swap(index, index + 1),
+        Some((index, ImageAction::SendBackward)) => loaded_images.swap(index, index - 1),
+        Some((index, ImageAction::BringToFront)) => loaded_images[index..].rotate_left(1),
+        Some((index, ImageAction::SendToBack)) => loaded_images[..=index].rotate_right(1),
+        None => (),
     }
 }
 
+/// An action on a loaded image that requires changing the list of images.
+///
+/// Images later in the list are drawn on top of earlier ones.
+#[derive(Clone, Copy, Debug)]
+pub enum ImageAction {
+    Remove,
+    Duplicate,
+    Tile,
+    BringForward,
+    SendBackward,
+    BringToFront,
+    SendToBack,
+}
+
 pub fn image_controls(
     ui: &mut Ui,
-    dpi: f32,
+    dpi: Vec2,
     canvas_size: Vec2,
     image: &mut LoadedImage,
     index: usize,
-    remove_index: &mut Option<usize>,
+    count: usize,
+    action: &mut Option<(usize, ImageAction)>,
 ) {
     ui.horizontal(|ui| {
         let (response, painter) = ui.allocate_painter(Vec2::splat(50.0), egui::Sense::empty());
@@ -295,7 +668,7 @@ pub fn image_controls(
         painter.image(
             image.sized_texture.id,
             response.rect,
-            egui::Rect::from_min_max(Pos2::new(0.0, 0.0), Pos2::new(1.0, 1.0)),
+            image.uv(),
             egui::Color32::WHITE,
         );
 
@@ -303,74 +676,141 @@ pub fn image_controls(
             ui.spacing_mut().interact_size.x = 72.0;
             ui.spacing_mut().item_spacing.y = 8.0;
 
-            ui.horizontal(|ui| {
-                ui.monospace("X:");
-                ui.add(px_slider(
-                    &mut image.offset.x,
-                    dpi,
-                    (-image.sized_texture.size.x * 2.0)
-                        ..=(canvas_size.x + image.sized_texture.size.x * 2.0),
-                ));
+            image_placement(ui, dpi, canvas_size, image);
 
-                ui.monospace("Y:");
-                ui.add(px_slider(
-                    &mut image.offset.y,
-                    dpi,
-                    (-image.sized_texture.size.y * 2.0)
-                        ..=(canvas_size.y + image.sized_texture.size.y * 2.0),
-                ));
+            ui.add(egui::Slider::new(&mut image.opacity, 0.0..=1.0).text("Opacity"));
+
+            ui.collapsing("Adjust", |ui| {
+                let mut adjustment = image.adjustment;
+                ui.add(
+                    egui::Slider::new(&mut adjustment.brightness, -1.0..=1.0).text("Brightness"),
+                );
+                ui.add(egui::Slider::new(&mut adjustment.contrast, 0.5..=2.0).text("Contrast"));
+                ui.add(egui::Slider::new(&mut adjustment.saturation, 0.0..=2.0).text("Saturation"));
+
+                if ui.small_button("Reset").clicked() {
+                    adjustment = Default::default();
+                }
+
+                image.set_adjustment(adjustment);
             });
 
-            ui.horizontal(|ui| {
-                ui.monospace("W:");
-                let mut width = image.size().x;
-                ui.add(px_slider(&mut width, dpi, 1.0..=(canvas_size.x * 10.0)));
-
-                if width != image.size().x {
-                    let new_scale = if image.scale_locked {
-                        width / image.size().x * image.scale
-                    } else {
-                        Vec2 {
-                            x: width / image.size().x * image.scale.x,
-                            ..image.scale
-                        }
-                    };
+            let is_front = index + 1 >= count;
+            let is_back = index == 0;
 
-                    image.rescale(new_scale);
+            ui.horizontal(|ui| {
+                for (enabled, label, image_action) in [
+                    (!is_front, "To Front", ImageAction::BringToFront),
+                    (!is_front, "Forward", ImageAction::BringForward),
+                    (!is_back, "Backward", ImageAction::SendBackward),
+                    (!is_back, "To Back", ImageAction::SendToBack),
+                ] {
+                    if ui
+                        .add_enabled(enabled, egui::Button::new(label).small())
+                        .clicked()
+                    {
+                        *action = Some((index, image_action));
+                    }
                 }
+            });
 
-                ui.monospace("H:");
-                let mut height = image.size().y;
-                ui.add(px_slider(&mut height, dpi, 1.0..=(canvas_size.y * 10.0)));
-
-                if height != image.size().y {
-                    let new_scale = if image.scale_locked {
-                        height / image.size().y * image.scale
-                    } else {
-                        Vec2 {
-                            y: height / image.size().y * image.scale.y,
-                            ..image.scale
-                        }
-                    };
+            ui.horizontal(|ui| {
+                if ui.small_button("Duplicate").clicked() {
+                    *action = Some((index, ImageAction::Duplicate));
+                }
 
-                    image.rescale(new_scale);
+                if ui
+                    .small_button("Tile")
+                    .on_hover_text("Fill the safe area with copies of this image")
+                    .clicked()
+                {
+                    *action = Some((index, ImageAction::Tile));
                 }
 
                 if ui
-                    .small_button(if image.scale_locked { "Unlock" } else { "Lock" })
+                    .small_button("Reset")
+                    .on_hover_text("Undo changes to the size, position, crop, and colors")
                     .clicked()
                 {
-                    image.scale_locked = !image.scale_locked;
+                    image.reset();
                 }
-            });
 
-            if ui.small_button("Remove").clicked() {
-                *remove_index = Some(index);
-            }
+                if image.crop().is_some() && ui.small_button("Reset Crop").clicked() {
+                    image.set_crop(None);
+                }
+
+                if ui.small_button("Remove").clicked() {
+                    *action = Some((index, ImageAction::Remove));
+                }
+            });
         });
     });
 }
 
+/// Edit the position and size of an image.
+pub fn image_placement(ui: &mut Ui, dpi: Vec2, canvas_size: Vec2, image: &mut LoadedImage) {
+    ui.horizontal(|ui| {
+        ui.monospace("X:");
+        ui.add(px_slider(
+            &mut image.offset.x,
+            dpi.x,
+            (-image.sized_texture.size.x * 2.0)
+                ..=(canvas_size.x + image.sized_texture.size.x * 2.0),
+        ));
+
+        ui.monospace("Y:");
+        ui.add(px_slider(
+            &mut image.offset.y,
+            dpi.y,
+            (-image.sized_texture.size.y * 2.0)
+                ..=(canvas_size.y + image.sized_texture.size.y * 2.0),
+        ));
+    });
+
+    ui.horizontal(|ui| {
+        ui.monospace("W:");
+        let mut width = image.size().x;
+        ui.add(px_slider(&mut width, dpi.x, 1.0..=(canvas_size.x * 10.0)));
+
+        if width != image.size().x {
+            let new_scale = if image.scale_locked {
+                width / image.size().x * image.scale
+            } else {
+                Vec2 {
+                    x: width / image.size().x * image.scale.x,
+                    ..image.scale
+                }
+            };
+
+            image.rescale(new_scale);
+        }
+
+        ui.monospace("H:");
+        let mut height = image.size().y;
+        ui.add(px_slider(&mut height, dpi.y, 1.0..=(canvas_size.y * 10.0)));
+
+        if height != image.size().y {
+            let new_scale = if image.scale_locked {
+                height / image.size().y * image.scale
+            } else {
+                Vec2 {
+                    y: height / image.size().y * image.scale.y,
+                    ..image.scale
+                }
+            };
+
+            image.rescale(new_scale);
+        }
+
+        if ui
+            .small_button(if image.scale_locked { "Unlock" } else { "Lock" })
+            .clicked()
+        {
+            image.scale_locked = !image.scale_locked;
+        }
+    });
+}
+
 pub fn px_slider<'a>(
     value: &'a mut f32,
     dpi: f32,
@@ -398,6 +838,7 @@ pub fn cut_controls(
     progress: Option<(usize, usize)>,
     has_intersections: bool,
     off_canvas: bool,
+    stale: bool,
 ) {
     ui.heading("Cut Preparation");
 
@@ -432,6 +873,16 @@ pub fn cut_controls(
     .on_hover_text("Minimum length to cut, anything smaller will be ignored");
     cut_tuning.minimum_length = minimum_length * dpi;
 
+    ui.add(
+        egui::Slider::new(&mut cut_tuning.blade_kerf, 0.05..=1.0)
+            .suffix(" mm")
+            .text("Blade Kerf"),
+    )
+    .on_hover_text("Width of the cut, used to show cut lines at their real size");
+
+    ui.checkbox(&mut cut_tuning.show_travel, "Show Travel Moves")
+        .on_hover_text("Show where the blade moves between cuts, in the order they're cut");
+
     ui.collapsing("Advanced Settings", |ui| {
         ui.add(egui::Slider::new(&mut cut_tuning.simplify, 0.0..=5.0).text("Simplify Amount"))
             .on_hover_text("Simplification epsilon, decreases total number of line segments");
@@ -446,11 +897,71 @@ pub fn cut_controls(
         })
         .response
         .on_hover_text("Increases number of smoothing iterations");
+
+        ui.horizontal(|ui| {
+            ui.add(
+                egui::DragValue::new(&mut cut_tuning.cleanup_radius)
+                    .range(0..=5)
+                    .speed(0.05)
+                    .suffix(" px"),
+            );
+            ui.label("Cleanup Radius");
+        })
+        .response
+        .on_hover_text("Remove specks and close small gaps in images before finding outlines");
+
+        ui.add(
+            egui::Slider::new(&mut cut_tuning.overlap_tolerance, 0.0..=5.0)
+                .suffix(" mm²")
+                .text("Overlap Tolerance"),
+        )
+        .on_hover_text("Area cut lines can overlap before they're considered overlapping");
+
+        let px_per_mm = dpi / 25.4;
+        let mut minimum_area = cut_tuning.minimum_area / (px_per_mm * px_per_mm);
+        ui.add(
+            egui::Slider::new(&mut minimum_area, 0.0..=50.0)
+                .suffix(" mm²")
+                .text("Minimum Cut Area"),
+        )
+        .on_hover_text("Minimum area to cut, drops thin lines that pass the minimum length");
+        cut_tuning.minimum_area = minimum_area * px_per_mm * px_per_mm;
+
+        ui.checkbox(&mut cut_tuning.promote_orphan_holes, "Promote Orphan Holes")
+            .on_hover_text("Cut holes that aren't inside a shape instead of ignoring them");
+
+        optional_value(
+            ui,
+            &mut cut_tuning.cut_pressure,
+            DEFAULT_CUT_PRESSURE,
+            "Blade Pressure",
+        )
+        .on_hover_text("Override how hard the blade presses, for thicker or thinner media");
+
+        optional_value(
+            ui,
+            &mut cut_tuning.cut_speed,
+            DEFAULT_CUT_SPEED,
+            "Cut Speed",
+        )
+        .on_hover_text("Override how fast the blade moves, slower can cut more cleanly");
+
+        ui.checkbox(&mut cut_tuning.preserve_corners, "Preserve Corners")
+            .on_hover_text("Keep sharp corners from being rounded by smoothing");
+
+        ui.add_enabled(
+            cut_tuning.preserve_corners,
+            egui::Slider::new(&mut cut_tuning.corner_angle, 10.0..=170.0)
+                .suffix("°")
+                .text("Corner Angle"),
+        )
+        .on_hover_text("Minimum turn for a point to be considered a corner");
     });
 
     let error_messages: Vec<_> = [
         has_intersections.then_some("Cut Lines Overlap"),
         off_canvas.then_some("Cut Lines Out of Bounds"),
+        stale.then_some("Cut Lines Out of Date, Regenerate"),
     ]
     .into_iter()
     .flatten()
@@ -477,3 +988,35 @@ pub fn cut_controls(
         });
     }
 }
+
+/// Edit a value that can be left unset to use a default.
+fn optional_value(ui: &mut Ui, value: &mut Option<u32>, default: u32, label: &str) -> Response {
+    ui.horizontal(|ui| {
+        let mut enabled = value.is_some();
+        let mut current = value.unwrap_or(default);
+
+        ui.checkbox(&mut enabled, label);
+        ui.add_enabled(enabled, egui::DragValue::new(&mut current).range(1..=100));
+
+        *value = enabled.then_some(current);
+    })
+    .response
+}
+
+/// Show why images didn't produce a cut, and what detail was dropped.
+pub fn cut_diagnostics(
+    ui: &mut Ui,
+    diagnostics: &[(usize, CutError)],
+    contour_stats: &ContourStats,
+) {
+    for (index, err) in diagnostics {
+        ui.colored_label(
+            ui.visuals().warn_fg_color,
+            format!("Image {} has no cut: {err}", index + 1),
+        );
+    }
+
+    for message in contour_stats.messages() {
+        ui.weak(message);
+    }
+}